@@ -0,0 +1,66 @@
+//! Overhead of writing through a [`Reopen`] compared to writing directly.
+//!
+//! Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::io::Write;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use reopen::Reopen;
+
+const LINE: &[u8] = b"A log line of a typical length, with some data in it: 42\n";
+
+fn write(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write");
+    group.bench_function("vec", |b| {
+        let mut writer = Vec::<u8>::new();
+        b.iter(|| {
+            writer.clear();
+            writer.write_all(black_box(LINE)).unwrap();
+        })
+    });
+    group.bench_function("reopen", |b| {
+        // No handle exists, so the request flag doesn't even need to be checked.
+        let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+        b.iter(|| {
+            writer.lock().unwrap().clear();
+            writer.write_all(black_box(LINE)).unwrap();
+        })
+    });
+    group.bench_function("reopen_with_handle", |b| {
+        // A handle exists, so someone might request a reopen at any time.
+        let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+        let _handle = writer.handle();
+        b.iter(|| {
+            writer.lock().unwrap().clear();
+            writer.write_all(black_box(LINE)).unwrap();
+        })
+    });
+    group.finish();
+}
+
+fn write_fmt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("write_fmt");
+    for &buffered in &[false, true] {
+        let name = if buffered { "buffered" } else { "plain" };
+        group.bench_function(name, |b| {
+            let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))
+                .unwrap()
+                .buffer_fmt(buffered);
+            b.iter(|| {
+                writer.lock().unwrap().clear();
+                writeln!(
+                    writer,
+                    "A log line no {} with {} in it",
+                    black_box(42),
+                    black_box("data")
+                )
+                .unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, write, write_fmt);
+criterion_main!(benches);
@@ -1,6 +1,51 @@
+use std::env;
+
 fn main() {
     let ac = autocfg::new();
-    ac.emit_path_cfg("std::io::Read::read_vectored", "vectored");
+    // A trait method can't be probed as a path, but the vectored IO came together with IoSlice.
+    ac.emit_path_cfg("std::io::IoSlice", "vectored");
+    ac.emit_path_cfg("std::convert::TryFrom", "try_from");
+    // Likewise, File::lock came together with TryLockError.
+    ac.emit_path_cfg("std::fs::TryLockError", "file_lock");
+
+    // The BorrowedBuf reading is nightly-only for now. Turn it on if the compiler accepts it.
+    println!("cargo:rustc-check-cfg=cfg(read_buf)");
+    let read_buf = r#"
+        #![feature(read_buf, core_io_borrowed_buf)]
+        use std::io::{BorrowedCursor, Read, Result};
+        pub fn probe<R: Read>(r: &mut R, buf: BorrowedCursor<'_>) -> Result<()> {
+            r.read_buf(buf)
+        }
+        pub fn probe_exact<R: Read>(r: &mut R, buf: BorrowedCursor<'_>) -> Result<()> {
+            r.read_buf_exact(buf)
+        }
+    "#;
+    if ac.probe_raw(read_buf).is_ok() {
+        autocfg::emit("read_buf");
+    }
+
+    // For the concurrency model checking (see src/shared.rs).
+    println!("cargo:rustc-check-cfg=cfg(loom)");
+
+    // The BSD family (including macOS) has both SIGINFO and kqueue.
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let bsd =
+        ["macos", "ios", "freebsd", "netbsd", "openbsd", "dragonfly"].contains(&target_os.as_str());
+    let loom = env::var_os("CARGO_CFG_LOOM").is_some();
+
+    // Status reporting on SIGINFO (see src/status.rs), where the signal exists.
+    println!("cargo:rustc-check-cfg=cfg(siginfo)");
+    let signals = env::var_os("CARGO_FEATURE_SIGNALS").is_some();
+    if bsd && signals && !loom {
+        autocfg::emit("siginfo");
+    }
+
+    // Watching the path by kqueue (see src/kqueue.rs).
+    println!("cargo:rustc-check-cfg=cfg(kqueue)");
+    let watch = env::var_os("CARGO_FEATURE_WATCH").is_some();
+    if bsd && watch && !loom {
+        autocfg::emit("kqueue");
+    }
 
     autocfg::rerun_path("build.rs");
 }
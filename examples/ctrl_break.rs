@@ -0,0 +1,51 @@
+//! Example of reopening a log file on Ctrl+Break (windows only).
+//!
+//! This program keeps writing messages into a file `log.txt`. If Ctrl+Break is pressed in its
+//! console, it reopens it.
+//!
+//! To demonstrate the effect:
+//!
+//! * Run the program.
+//! * Observe `log.txt` appeared and it is growing.
+//! * Rename the `log.txt` to some other file (`move log.txt log2.txt`), this works on windows
+//!   only because the file is opened with the sharing flags allowing it.
+//! * Press Ctrl+Break in the console of the program.
+//! * See `log2.txt` no longer grows, new `log.txt` appeared and grows.
+
+#[cfg(windows)]
+#[rustfmt::skip]
+mod example {
+
+use std::io::{Error, Write};
+use std::thread;
+use std::time::Duration;
+
+use reopen::Reopen;
+use reopen::windows::CtrlEvent;
+
+pub fn main() -> Result<(), Error> {
+    let mut log = Reopen::append_to("log.txt")?;
+    // Keep the guard alive, dropping it would unregister the handler.
+    let _guard = log.handle().register_console_ctrl(CtrlEvent::CtrlBreak)?;
+    let mut no = 1u128;
+    loop {
+        thread::sleep(Duration::from_secs(1));
+        writeln!(log, "Tick no {}", no)?;
+        no += 1;
+    }
+}
+
+}
+
+// The event doesn't exist elsewhere
+#[cfg(not(windows))]
+#[rustfmt::skip]
+mod example {
+pub fn main() -> Result<(), std::io::Error> {
+    Ok(())
+}
+}
+
+fn main() -> Result<(), std::io::Error> {
+    example::main()
+}
@@ -0,0 +1,62 @@
+//! Example of logging through fern into a file reopened on SIGHUP.
+//!
+//! This is the file logging example of fern, with the `File` replaced by a [`Reopen`]. Run it,
+//! move the `output.log` away and send it `SIGHUP` (`killall -SIGHUP fern`). The new messages
+//! go to a new `output.log`.
+//!
+//! # Features
+//!
+//! This relies on the `fern` and `signals` features.
+
+#[cfg(all(feature = "fern", feature = "signals"))]
+#[rustfmt::skip]
+mod example {
+
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+use log::{debug, info};
+use reopen::integrations::fern_output;
+use reopen::Reopen;
+
+#[cfg(windows)] // Windows has a very limited set of signals, but make it compile at least :-(
+use reopen::signals::consts::SIGINT as SIGHUP;
+#[cfg(not(windows))]
+use reopen::signals::consts::SIGHUP;
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+    let log = Reopen::append_to("output.log")?;
+    log.handle().register_signal(SIGHUP)?;
+    fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!("[{}][{}] {}", record.level(), record.target(), message))
+        })
+        .level(log::LevelFilter::Debug)
+        .chain(std::io::stdout())
+        .chain(fern_output(log))
+        .apply()?;
+
+    let mut no = 1u128;
+    loop {
+        thread::sleep(Duration::from_secs(1));
+        info!("Tick no {}", no);
+        debug!("Still going");
+        no += 1;
+    }
+}
+
+}
+
+// Version just to make compiler happy if features are not turned on
+#[cfg(not(all(feature = "fern", feature = "signals")))]
+#[rustfmt::skip]
+mod example {
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+}
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    example::main()
+}
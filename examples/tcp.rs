@@ -0,0 +1,53 @@
+//! Example of sending logs to a TCP collector, connecting again when it goes away.
+//!
+//! Run a collector (eg. `nc -lk 5140`) and this example, with the address as an argument
+//! (`localhost:5140` by default). Stop the collector and start it again, the messages resume
+//! flowing after a few get lost. The address is resolved again on each reconnect.
+//!
+//! # Features
+//!
+//! This relies on the `net` feature.
+
+#[cfg(feature = "net")]
+#[rustfmt::skip]
+mod example {
+
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+use reopen::net::tcp;
+use reopen::Reopen;
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+    let addr = env::args().nth(1).unwrap_or_else(|| "localhost:5140".to_owned());
+    let mut log = Reopen::from_fn(tcp(addr, Duration::from_secs(5), None))?
+        .reopen_on_disconnect(true)
+        .reopen_on_broken_pipe(true)
+        .buffer_fmt(true);
+    let mut no = 0;
+    loop {
+        if let Err(e) = writeln!(log, "Tick no {}", no) {
+            eprintln!("Tick no {} lost: {}", no, e);
+        }
+        no += 1;
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+}
+
+// Version just to make compiler happy if features are not turned on
+#[cfg(not(feature = "net"))]
+#[rustfmt::skip]
+mod example {
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+}
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    example::main()
+}
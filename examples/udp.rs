@@ -0,0 +1,63 @@
+//! Example of binding a UDP socket again on SIGUSR1.
+//!
+//! This program keeps sending messages to a collector at the address given as an argument
+//! (`127.0.0.1:5140` by default), from a socket bound to an address read from the
+//! `UDP_BIND` environment variable (`0.0.0.0:0` by default). Run a collector
+//! (`nc -lku 5140`), then this example. Sending it `SIGUSR1` (`killall -SIGUSR1 udp`) binds a
+//! new socket (eg. after a VPN reconnected and the address of the interface changed), the
+//! messages keep flowing from the new one.
+//!
+//! # Features
+//!
+//! This relies on the `net` and `signals` features (and works on unix only).
+
+#[cfg(all(feature = "net", feature = "signals", unix))]
+#[rustfmt::skip]
+mod example {
+
+use std::env;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::thread;
+use std::time::Duration;
+
+use reopen::net::udp_bound;
+use reopen::signals::consts::SIGUSR1;
+use reopen::Reopen;
+
+fn bind_addr() -> SocketAddr {
+    env::var("UDP_BIND")
+        .ok()
+        .and_then(|addr| addr.parse().ok())
+        .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 0)))
+}
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+    let target = env::args().nth(1).unwrap_or_else(|| "127.0.0.1:5140".to_owned());
+    let mut socket = Reopen::from_fn(udp_bound(bind_addr))?;
+    socket.handle().register_signal(SIGUSR1)?;
+    let mut no = 0;
+    loop {
+        let msg = format!("Tick no {} from {}\n", no, socket.lock()?.local_addr()?);
+        if let Err(e) = socket.send_to(msg.as_bytes(), &target[..]) {
+            eprintln!("Tick no {} lost: {}", no, e);
+        }
+        no += 1;
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+}
+
+// Version just to make compiler happy if features are not turned on
+#[cfg(not(all(feature = "net", feature = "signals", unix)))]
+#[rustfmt::skip]
+mod example {
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+}
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    example::main()
+}
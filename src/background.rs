@@ -0,0 +1,289 @@
+use std::io::{Error, ErrorKind};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SendError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use super::{clock, Constructor, Reopen};
+
+type Spawn<FD> = Box<dyn Fn(Arc<Mutex<Constructor<FD>>>) -> Receiver<Result<FD, Error>> + Send>;
+
+/// A request for the opener thread: the constructor to call and where to send the result.
+type Job<FD> = (Arc<Mutex<Constructor<FD>>>, Sender<Result<FD, Error>>);
+
+/// State of opening FDs in a helper thread.
+pub(crate) struct Background<FD> {
+    /// Runs the constructor in a helper thread.
+    ///
+    /// This is type-erased here, so the `FD: Send + 'static` bound is needed only when turning
+    /// the background opening on.
+    spawn: Spawn<FD>,
+    /// An open currently in progress and if it was requested (as opposed to opening after a
+    /// failure).
+    pending: Option<(Receiver<Result<FD, Error>>, bool)>,
+    /// How long to wait for the open if there's no old FD to use in the meantime.
+    ///
+    /// If not set, such opens are done synchronously.
+    pub(crate) wait: Option<Duration>,
+}
+
+/// Runs the constructors sent to it, one by one, until the [`Reopen`] goes away.
+fn opener<FD>(jobs: Receiver<Job<FD>>) {
+    for (constructor, result) in jobs {
+        let opened = panic::catch_unwind(AssertUnwindSafe(|| {
+            let constructor = constructor.lock().unwrap_or_else(PoisonError::into_inner);
+            constructor()
+        }));
+        // If the constructor panicked, the result sender is dropped, which is reported as such on
+        // the other end. Either way, the Reopen might be gone in the meantime, nothing to do
+        // about that.
+        if let Ok(opened) = opened {
+            let _ = result.send(opened);
+        }
+    }
+}
+
+impl<FD: Send + 'static> Reopen<FD> {
+    /// Opens the new FD in a helper thread and keeps using the old one until it is ready.
+    ///
+    /// Usually, the reopening happens at the next operation after it is requested and the
+    /// operation waits for the new FD to be opened. If opening is slow (eg. a network filesystem
+    /// or a remote connection), the operation is slowed down by that.
+    ///
+    /// When this is turned on, a reopen request starts opening the new FD in a new helper thread
+    /// and the operations keep using the old FD in the meantime. Once the new FD is ready, it
+    /// replaces the old one at the next operation.
+    ///
+    /// Some details:
+    ///
+    /// * The operations that would have to wait for an FD anyway (when there's no old one, eg.
+    ///   because of a failed [`write_record`][Reopen::write_record]) still open it
+    ///   synchronously. If an open is already in progress in the helper thread, they wait for
+    ///   that one instead of opening another FD.
+    /// * If the old FD returns errors in the meantime, these are returned to the caller as usual.
+    ///   The old FD is still replaced once the new one is ready.
+    /// * If the opening in the helper thread fails, the old FD stays in service and another
+    ///   attempt is started by the next operation.
+    /// * Further reopen requests while one is already in progress are merged into it.
+    ///
+    /// See [`background_open`][Reopen::background_open] for doing all the opens in a helper
+    /// thread. This is off by default.
+    pub fn double_buffered(mut self, enable: bool) -> Self {
+        if enable {
+            self.slow = true;
+            if self.background.is_none() {
+                self.background = Some(Background {
+                    spawn: Box::new(|constructor: Arc<Mutex<Constructor<FD>>>| {
+                        let (sender, receiver) = mpsc::channel();
+                        thread::spawn(move || {
+                            let constructor =
+                                constructor.lock().unwrap_or_else(PoisonError::into_inner);
+                            // The Reopen might be gone in the meantime, nothing to do about it.
+                            let _ = sender.send(constructor());
+                        });
+                        receiver
+                    }),
+                    pending: None,
+                    wait: None,
+                });
+            }
+        } else {
+            self.background = None;
+        }
+        self
+    }
+
+    /// Runs all the opens in a dedicated opener thread.
+    ///
+    /// This isolates the blocking work of the constructor (eg. resolving names, handshaking or
+    /// a hung network filesystem) from the threads doing the IO. Every call of the constructor
+    /// after this is turned on happens in a single long-lived helper thread, owned by this
+    /// [`Reopen`]. The thread is started on the first open and finishes when the [`Reopen`] is
+    /// dropped (the drop doesn't wait for it, so a constructor that blocks forever doesn't block
+    /// the drop, the thread is just left behind until the constructor returns). If the
+    /// constructor panics, the open is treated as failed and the thread keeps serving.
+    ///
+    /// The operations then:
+    ///
+    /// * Keep using the old FD while a new one is being opened after a reopen request (as with
+    ///   [`double_buffered`][Reopen::double_buffered]).
+    /// * If there's no old FD (eg. with [`lazy`][Reopen::lazy] or after a failed open), wait
+    ///   for the open at most for `wait` (measured by the [clock][Reopen::with_clock]). If it
+    ///   doesn't finish in time, the operation fails with [`TimedOut`][ErrorKind::TimedOut] and
+    ///   the open keeps running, a later operation picks up its result. The same goes for the
+    ///   other ways of opening (eg. [`block_until_openable`][Reopen::block_until_openable]), none
+    ///   of them calls the constructor directly, so none gets stuck with a constructor that
+    ///   hangs.
+    /// * Further requests while an open is in progress are merged into it, there's never more
+    ///   than one open in progress.
+    ///
+    /// Note that the first open of [`new`][Reopen::new] still happens in the calling thread
+    /// (before this can be turned on), use [`lazy`][Reopen::lazy] to avoid that.
+    ///
+    /// This replaces [`double_buffered`][Reopen::double_buffered] (and turning either off turns
+    /// off both). This is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Write;
+    /// # use std::time::Duration;
+    /// # use reopen::Reopen;
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::new())))
+    ///     .unwrap()
+    ///     .background_open(true, Duration::from_secs(1));
+    /// writer.handle().reopen();
+    /// writer.write_all(b"Hello").unwrap();
+    /// ```
+    pub fn background_open(mut self, enable: bool, wait: Duration) -> Self {
+        if enable {
+            self.slow = true;
+            let jobs: Mutex<Option<Sender<Job<FD>>>> = Mutex::new(None);
+            self.background = Some(Background {
+                spawn: Box::new(move |constructor: Arc<Mutex<Constructor<FD>>>| {
+                    let (sender, receiver) = mpsc::channel();
+                    let mut jobs = jobs.lock().unwrap_or_else(PoisonError::into_inner);
+                    let mut job = (constructor, sender);
+                    if let Some(running) = jobs.as_ref() {
+                        match running.send(job) {
+                            Ok(()) => return receiver,
+                            // The thread is gone, start a new one.
+                            Err(SendError(returned)) => job = returned,
+                        }
+                    }
+                    let (sender, incoming) = mpsc::channel();
+                    sender.send(job).expect("Fresh channel is open");
+                    thread::spawn(move || opener(incoming));
+                    *jobs = Some(sender);
+                    receiver
+                }),
+                pending: None,
+                wait: Some(wait),
+            });
+        } else {
+            self.background = None;
+        }
+        self
+    }
+}
+
+impl<FD> Reopen<FD> {
+    /// Starts opening a new FD in the background, unless already in progress.
+    pub(crate) fn start_background(&mut self, requested: bool) {
+        let in_progress = self
+            .background
+            .as_ref()
+            .expect("Background opening not enabled")
+            .pending
+            .is_some();
+        if !in_progress {
+            // If this fails, so does the constructor and that gets reported.
+            let _ = self.create_parents();
+            let background = self.background.as_mut().unwrap();
+            let receiver = (background.spawn)(Arc::clone(&self.constructor));
+            background.pending = Some((receiver, requested));
+        }
+    }
+
+    /// A background open is in progress and needs to be polled.
+    #[inline]
+    pub(crate) fn background_pending(&self) -> bool {
+        match self.background {
+            Some(ref background) => background.pending.is_some(),
+            None => false,
+        }
+    }
+
+    /// Checks if the background open is done and swaps the FD if so.
+    pub(crate) fn poll_background(&mut self) {
+        // The errors are already recorded and nobody waits for this one.
+        let _ = self.wait_background(Duration::from_secs(0));
+    }
+
+    /// Waits for the background open to finish (up to the timeout, if any) and takes its result.
+    ///
+    /// Returns nothing if there's no open in progress or if it didn't finish in time. The failure
+    /// is not recorded yet.
+    fn receive_background(&mut self, timeout: Option<Duration>) -> Option<Result<FD, Error>> {
+        let (result, requested) = match self.background.as_ref().and_then(|b| b.pending.as_ref()) {
+            Some(&(ref pending, requested)) => {
+                let result = match timeout {
+                    Some(timeout) => clock::recv_timeout(&*self.clock, pending, timeout),
+                    None => pending.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                (result, requested)
+            }
+            None => return None,
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => return None,
+            // Error::other is too new for our MSRV.
+            #[allow(clippy::io_other_error)]
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(Error::new(ErrorKind::Other, "Constructor panicked"))
+            }
+        };
+        self.background.as_mut().unwrap().pending = None;
+        if requested {
+            if result.is_ok() {
+                self.reopens += 1;
+            } else {
+                self.shared.restore_request();
+            }
+        }
+        Some(result)
+    }
+
+    /// Waits for the background open to finish (up to the timeout) and swaps the FD if so.
+    ///
+    /// Returns the error if the open finished, but failed.
+    pub(crate) fn wait_background(&mut self, timeout: Duration) -> Result<(), Error> {
+        match self.receive_background(Some(timeout)) {
+            Some(Ok(fd)) => {
+                self.close();
+                self.install(fd);
+                Ok(())
+            }
+            Some(Err(e)) => {
+                self.open_failed(&e);
+                Err(e)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Calls the constructor through the opener thread, waiting for it at most the given time.
+    ///
+    /// This is used instead of calling the constructor directly with
+    /// [`background_open`][Reopen::background_open]. The opener thread might be stuck in the
+    /// constructor (holding its lock), this must not get stuck with it.
+    pub(crate) fn construct_background(&mut self, wait: Duration) -> Result<FD, Error> {
+        self.start_background(false);
+        match self.receive_background(Some(wait)) {
+            Some(result) => result,
+            None => Err(Error::new(ErrorKind::TimedOut, "Open still in progress")),
+        }
+    }
+
+    /// Takes over the FD from the open in progress, waiting for it as long as it takes.
+    ///
+    /// This is used when the FD is needed right away and there's no old one to use in the
+    /// meantime. Calling the constructor would only wait for the lock the helper thread holds and
+    /// then open the FD a second time.
+    pub(crate) fn take_background(&mut self) -> Option<Result<FD, Error>> {
+        self.receive_background(None)
+    }
+
+    /// Opens the FD through the opener thread, waiting for it at most the given time.
+    pub(crate) fn open_background(&mut self, requested: bool, wait: Duration) -> Result<(), Error> {
+        self.start_background(requested);
+        self.wait_background(wait)?;
+        if self.fd.is_some() {
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::TimedOut, "Open still in progress"))
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::io::{Error, ErrorKind, Write};
+use std::mem;
+
+use super::{Handle, Reopen, Reopenable};
+
+impl<FD: Write> Reopen<FD> {
+    /// Wraps the [`Reopen`] into a buffer that respects the reopens.
+    ///
+    /// Wrapping a [`Reopen`] into a [`BufWriter`][std::io::BufWriter] puts the buffer outside of
+    /// the reopening. When a reopen is requested, the data still sitting in the buffer are
+    /// written only later, into the new FD, although they were written before the reopen. With
+    /// log rotation, the end of the old file ends up at the start of the new one.
+    ///
+    /// The [`BufferedReopen`] writes its buffer into the old FD before the reopen happens,
+    /// so the data end up where they belong.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?.wrap_buffered(8192);
+    /// writeln!(writer, "Hello")?;
+    /// writer.flush()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn wrap_buffered(self, capacity: usize) -> BufferedReopen<FD> {
+        BufferedReopen {
+            reopen: self,
+            buffer: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+}
+
+/// A buffered writer over [`Reopen`] that writes the buffer into the right FD.
+///
+/// Created by [`Reopen::wrap_buffered`]. The writes are collected in the buffer until it fills
+/// up (or until [`flush`][Write::flush]). Before each write or flush, if a reopen is pending, the
+/// buffer is first written into the current (old) FD and the reopen is done. If writing into the
+/// old FD fails, the data are kept and go into the new FD instead.
+///
+/// The buffer is written when this is dropped, but any errors are ignored then. Call
+/// [`flush`][Write::flush] to handle them.
+#[derive(Debug)]
+pub struct BufferedReopen<FD: Write> {
+    reopen: Reopen<FD>,
+    buffer: Vec<u8>,
+    capacity: usize,
+}
+
+impl<FD: Write> BufferedReopen<FD> {
+    /// The wrapped [`Reopen`].
+    pub fn get_ref(&self) -> &Reopen<FD> {
+        &self.reopen
+    }
+
+    /// The wrapped [`Reopen`].
+    ///
+    /// Writing into it directly bypasses the buffer.
+    pub fn get_mut(&mut self) -> &mut Reopen<FD> {
+        &mut self.reopen
+    }
+
+    /// Returns a handle to request a reopen of the wrapped [`Reopen`].
+    pub fn handle(&self) -> Handle {
+        self.reopen.handle()
+    }
+
+    /// The data waiting in the buffer.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Takes the data out of the buffer, without writing them.
+    ///
+    /// If writing the buffer fails (eg. the disk is full), the data stay in the buffer and are
+    /// tried again with the next write or flush. This allows to rescue them instead (eg. into an
+    /// emergency file elsewhere). Only the data not written yet are returned, the part that made
+    /// it into the FD before the failure is not.
+    ///
+    /// The buffer is empty afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?.wrap_buffered(8192);
+    /// writer.write_all(b"Hello")?;
+    /// assert_eq!(b"Hello", &writer.drain_buffered()[..]);
+    /// assert!(writer.buffered().is_empty());
+    /// # Ok(()) }
+    /// ```
+    pub fn drain_buffered(&mut self) -> Vec<u8> {
+        mem::replace(&mut self.buffer, Vec::with_capacity(self.capacity))
+    }
+
+    /// Writes the buffer into the old FD if a reopen is pending.
+    fn write_old(&mut self) {
+        if self.buffer.is_empty() || !self.reopen.shared.maybe_requested() {
+            return;
+        }
+        if let Some(fd) = self.reopen.fd.as_mut() {
+            if fd.write_all(&self.buffer).and_then(|()| fd.flush()).is_ok() {
+                self.reopen.written(self.buffer.len());
+                self.buffer.clear();
+            }
+        }
+    }
+
+    /// Writes the buffer into the old FD if a reopen is pending, then does the reopen.
+    ///
+    /// Doing the reopen right away makes sure the data buffered afterwards go to the new FD.
+    fn reopen_pending(&mut self) -> Result<(), Error> {
+        if !self.reopen.shared.maybe_requested() {
+            return Ok(());
+        }
+        self.write_old();
+        self.reopen.current().map(|_| ())
+    }
+
+    /// Writes the buffer through the [`Reopen`].
+    fn flush_buffer(&mut self) -> Result<(), Error> {
+        self.reopen_pending()?;
+        // Not write_all, on failure we need to know what stays in the buffer.
+        let mut written = 0;
+        let result = loop {
+            if written == self.buffer.len() {
+                break Ok(());
+            }
+            match self.reopen.write(&self.buffer[written..]) {
+                Ok(0) => {
+                    break Err(Error::new(
+                        ErrorKind::WriteZero,
+                        "failed to write the buffered data",
+                    ))
+                }
+                Ok(n) => written += n,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => (),
+                Err(e) => break Err(e),
+            }
+        };
+        self.buffer.drain(..written);
+        result
+    }
+}
+
+/// The buffer is written into the old FD first.
+impl<FD: Write> Reopenable for BufferedReopen<FD> {
+    fn handle(&self) -> Handle {
+        BufferedReopen::handle(self)
+    }
+
+    fn reopen_now(&mut self) -> Result<(), Error> {
+        self.reopen.request_inside();
+        self.write_old();
+        self.reopen.reopen_now()
+    }
+}
+
+impl<FD: Write> Write for BufferedReopen<FD> {
+    fn flush(&mut self) -> Result<(), Error> {
+        self.flush_buffer()?;
+        self.reopen.flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.reopen_pending()?;
+        if self.buffer.len() + buf.len() > self.capacity {
+            self.flush_buffer()?;
+        }
+        if buf.len() >= self.capacity {
+            self.reopen.write(buf)
+        } else {
+            self.buffer.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+}
+
+impl<FD: Write> Drop for BufferedReopen<FD> {
+    fn drop(&mut self) {
+        // No reason to open a new FD just to drop it.
+        self.write_old();
+        if !self.buffer.is_empty() {
+            // Nobody to report the error to.
+            let _ = self.reopen.write_all(&self.buffer);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Summarizing each file in a footer, for integrity checks.
+
+use std::io::{Error, Write};
+
+use super::Reopen;
+
+/// The CRC-32 (IEEE) of each nibble, to process the data half a byte at a time.
+const CRC_TABLE: [u32; 16] = [
+    0x0000_0000,
+    0x1db7_1064,
+    0x3b6e_20c8,
+    0x26d9_30ac,
+    0x76dc_4190,
+    0x6b6b_51f4,
+    0x4db2_6158,
+    0x5005_713c,
+    0xedb8_8320,
+    0xf00f_9344,
+    0xd6d6_a3e8,
+    0xcb61_b38c,
+    0x9b64_c2b0,
+    0x86d3_d2d4,
+    0xa00a_e278,
+    0xbdbd_f21c,
+];
+
+/// Writes into the inner writer, summarizing the data in a footer at the end.
+///
+/// This counts the bytes and lines written and computes their CRC-32 (the one of zip, gzip or
+/// `cksum -a crc32b`). When this is dropped (or [finished][ChecksumWriter::finish]), a single
+/// line with the summary is written at the end:
+///
+/// ```text
+/// # lines=2 bytes=12 crc32=dcf48607
+/// ```
+///
+/// The summary covers exactly the bytes accepted by the inner writer before the footer, not the
+/// footer itself. A checker can therefore strip the last line and compare. Note that when used
+/// in a [`Reopen`], the [header][Reopen::with_header] and [footer][Reopen::with_footer] are
+/// written through this too, so they are covered.
+///
+/// Usually used through [`Reopen::with_checksum_footer`].
+#[derive(Debug)]
+pub struct ChecksumWriter<W: Write> {
+    inner: W,
+    crc: u32,
+    lines: u64,
+    bytes: u64,
+    finished: bool,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    /// Wraps the writer.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            crc: !0,
+            lines: 0,
+            bytes: 0,
+            finished: false,
+        }
+    }
+
+    /// The wrapped writer.
+    ///
+    /// Writing into it directly bypasses the checksum.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Number of bytes written so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Number of lines (line ends) written so far.
+    pub fn lines(&self) -> u64 {
+        self.lines
+    }
+
+    /// The CRC-32 of the data written so far.
+    pub fn crc32(&self) -> u32 {
+        !self.crc
+    }
+
+    /// Writes the footer and flushes.
+    ///
+    /// This is done on drop too, but the errors are ignored there. After this, the footer is not
+    /// written again, but further writes are still passed through.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+        // Formatted first, to write it at once.
+        let footer = format!(
+            "# lines={} bytes={} crc32={:08x}\n",
+            self.lines,
+            self.bytes,
+            self.crc32()
+        );
+        self.inner.write_all(footer.as_bytes())?;
+        self.inner.flush()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.crc = CRC_TABLE[((self.crc ^ u32::from(byte)) & 0xf) as usize] ^ (self.crc >> 4);
+            self.crc =
+                CRC_TABLE[((self.crc ^ u32::from(byte >> 4)) & 0xf) as usize] ^ (self.crc >> 4);
+            if byte == b'\n' {
+                self.lines += 1;
+            }
+        }
+        self.bytes += data.len() as u64;
+    }
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let written = self.inner.write(buf)?;
+        self.update(&buf[..written]);
+        Ok(written)
+    }
+}
+
+impl<W: Write> Drop for ChecksumWriter<W> {
+    fn drop(&mut self) {
+        // Nobody to report the error to.
+        let _ = self.finish();
+    }
+}
+
+impl<FD: Write + 'static> Reopen<ChecksumWriter<FD>> {
+    /// Creates a [`Reopen`] ending each FD with a footer summarizing its content.
+    ///
+    /// Each FD opened by the constructor is wrapped into a [`ChecksumWriter`], which writes the
+    /// line and byte counts and the CRC-32 of the data as the last line, when the FD is closed
+    /// on reopen (or when the whole [`Reopen`] is dropped). See there for the details.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::with_checksum_footer(Box::new(|| Ok(Vec::<u8>::new())))?;
+    /// writeln!(writer, "Hello")?;
+    /// writer.lock()?.finish()?;
+    /// assert_eq!(
+    ///     b"Hello\n# lines=1 bytes=6 crc32=31963516\n",
+    ///     &writer.lock()?.get_ref()[..],
+    /// );
+    /// # Ok(()) }
+    /// ```
+    pub fn with_checksum_footer(
+        constructor: Box<dyn Fn() -> Result<FD, Error> + Send>,
+    ) -> Result<Self, Error> {
+        Self::from_fn(move || constructor().map(ChecksumWriter::new))
+    }
+}
@@ -0,0 +1,162 @@
+use std::cmp;
+use std::fmt::Arguments;
+use std::io::{Error, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::process::piped;
+use super::{Clock, RealClock, Reopen};
+
+/// Writes into the standard input of a child process.
+///
+/// This keeps the [`Child`] together with its [`ChildStdin`], so the process can be reaped when
+/// it's no longer needed. Dropping it closes the standard input (the child sees an end of file)
+/// and then waits for the child to exit. Therefore, dropping blocks until the child terminates; a
+/// child that doesn't exit on the end of its input blocks it forever, unless a grace period is
+/// set by [`kill_after`][ChildWriter::kill_after].
+///
+/// Usually used through [`Reopen::spawn_child`].
+#[derive(Debug)]
+pub struct ChildWriter {
+    /// Always `Some`, taken only in drop.
+    stdin: Option<ChildStdin>,
+    child: Child,
+    kill_after: Option<Duration>,
+    clock: Arc<dyn Clock>,
+}
+
+/// How often to check if the child exited during the grace period.
+const EXIT_POLL: Duration = Duration::from_millis(10);
+
+impl ChildWriter {
+    /// Spawns the command with a pipe to its standard input.
+    ///
+    /// The standard input of the command is overwritten, the rest of its configuration is kept.
+    pub fn spawn(command: &mut Command) -> Result<Self, Error> {
+        let mut child = command.stdin(Stdio::piped()).spawn()?;
+        let stdin = child.stdin.take().expect("Asked for a piped stdin");
+        Ok(Self {
+            stdin: Some(stdin),
+            child,
+            kill_after: None,
+            clock: Arc::new(RealClock),
+        })
+    }
+
+    /// Kills the child if it doesn't exit in time after its input is closed.
+    ///
+    /// When this is dropped (eg. on a reopen), the child gets the end of file and the given grace
+    /// period to exit on its own. If it is still running after that, it is killed (by `SIGKILL`
+    /// on unix). Either way, it is waited for, so by the time the drop finishes, the child is
+    /// gone. As the [`Reopen`] drops the old FD before opening a new one, the old child and its
+    /// replacement never run at the same time.
+    ///
+    /// The drop blocks for up to the grace period (and the time it takes to kill the child).
+    pub fn kill_after(mut self, grace: Duration) -> Self {
+        self.kill_after = Some(grace);
+        self
+    }
+
+    /// Sets the clock measuring the [grace period][ChildWriter::kill_after].
+    ///
+    /// By default, it's the [`RealClock`]. Usually, this is the same clock as given to
+    /// [`Reopen::with_clock`].
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Access to the child process (eg. to learn its PID).
+    pub fn child(&self) -> &Child {
+        &self.child
+    }
+
+    /// Mutable access to the child process (eg. to kill it).
+    pub fn child_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    fn stdin(&mut self) -> &mut ChildStdin {
+        self.stdin.as_mut().expect("Stdin taken before drop")
+    }
+}
+
+impl Write for ChildWriter {
+    fn flush(&mut self) -> Result<(), Error> {
+        self.stdin().flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.stdin().write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        self.stdin().write_all(buf)
+    }
+
+    fn write_fmt(&mut self, fmt: Arguments<'_>) -> Result<(), Error> {
+        self.stdin().write_fmt(fmt)
+    }
+}
+
+impl Drop for ChildWriter {
+    fn drop(&mut self) {
+        // Close the pipe first, so the child knows it should terminate.
+        self.stdin = None;
+        if let Some(grace) = self.kill_after {
+            let deadline = self.clock.now() + grace;
+            loop {
+                match self.child.try_wait() {
+                    Ok(Some(_)) => return,
+                    Ok(None) => {
+                        let now = self.clock.now();
+                        if now >= deadline {
+                            break;
+                        }
+                        self.clock.sleep(cmp::min(EXIT_POLL, deadline - now));
+                    }
+                    Err(_) => break,
+                }
+            }
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+impl Reopen<ChildWriter> {
+    /// Creates a [`Reopen`] writing into the standard input of a child process.
+    ///
+    /// The closure creates the command to spawn, both initially and on each reopen. The
+    /// [reopening on broken pipe][Reopen::reopen_on_broken_pipe] is turned on, so when the child
+    /// dies, the next write fails and the one after that spawns a new child. A reopen
+    /// (requested by a [`Handle`][crate::Handle]) also replaces the child by a new one.
+    ///
+    /// The old children are reaped when their [`ChildWriter`] is dropped, see there for the
+    /// details. To create the [`Reopen`] in other ways, use the
+    /// [`piped`] constructor directly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::{Error, Write};
+    /// # use std::process::Command;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut log = Reopen::spawn_child(|| {
+    ///     let mut command = Command::new("logger");
+    ///     command.arg("-t").arg("my-app");
+    ///     command
+    /// })?;
+    /// writeln!(log, "Hello")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn spawn_child<F>(command: F) -> Result<Self, Error>
+    where
+        F: Fn() -> Command + Send + 'static,
+    {
+        let reopen = Self::from_fn(piped(command))?;
+        Ok(reopen.reopen_on_broken_pipe(true))
+    }
+}
@@ -0,0 +1,160 @@
+//! Source of time for the time-based behavior.
+
+use std::fmt::Debug;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError};
+#[cfg(feature = "test-util")]
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A source of the current time.
+///
+/// Everything time-based in a [`Reopen`][crate::Reopen] asks the clock set by
+/// [`with_clock`][crate::Reopen::with_clock] for the current time instead of asking the system
+/// directly. This allows tests to control the time (see `ManualClock`, available with the
+/// `test-util` feature).
+pub trait Clock: Debug + Send + Sync {
+    /// The current monotonic time.
+    fn now(&self) -> Instant;
+
+    /// The current wall-clock time.
+    fn system_now(&self) -> SystemTime;
+
+    /// Waits for the given time.
+    ///
+    /// By default, this puts the current thread to sleep.
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+
+    /// The clock moves with the real time.
+    ///
+    /// The waits for other threads (with a timeout) are then left to the OS. Otherwise, they poll
+    /// and end once this clock passes the timeout, so a clock moved by hand (like `ManualClock`)
+    /// returns `false`. By default, this returns `true`.
+    fn is_real_time(&self) -> bool {
+        true
+    }
+}
+
+/// How often to check for a message when waiting for it by a clock not moving with real time.
+const RECV_POLL: Duration = Duration::from_millis(1);
+
+/// Waits for a message, at most the given time of the clock.
+///
+/// With a [real-time][Clock::is_real_time] clock, this is [`Receiver::recv_timeout`]. Otherwise,
+/// this polls for the message until it comes or until the clock is moved past the timeout (by
+/// someone else, sleeping on such clock may move it right away, without waiting for anything).
+pub(crate) fn recv_timeout<T>(
+    clock: &dyn Clock,
+    receiver: &Receiver<T>,
+    timeout: Duration,
+) -> Result<T, RecvTimeoutError> {
+    if clock.is_real_time() {
+        return receiver.recv_timeout(timeout);
+    }
+    let deadline = clock.now() + timeout;
+    loop {
+        match receiver.try_recv() {
+            Ok(msg) => return Ok(msg),
+            Err(TryRecvError::Disconnected) => return Err(RecvTimeoutError::Disconnected),
+            Err(TryRecvError::Empty) => (),
+        }
+        if clock.now() >= deadline {
+            return Err(RecvTimeoutError::Timeout);
+        }
+        thread::sleep(RECV_POLL);
+    }
+}
+
+/// The real system clock.
+///
+/// This is the default one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RealClock;
+
+impl Clock for RealClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that moves only when told to.
+///
+/// It starts at the time of creation and moves forward only by calling
+/// [`advance`][ManualClock::advance]. Sleeping on it doesn't wait, it just advances the clock.
+/// Waiting for another thread (eg. for a background open) with a timeout ends only when the
+/// other thread is done or when the clock gets advanced past the timeout.
+///
+/// # Features
+///
+/// This is available only with the `test-util` feature enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use reopen::{Clock, ManualClock};
+/// let clock = ManualClock::new();
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(10));
+/// assert_eq!(Duration::from_secs(10), clock.now() - start);
+/// ```
+#[cfg(feature = "test-util")]
+#[derive(Debug)]
+pub struct ManualClock {
+    start: Instant,
+    system_start: SystemTime,
+    elapsed: Mutex<Duration>,
+}
+
+#[cfg(feature = "test-util")]
+impl ManualClock {
+    /// Creates a clock set to the current time.
+    pub fn new() -> Self {
+        ManualClock {
+            start: Instant::now(),
+            system_start: SystemTime::now(),
+            elapsed: Mutex::new(Duration::from_secs(0)),
+        }
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+
+    fn elapsed(&self) -> Duration {
+        *self.elapsed.lock().unwrap()
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        self.system_start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+
+    fn is_real_time(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,161 @@
+//! Writing into compressed files.
+//!
+//! # Features
+//!
+//! The gzip files need the `flate2` feature, the zstd files need the `zstd` feature.
+
+use std::fs::File;
+#[cfg(feature = "serde")]
+use std::io;
+use std::io::{Error, Write};
+#[cfg(feature = "serde")]
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(feature = "flate2")]
+use flate2::write::GzEncoder;
+#[cfg(feature = "flate2")]
+use flate2::Compression;
+#[cfg(feature = "zstd")]
+use zstd::Encoder;
+
+use super::file::append_options;
+#[cfg(feature = "serde")]
+use super::rotation::Compress;
+use super::Reopen;
+
+/// Creates a [`Reopen`] writing into a gzip-compressed file.
+///
+/// The file is opened for appending (as in [`append_to`][Reopen::append_to]) and the data are
+/// compressed on the way. A gzip file is valid only once its trailer is written, so the old
+/// encoder is finished before it is closed, both on reopen and when the [`Reopen`] is dropped.
+///
+/// If the file already exists, a new gzip member is appended to it. That's still a valid gzip
+/// file, but it needs to be read by something that understands multiple members (eg. `zcat` or
+/// [`MultiGzDecoder`][flate2::read::MultiGzDecoder]).
+///
+/// Note that the encoder keeps some data inside until it has enough to compress, so not all of
+/// the data written reach the file right away. A crash leaves the file truncated (and without
+/// the trailer).
+///
+/// # Errors
+///
+/// If the file can't be opened.
+///
+/// # Features
+///
+/// This is available only with the `flate2` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # fn main() -> Result<(), Error> {
+/// let mut log = reopen::compress::gzip_file("/log/file.log.gz")?;
+/// writeln!(log, "Hello")?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "flate2")]
+pub fn gzip_file<P: Into<PathBuf>>(path: P) -> Result<Reopen<GzEncoder<File>>, Error> {
+    let path = path.into();
+    let options = append_options();
+    let mut reopen = Reopen::from_fn({
+        let path = path.clone();
+        move || {
+            let file = options.open(&path)?;
+            Ok(GzEncoder::new(file, Compression::default()))
+        }
+    })?;
+    reopen.close_hook = Some(Box::new(|encoder: &mut GzEncoder<File>| {
+        encoder.try_finish()?;
+        encoder.get_mut().flush()
+    }));
+    reopen.path = Some(path);
+    Ok(reopen)
+}
+
+/// Creates a [`Reopen`] writing into a zstd-compressed file.
+///
+/// The file is opened for appending (as in [`append_to`][Reopen::append_to]) and the data are
+/// compressed on the given `level` (`0` means the zstd default). The frame is finished before the
+/// file is closed, both on reopen and when the [`Reopen`] is dropped, so each file holds complete
+/// frames.
+///
+/// If the file already exists, a new frame is appended to it. The zstd format allows multiple
+/// frames in a file and the usual tools (`zstdcat`, [`zstd::stream::decode_all`]) read all of
+/// them.
+///
+/// The [`flush`][Write::flush] pushes the data compressed so far into the file, without finishing
+/// the frame. It costs some compression ratio, but after a crash, the data up to the last flush
+/// can still be decompressed (by a decoder tolerating the truncated frame).
+///
+/// # Errors
+///
+/// If the file can't be opened or the encoder can't be created (eg. the level is out of range).
+///
+/// # Features
+///
+/// This is available only with the `zstd` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # fn main() -> Result<(), Error> {
+/// let mut log = reopen::compress::zstd_file("/log/file.log.zst", 3)?;
+/// writeln!(log, "Hello")?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "zstd")]
+pub fn zstd_file<P: Into<PathBuf>>(
+    path: P,
+    level: i32,
+) -> Result<Reopen<Encoder<'static, File>>, Error> {
+    let path = path.into();
+    let options = append_options();
+    let mut reopen = Reopen::from_fn({
+        let path = path.clone();
+        move || {
+            let file = options.open(&path)?;
+            Encoder::new(file, level)
+        }
+    })?;
+    reopen.close_hook = Some(Box::new(|encoder: &mut Encoder<'static, File>| {
+        encoder.do_finish()?;
+        encoder.get_mut().flush()
+    }));
+    reopen.path = Some(path);
+    Ok(reopen)
+}
+
+/// Compresses a whole file by gzip.
+#[cfg(all(feature = "flate2", feature = "serde"))]
+fn gzip_copy(from: &Path, to: &Path) -> Result<(), Error> {
+    let mut source = File::open(from)?;
+    let mut encoder = GzEncoder::new(File::create(to)?, Compression::default());
+    io::copy(&mut source, &mut encoder)?;
+    encoder.finish()?.sync_all()
+}
+
+/// Compressing the rotated files by gzip.
+#[cfg(all(feature = "flate2", feature = "serde"))]
+pub(crate) const GZIP: Compress = Compress {
+    suffix: ".gz",
+    compress: gzip_copy,
+};
+
+/// Compresses a whole file by zstd (on the default level).
+#[cfg(all(feature = "zstd", feature = "serde"))]
+fn zstd_copy(from: &Path, to: &Path) -> Result<(), Error> {
+    let mut source = File::open(from)?;
+    let mut encoder = Encoder::new(File::create(to)?, 0)?;
+    io::copy(&mut source, &mut encoder)?;
+    encoder.finish()?.sync_all()
+}
+
+/// Compressing the rotated files by zstd.
+#[cfg(all(feature = "zstd", feature = "serde"))]
+pub(crate) const ZSTD: Compress = Compress {
+    suffix: ".zst",
+    compress: zstd_copy,
+};
@@ -0,0 +1,211 @@
+use std::fs::File;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+use super::compress;
+use super::rotation::Compress;
+use super::Reopen;
+
+fn default_record_retries() -> usize {
+    1
+}
+
+fn default_keep() -> usize {
+    5
+}
+
+/// How to compress the rotated files, see [`ReopenConfig::compression`].
+///
+/// Serialized in lowercase (eg. `"gzip"`).
+///
+/// # Features
+///
+/// This is available only with the `serde` feature enabled.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Compression {
+    /// The gzip format (`.gz` files), needs the `flate2` feature.
+    Gzip,
+    /// The zstd format (`.zst` files), needs the `zstd` feature.
+    Zstd,
+}
+
+/// A description of a [`Reopen<File>`][Reopen], loadable from a configuration file.
+///
+/// This describes how the file is opened and handled, it can be serialized and deserialized by
+/// [`serde`](https://serde.rs) and the [`build`][ReopenConfig::build] method creates the
+/// [`Reopen`] from it.
+///
+/// All the fields except the `path` are optional when deserializing.
+///
+/// # Features
+///
+/// This is available only with the `serde` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use reopen::ReopenConfig;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config: ReopenConfig = serde_json::from_str(r##"{
+///     "path": "/log/file",
+///     "header": "# Log format 2\n",
+///     "size_limit": 10485760,
+///     "keep": 3,
+///     "compression": "gzip",
+///     "signals": [1]
+/// }"##)?;
+/// let log = config.build()?;
+/// # let _ = log;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ReopenConfig {
+    /// The file to append to (see [`Reopen::append_to`]).
+    pub path: PathBuf,
+
+    /// Rotate the file once it grows to this many bytes.
+    ///
+    /// See [`Reopen::append_with_rotation`]. Without it, the file is never rotated by this crate
+    /// (but it can still be rotated by an external tool, eg. logrotate).
+    #[serde(default)]
+    pub size_limit: Option<u64>,
+
+    /// How many rotated files to keep, with the `size_limit`.
+    ///
+    /// Defaults to 5.
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+
+    /// Compress the rotated files, with the `size_limit`.
+    ///
+    /// The file is compressed into `path.1.gz` (or `path.1.zst`) when rotated. Building the
+    /// config fails if the feature for the compression isn't enabled.
+    ///
+    /// The compression runs synchronously, in the thread doing the reopen and while holding the
+    /// [`Reopen`]. Compressing a whole full file takes a while (with a large `size_limit`,
+    /// possibly seconds), during which the write that triggered the rotation (and anything else
+    /// waiting for the same [`Reopen`]) is blocked. Use a smaller `size_limit` or compress the
+    /// rotated files outside of the program if that is too much.
+    #[serde(default)]
+    pub compression: Option<Compression>,
+
+    /// See [`Reopen::with_header`].
+    #[serde(default)]
+    pub header: Option<String>,
+
+    /// See [`Reopen::with_footer`].
+    #[serde(default)]
+    pub footer: Option<String>,
+
+    /// See [`Reopen::reopen_on_write_zero`].
+    #[serde(default)]
+    pub reopen_on_write_zero: bool,
+
+    /// See [`Reopen::record_retries`].
+    #[serde(default = "default_record_retries")]
+    pub record_retries: usize,
+
+    /// Signal numbers to reopen on.
+    ///
+    /// This needs the `signals` feature, building a config with signals fails without it (and on
+    /// platforms without signals, like WASI).
+    #[serde(default)]
+    pub signals: Vec<i32>,
+}
+
+impl ReopenConfig {
+    /// Creates a configuration with the given path and everything else set to defaults.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        ReopenConfig {
+            path: path.into(),
+            size_limit: None,
+            keep: default_keep(),
+            compression: None,
+            header: None,
+            footer: None,
+            reopen_on_write_zero: false,
+            record_retries: default_record_retries(),
+            signals: Vec::new(),
+        }
+    }
+
+    /// Creates the [`Reopen`] described by this configuration.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be opened or the signals can't be registered. Also if the config asks
+    /// for something that isn't available (eg. compression without its feature or without the
+    /// `size_limit`).
+    ///
+    /// The signals are unregistered when the [`Reopen`] is dropped.
+    pub fn build(&self) -> Result<Reopen<File>, Error> {
+        let reopen = match self.size_limit {
+            Some(limit) => Reopen::rotating(self.path.clone(), limit, self.keep, self.compress()?)?,
+            None if self.compression.is_some() => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Compression of rotated files needs the size_limit",
+                ))
+            }
+            None => Reopen::append_to(&self.path)?,
+        };
+        let mut reopen = reopen
+            .reopen_on_write_zero(self.reopen_on_write_zero)
+            .record_retries(self.record_retries);
+        if let Some(header) = &self.header {
+            reopen = reopen.with_header(header.as_bytes());
+        }
+        if let Some(footer) = &self.footer {
+            reopen = reopen.with_footer(footer.as_bytes());
+        }
+        self.register_signals(&mut reopen)?;
+        Ok(reopen)
+    }
+
+    fn compress(&self) -> Result<Option<Compress>, Error> {
+        match self.compression {
+            None => Ok(None),
+            #[cfg(feature = "flate2")]
+            Some(Compression::Gzip) => Ok(Some(compress::GZIP)),
+            #[cfg(feature = "zstd")]
+            Some(Compression::Zstd) => Ok(Some(compress::ZSTD)),
+            #[allow(unreachable_patterns)]
+            Some(compression) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "{:?} compression needs its feature (flate2 or zstd)",
+                    compression
+                ),
+            )),
+        }
+    }
+
+    #[cfg(all(feature = "signals", not(loom)))]
+    fn register_signals(&self, reopen: &mut Reopen<File>) -> Result<(), Error> {
+        let handle = reopen.handle();
+        for &signal in &self.signals {
+            // Pushed one by one, so the ones registered before a failure are cleaned up too.
+            let id = handle.register_signal(signal)?;
+            reopen.signals.push(id);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(all(feature = "signals", not(loom))))]
+    fn register_signals(&self, _: &mut Reopen<File>) -> Result<(), Error> {
+        if self.signals.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Reopening on signals needs the signals feature",
+            ))
+        }
+    }
+}
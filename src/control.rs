@@ -0,0 +1,169 @@
+//! A tiny line-based protocol for controlling a [`Reopen`][crate::Reopen] from another process.
+//!
+//! The client sends one command per line and gets one line back for each:
+//!
+//! * `reopen`: Requests a reopen and waits for it to happen. Answers `ok` once the new FD is
+//!   open, `failed <error kind>` if opening it failed or `timeout` if nothing happened for too
+//!   long (the reopen happens on the next operation, so an idle [`Reopen`][crate::Reopen] doesn't
+//!   reopen).
+//! * `status`: Answers `pending` if a reopen was requested and didn't happen yet, `idle`
+//!   otherwise.
+//!
+//! Anything else is answered by `unknown command`.
+
+use std::io::{BufRead, Error, Write};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+use super::clock::{self, Clock};
+use super::events::ReopenEvent;
+use super::Handle;
+
+/// Waits for the reopen requested after subscribing.
+fn reopen(handle: &Handle, clock: &dyn Clock, timeout: Duration) -> String {
+    let events = handle.0.events.subscribe();
+    handle.reopen();
+    let deadline = clock.now() + timeout;
+    loop {
+        let now = clock.now();
+        if now >= deadline {
+            return "timeout".to_owned();
+        }
+        match clock::recv_timeout(clock, &events, deadline - now) {
+            Ok(ReopenEvent::OpenSucceeded { .. }) => return "ok".to_owned(),
+            Ok(ReopenEvent::OpenFailed { error_kind }) => {
+                return format!("failed {:?}", error_kind)
+            }
+            Ok(_) => (),
+            Err(RecvTimeoutError::Timeout) => return "timeout".to_owned(),
+            // The Reopen is gone, it'll never reopen.
+            Err(RecvTimeoutError::Disconnected) => return "failed gone".to_owned(),
+        }
+    }
+}
+
+/// Serves the commands of a single client, until it disconnects.
+///
+/// The `timeout` of the `reopen` command is measured by the `clock`.
+pub(crate) fn serve<R: BufRead, W: Write>(
+    handle: &Handle,
+    clock: &dyn Clock,
+    input: R,
+    mut output: W,
+    timeout: Duration,
+) -> Result<(), Error> {
+    for line in input.lines() {
+        let answer = match line?.trim() {
+            "reopen" => reopen(handle, clock, timeout),
+            "status" if handle.0.requested() => "pending".to_owned(),
+            "status" => "idle".to_owned(),
+            _ => "unknown command".to_owned(),
+        };
+        writeln!(output, "{}", answer)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Error, ErrorKind, Write};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    use super::*;
+    use crate::{ManualClock, RealClock, Reopen};
+
+    fn run_by(handle: &Handle, clock: &dyn Clock, commands: &str) -> String {
+        let mut output = Vec::new();
+        serve(
+            handle,
+            clock,
+            Cursor::new(commands),
+            &mut output,
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    fn run(handle: &Handle, commands: &str) -> String {
+        run_by(handle, &RealClock, commands)
+    }
+
+    #[test]
+    fn status_and_unknown() {
+        let handle = Handle::stub();
+        assert_eq!("idle\nunknown command\n", run(&handle, "status\nhello\n"));
+        handle.reopen();
+        assert_eq!("pending\n", run(&handle, "status\n"));
+    }
+
+    /// Nobody uses the Reopen, so it doesn't reopen.
+    #[test]
+    fn reopen_timeout() {
+        let reopen = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+        let clock = Arc::new(ManualClock::new());
+        let start = clock.now();
+        let done = Arc::new(AtomicBool::new(false));
+        // The time runs only when moved.
+        let mover = thread::spawn({
+            let clock = Arc::clone(&clock);
+            let done = Arc::clone(&done);
+            move || {
+                while !done.load(Ordering::Relaxed) {
+                    clock.advance(Duration::from_secs(1));
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+        });
+        assert_eq!(
+            "timeout\npending\n",
+            run_by(&reopen.handle(), &*clock, "reopen\nstatus\n")
+        );
+        done.store(true, Ordering::Relaxed);
+        mover.join().unwrap();
+        assert!(clock.now() - start >= Duration::from_secs(10));
+    }
+
+    fn busy_writer(fail: bool) -> (Handle, Arc<AtomicBool>, thread::JoinHandle<()>) {
+        let first = AtomicBool::new(true);
+        let mut writer = Reopen::new(Box::new(move || {
+            if fail && !first.swap(false, Ordering::Relaxed) {
+                Err(Error::new(ErrorKind::NotFound, "Not there"))
+            } else {
+                Ok(Vec::<u8>::new())
+            }
+        }))
+        .unwrap();
+        let handle = writer.handle();
+        let done = Arc::new(AtomicBool::new(false));
+        let thread = thread::spawn({
+            let done = Arc::clone(&done);
+            move || {
+                while !done.load(Ordering::Relaxed) {
+                    let _ = writer.write_all(b"x");
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+        });
+        (handle, done, thread)
+    }
+
+    #[test]
+    fn reopen_acknowledged() {
+        let (handle, done, thread) = busy_writer(false);
+        assert_eq!("ok\n", run(&handle, "reopen\n"));
+        done.store(true, Ordering::Relaxed);
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn reopen_failed() {
+        let (handle, done, thread) = busy_writer(true);
+        assert_eq!("failed NotFound\n", run(&handle, "reopen\n"));
+        done.store(true, Ordering::Relaxed);
+        thread.join().unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+//! Opening devices that may come and go.
+//!
+//! # Features
+//!
+//! This is available only with the `serial` feature enabled, on unix.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Error, ErrorKind};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// Checks if the name matches the pattern with `*` and `?` wildcards.
+fn matches(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            matches(rest, name) || (!name.is_empty() && matches(pattern, &name[1..]))
+        }
+        (Some((b'?', rest)), Some((_, name_rest))) => matches(rest, name_rest),
+        (Some((p, rest)), Some((n, name_rest))) if p == n => matches(rest, name_rest),
+        _ => false,
+    }
+}
+
+/// Finds the first (in alphabetical order) path matching the pattern.
+fn find(pattern: &Path) -> Result<PathBuf, Error> {
+    let (dir, name) = match (pattern.parent(), pattern.file_name()) {
+        (Some(dir), Some(name)) => (dir, name.as_bytes()),
+        _ => return Err(Error::new(ErrorKind::InvalidInput, "Not a path to a file")),
+    };
+    let mut found = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter(|entry| matches(name, entry.file_name().as_bytes()))
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    found.sort();
+    found
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "No device matches the pattern"))
+}
+
+/// Creates a constructor opening a serial device found by a pattern.
+///
+/// USB serial adapters get a new device node (eg. `/dev/ttyUSB1` instead of `/dev/ttyUSB0`)
+/// when they re-enumerate. Their stable names in `/dev/serial/by-id/` don't change, but they
+/// are links created only after the device appears and may contain parts (eg. the serial
+/// number) the program doesn't know up front. This looks for the device anew on each call of
+/// the constructor.
+///
+/// The last component of the pattern may contain the `*` (any number of any characters) and `?`
+/// (a single character) wildcards, the rest of the path is taken literally. If more devices
+/// match, the first one in the alphabetical order is used. The device is opened for both
+/// reading and writing and without becoming the controlling terminal. Then the `setup` is run
+/// on it to configure it (eg. the baud rate and raw mode through `termios`), as these settings
+/// are lost with the device.
+///
+/// A write to an adapter that was unplugged fails with `EIO` or `ENXIO`, which (unlike the
+/// errors of pipes or sockets) don't have a kind of their own. Pass them to
+/// [`reopen_on_os_errors`][crate::Reopen::reopen_on_os_errors] to look for the device again on
+/// the next operation. Until it appears again, the reopens fail with
+/// [`NotFound`][ErrorKind::NotFound] and are tried again on each operation.
+///
+/// # Features
+///
+/// This is available only with the `serial` feature enabled, on unix.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # use reopen::Reopen;
+/// # use reopen::device::serial_by_id;
+/// # fn main() -> Result<(), Error> {
+/// let mut telemetry = Reopen::from_fn(serial_by_id("/dev/serial/by-id/usb-FTDI_*", |_| {
+///     // Set the baud rate and such, eg. by the termios crate
+///     Ok(())
+/// }))?
+/// .reopen_on_os_errors(vec![libc::EIO, libc::ENXIO]);
+/// writeln!(telemetry, "temperature=21.5")?;
+/// # Ok(()) }
+/// ```
+pub fn serial_by_id<F>(pattern: &str, setup: F) -> impl Fn() -> Result<File, Error>
+where
+    F: Fn(&mut File) -> Result<(), Error>,
+{
+    let pattern = PathBuf::from(pattern);
+    move || {
+        let path = find(&pattern)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY)
+            .open(path)?;
+        setup(&mut file)?;
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn wildcards() {
+        assert!(matches(b"usb-FTDI_*", b"usb-FTDI_FT232R-if00-port0"));
+        assert!(matches(b"usb-*-port?", b"usb-FTDI-port0"));
+        assert!(matches(b"*", b""));
+        assert!(matches(b"exact", b"exact"));
+        assert!(!matches(b"usb-*-port?", b"usb-FTDI-port10"));
+        assert!(!matches(b"exact", b"exact2"));
+        assert!(!matches(b"?", b""));
+    }
+}
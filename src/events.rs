@@ -0,0 +1,73 @@
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Mutex;
+
+/// How many events can wait in a single subscriber's channel before new ones get dropped.
+pub(crate) const EVENT_CAPACITY: usize = 128;
+
+/// An event in the life of a [`Reopen`][crate::Reopen].
+///
+/// These are delivered to the receivers created by [`Reopen::subscribe`][crate::Reopen::subscribe].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReopenEvent {
+    /// A new FD was successfully opened.
+    ///
+    /// The generation is the number of reopens done so far, therefore the FD opened at
+    /// construction has generation `0`.
+    OpenSucceeded {
+        /// The generation of the newly opened FD.
+        generation: u64,
+    },
+    /// An attempt to open a new FD failed.
+    ///
+    /// Another attempt is made on the next operation.
+    OpenFailed {
+        /// The kind of the error the constructor returned.
+        error_kind: ErrorKind,
+    },
+    /// Someone asked for a reopen.
+    ///
+    /// Note that requests coming from a signal handler (see `Handle::register_signal`) can't
+    /// produce this event, as it's not possible to do so from within a signal handler.
+    ReopenRequested,
+    /// The FD was closed (dropped), either as part of a reopen or because the whole
+    /// [`Reopen`][crate::Reopen] was dropped.
+    Closed,
+}
+
+/// The registered receivers of [`ReopenEvent`]s.
+#[derive(Debug, Default)]
+pub(crate) struct Subscribers {
+    senders: Mutex<Vec<SyncSender<ReopenEvent>>>,
+    dropped: AtomicUsize,
+}
+
+impl Subscribers {
+    pub(crate) fn subscribe(&self) -> Receiver<ReopenEvent> {
+        let (sender, receiver) = mpsc::sync_channel(EVENT_CAPACITY);
+        self.senders.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Sends the event to all the subscribers.
+    ///
+    /// This never blocks. If a subscriber doesn't keep up, the event is dropped (and counted) for
+    /// it. Subscribers that went away are forgotten.
+    pub(crate) fn emit(&self, event: ReopenEvent) {
+        let mut senders = self.senders.lock().unwrap();
+        let dropped = &self.dropped;
+        senders.retain(|sender| match sender.try_send(event.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                dropped.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+
+    pub(crate) fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,91 @@
+//! Writing into named pipes (FIFOs) whose reader may come and go.
+//!
+//! # Features
+//!
+//! This is available only with the `fifo` feature enabled, on unix.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use super::Reopen;
+
+/// Opens a FIFO for writing, failing right away if nobody reads it.
+///
+/// A plain open for writing blocks until a reader shows up, possibly forever. This opens it in
+/// the non-blocking mode instead, which fails if there's no reader. That failure is reported
+/// with the [`NotConnected`][ErrorKind::NotConnected] kind (instead of the raw `ENXIO`). After
+/// the open succeeds, the FIFO is switched back to the blocking mode, so writes wait for the
+/// reader to catch up instead of failing with [`WouldBlock`][ErrorKind::WouldBlock].
+///
+/// This is the constructor used by [`writer`], usable on its own for more elaborate setups.
+///
+/// # Features
+///
+/// This is available only with the `fifo` feature enabled, on unix.
+#[allow(unsafe_code)]
+pub fn open(path: &Path) -> Result<File, Error> {
+    let file = OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+        .map_err(|e| {
+            if e.raw_os_error() == Some(libc::ENXIO) {
+                let msg = format!("Nobody reads the FIFO {}", path.display());
+                Error::new(ErrorKind::NotConnected, msg)
+            } else {
+                e
+            }
+        })?;
+    let fd = file.as_raw_fd();
+    // SAFETY: only reading the flags of a descriptor we own.
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags == -1 {
+        return Err(Error::last_os_error());
+    }
+    // SAFETY: only changing the flags of a descriptor we own.
+    if unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) } == -1 {
+        return Err(Error::last_os_error());
+    }
+    Ok(file)
+}
+
+/// Creates a [`Reopen`] writing into a FIFO, tolerating the reader to be gone for a while.
+///
+/// The FIFO is opened by [`open`] on the first use (see [`lazy`][Reopen::lazy]). While there's
+/// no reader, the opens fail with [`NotConnected`][ErrorKind::NotConnected] and each operation
+/// tries again. When the reader goes away, the write fails with
+/// [`BrokenPipe`][ErrorKind::BrokenPipe] and the FIFO is reopened before the next operation
+/// (see [`reopen_on_broken_pipe`][Reopen::reopen_on_broken_pipe]). There's no backoff, it is up
+/// to the caller how often to retry (or to drop the data meanwhile).
+///
+/// Note that the writes fail with [`BrokenPipe`][ErrorKind::BrokenPipe] only if the `SIGPIPE`
+/// signal is ignored, otherwise it kills the process. Rust programs ignore it by default.
+///
+/// The data written while the reader was leaving may be lost, as with any pipe.
+///
+/// # Features
+///
+/// This is available only with the `fifo` feature enabled, on unix.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::Write;
+/// let mut events = reopen::fifo::writer("/run/collector/events");
+/// if let Err(e) = writeln!(events, "Something happened") {
+///     eprintln!("Event lost: {}", e);
+/// }
+/// ```
+pub fn writer<P: Into<PathBuf>>(path: P) -> Reopen<File> {
+    let path = path.into();
+    let mut reopen = Reopen::lazy(Box::new({
+        let path = path.clone();
+        move || open(&path)
+    }))
+    .reopen_on_broken_pipe(true);
+    reopen.path = Some(path);
+    reopen
+}
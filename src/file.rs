@@ -0,0 +1,204 @@
+#[cfg(try_from)]
+use std::convert::TryFrom;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Error, ErrorKind};
+#[cfg(unix)]
+use std::os::unix::fs::FileExt;
+#[cfg(windows)]
+use std::os::windows::fs::OpenOptionsExt;
+use std::path::Path;
+#[cfg(try_from)]
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use super::Reopen;
+
+// The values from the windows API, stable forever. Not worth making windows-sys mandatory for the
+// three constants.
+#[cfg(windows)]
+const FILE_SHARE_READ: u32 = 0x1;
+#[cfg(windows)]
+const FILE_SHARE_WRITE: u32 = 0x2;
+#[cfg(windows)]
+const FILE_SHARE_DELETE: u32 = 0x4;
+
+impl Reopen<File> {
+    /// Creates a [`Reopen`] appending to a file at the given path.
+    ///
+    /// The file is created if it doesn't exist (both initially and on each reopen).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut log = Reopen::append_to("/log/file")?;
+    /// writeln!(log, "Hello")?;
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Platform specifics
+    ///
+    /// On windows, the file is opened with the `FILE_SHARE_DELETE` share mode (in addition to
+    /// reading and writing), so other programs can rename or delete it while it is open. Without
+    /// it, rotating the file by an external tool fails with "file in use". Use
+    /// [`append_with`][Reopen::append_with] to open it without.
+    pub fn append_to<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::append_with(path, append_options())
+    }
+
+    /// Creates a [`Reopen`] appending to a file at the given path, without opening it yet.
+    ///
+    /// This is the same as [`append_to`][Reopen::append_to], but the file is opened on the first
+    /// use (see [`lazy`][Reopen::lazy]).
+    pub fn lazy_append_to<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let options = append_options();
+        let mut reopen = Self::lazy(Box::new({
+            let path = path.clone();
+            move || options.open(&path)
+        }));
+        reopen.path = Some(path);
+        reopen
+    }
+
+    /// Creates the parent directories of the file before each open.
+    ///
+    /// If the directory the file lives in doesn't exist, it is created (including all the
+    /// missing directories above it) before each attempt to open the file. This is useful for
+    /// logging into directories that don't exist yet, eg. in a fresh container or with
+    /// date-based paths.
+    ///
+    /// This works only with the path-based helpers (eg. [`append_to`][Reopen::append_to]), as
+    /// otherwise the path is not known. Note that [`append_to`][Reopen::append_to] opens the file
+    /// right away, before this can be set, therefore this is best combined with
+    /// [`lazy_append_to`][Reopen::lazy_append_to].
+    ///
+    /// This is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut log = Reopen::lazy_append_to("/log/2024/01/app.log").with_create_parents(true);
+    /// writeln!(log, "Hello")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn with_create_parents(mut self, enable: bool) -> Self {
+        self.create_parents = enable;
+        self
+    }
+
+    /// Creates a [`Reopen`] opening the file at the given path with the given options.
+    ///
+    /// The options are used as they are, both initially and on each reopen. Unlike with
+    /// [`append_to`][Reopen::append_to], nothing platform specific is added.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::fs::OpenOptions;
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut options = OpenOptions::new();
+    /// options.create(true).append(true);
+    /// // On windows, this is opened without FILE_SHARE_DELETE.
+    /// let mut log = Reopen::append_with("/log/file", options)?;
+    /// writeln!(log, "Hello")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn append_with<P: AsRef<Path>>(path: P, options: OpenOptions) -> Result<Self, Error> {
+        let path = path.as_ref().to_path_buf();
+        let mut reopen = Self::new(Box::new({
+            let path = path.clone();
+            move || options.open(&path)
+        }))?;
+        reopen.path = Some(path);
+        Ok(reopen)
+    }
+
+    /// Writes at the given offset, without moving the position in the file.
+    ///
+    /// This is the [`FileExt::write_at`] of the current file, reopening it first if requested.
+    ///
+    /// # Platform support
+    ///
+    /// This is available only on unix.
+    #[cfg(unix)]
+    pub fn write_at(&mut self, buf: &[u8], offset: u64) -> Result<usize, Error> {
+        let result = self.current()?.write_at(buf, offset);
+        if let Ok(written) = result {
+            self.written(written);
+        }
+        result
+    }
+
+    /// Reads from the given offset, without moving the position in the file.
+    ///
+    /// This is the [`FileExt::read_at`] of the current file, reopening it first if requested.
+    ///
+    /// # Platform support
+    ///
+    /// This is available only on unix.
+    #[cfg(unix)]
+    pub fn read_at(&mut self, buf: &mut [u8], offset: u64) -> Result<usize, Error> {
+        self.current()?.read_at(buf, offset)
+    }
+}
+
+impl<FD> Reopen<FD> {
+    /// Creates the parent directories of the path, if turned on.
+    pub(crate) fn create_parents(&self) -> Result<(), Error> {
+        if !self.create_parents {
+            return Ok(());
+        }
+        let parent = match self.path.as_ref().and_then(|path| path.parent()) {
+            Some(parent) if parent != Path::new("") => parent,
+            _ => return Ok(()),
+        };
+        match fs::create_dir_all(parent) {
+            // Someone else was faster, that's fine.
+            Err(ref e) if e.kind() == ErrorKind::AlreadyExists => Ok(()),
+            result => result,
+        }
+    }
+}
+
+/// The options of [`Reopen::append_to`].
+pub(crate) fn append_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(windows)]
+    options.share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE);
+    options
+}
+
+/// Same as [`Reopen::append_to`].
+#[cfg(try_from)]
+impl TryFrom<PathBuf> for Reopen<File> {
+    type Error = Error;
+    fn try_from(path: PathBuf) -> Result<Self, Error> {
+        Self::append_to(path)
+    }
+}
+
+/// Same as [`Reopen::append_to`].
+#[cfg(try_from)]
+impl<'a> TryFrom<&'a Path> for Reopen<File> {
+    type Error = Error;
+    fn try_from(path: &'a Path) -> Result<Self, Error> {
+        Self::append_to(path)
+    }
+}
+
+/// Same as [`Reopen::append_to`], the string is the path.
+impl FromStr for Reopen<File> {
+    type Err = Error;
+    fn from_str(path: &str) -> Result<Self, Error> {
+        Self::append_to(path)
+    }
+}
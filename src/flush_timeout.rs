@@ -0,0 +1,100 @@
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use super::{clock, trace, Reopen};
+
+type Finish<FD> = Box<dyn Fn(FD) -> Receiver<()> + Send>;
+
+/// State of flushing the old FDs with a time limit.
+pub(crate) struct FlushTimeout<FD> {
+    pub(crate) timeout: Duration,
+    /// Flushes and drops the FD in a new thread, reporting when done.
+    ///
+    /// Type-erased, so the `FD: Write + Send + 'static` bounds are needed only when turning this
+    /// on.
+    finish: Finish<FD>,
+    /// How many flushes didn't finish in time.
+    pub(crate) timeouts: u64,
+}
+
+impl<FD: Write + Send + 'static> Reopen<FD> {
+    /// Limits the time spent flushing the old FD on a reopen.
+    ///
+    /// Usually, the old FD is simply dropped when replaced (flushing whatever it buffers, eg. in
+    /// case of a [`BufWriter`][std::io::BufWriter]). If the flush hangs (eg. a stuck network
+    /// filesystem or a remote peer not reading), so does the reopen and the operation waiting
+    /// for it.
+    ///
+    /// When this is set, the old FD is flushed and dropped in a helper thread and the reopen
+    /// waits for it at most for the given time (measured by the [clock][Reopen::with_clock]). If
+    /// the flush takes longer, the reopen proceeds anyway and the helper thread is left to finish
+    /// on its own. Such overruns are counted (see [`flush_timeouts`][Reopen::flush_timeouts])
+    /// and logged with the `log` or `tracing` features. The same applies to the last FD when the
+    /// [`Reopen`] is dropped.
+    ///
+    /// The close hook (eg. writing a [footer][Reopen::with_footer]) still runs before that, in
+    /// the current thread and without the limit. Any errors of the flush are ignored, as when
+    /// dropping the FD.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{BufWriter, Write};
+    /// # use std::time::Duration;
+    /// # use reopen::Reopen;
+    /// let mut writer = Reopen::new(Box::new(|| Ok(BufWriter::new(Vec::new()))))
+    ///     .unwrap()
+    ///     .flush_timeout(Duration::from_secs(5));
+    /// writer.handle().reopen();
+    /// writer.write_all(b"Hello").unwrap();
+    /// assert_eq!(0, writer.flush_timeouts());
+    /// ```
+    pub fn flush_timeout(mut self, timeout: Duration) -> Self {
+        self.flush_timeout = Some(FlushTimeout {
+            timeout,
+            finish: Box::new(|mut fd: FD| {
+                let (sender, receiver) = mpsc::channel();
+                thread::spawn(move || {
+                    let _ = fd.flush();
+                    drop(fd);
+                    // The Reopen might have given up on us already.
+                    let _ = sender.send(());
+                });
+                receiver
+            }),
+            timeouts: 0,
+        });
+        self
+    }
+}
+
+impl<FD> Reopen<FD> {
+    /// How many times flushing the old FD took longer than the
+    /// [`flush_timeout`][Reopen::flush_timeout].
+    pub fn flush_timeouts(&self) -> u64 {
+        self.flush_timeout.as_ref().map(|f| f.timeouts).unwrap_or(0)
+    }
+
+    /// Gets rid of the old FD, within the time limit if one is set.
+    pub(crate) fn finish(&mut self, fd: FD) {
+        let flush = match self.flush_timeout.as_mut() {
+            Some(flush) => flush,
+            None => {
+                drop(fd);
+                return;
+            }
+        };
+        let finished = (flush.finish)(fd);
+        match clock::recv_timeout(&*self.clock, &finished, flush.timeout) {
+            // Disconnected means the flush panicked, but it's done either way.
+            Ok(()) | Err(RecvTimeoutError::Disconnected) => (),
+            Err(RecvTimeoutError::Timeout) => {
+                flush.timeouts += 1;
+                let timeout = flush.timeout;
+                trace::flush_timed_out(self.label(), timeout);
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Guarding against filling up the disk.
+
+#[cfg(all(any(unix, windows), feature = "free-space"))]
+use std::fs::File;
+use std::io::{Error, ErrorKind};
+#[cfg(all(any(unix, windows), feature = "free-space"))]
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::Reopen;
+
+pub(crate) const LOW_FREE_SPACE: &str = "disk full guard: free space below the minimum";
+
+/// What to do when the free space drops below the minimum.
+///
+/// See [`with_min_free_space_by`][Reopen::with_min_free_space_by].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LowSpaceAction {
+    /// Request a reopen.
+    ///
+    /// This is useful if the constructor makes some space (eg. removes the oldest rotated files).
+    Reopen,
+
+    /// Make the operations fail.
+    ///
+    /// The [`Reopen`] is [poisoned][Reopen::is_poisoned] until there's enough space again.
+    Refuse,
+}
+
+type Query = Box<dyn Fn() -> Result<u64, Error> + Send>;
+
+/// State of watching the free space.
+pub(crate) struct SpaceGuard {
+    min: u64,
+    interval: Duration,
+    action: LowSpaceAction,
+    query: Query,
+    /// When to check the space next time.
+    next: Option<Instant>,
+    /// The space was below the minimum at the last check.
+    low: bool,
+}
+
+impl<FD> Reopen<FD> {
+    /// Watches the free space, reacting when it drops below the minimum.
+    ///
+    /// Once in the given interval, an operation asks the `query` how many bytes are free (on the
+    /// disk the FD writes to). If it is less than `min`, the `action` is taken:
+    ///
+    /// * With [`LowSpaceAction::Reopen`], a reopen is requested, which then happens right away in
+    ///   the same operation. This happens only once when the space drops below the minimum, the
+    ///   next reopen is requested only after it gets above it again and drops again.
+    /// * With [`LowSpaceAction::Refuse`], the operations fail until a later check finds enough
+    ///   space again.
+    ///
+    /// If the query fails, the result of the last check stays in effect.
+    ///
+    /// Asking for the free space is a system call, the interval allows to do it only once in a
+    /// while instead of on every operation. But every operation still reads the
+    /// [clock][Reopen::with_clock] to find out if the interval is up.
+    ///
+    /// For files opened by the path-based helpers (like [`append_to`][Reopen::append_to]), there's
+    /// [`with_min_free_space`][Reopen::with_min_free_space] asking the OS.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use std::time::Duration;
+    /// # use reopen::{LowSpaceAction, Reopen};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?.with_min_free_space_by(
+    ///     1024 * 1024,
+    ///     Duration::from_secs(1),
+    ///     LowSpaceAction::Refuse,
+    ///     || Ok(0),
+    /// );
+    /// assert!(writer.write_all(b"Hello").is_err());
+    /// assert!(writer.is_poisoned());
+    /// # Ok(()) }
+    /// ```
+    pub fn with_min_free_space_by<F>(
+        mut self,
+        min: u64,
+        interval: Duration,
+        action: LowSpaceAction,
+        query: F,
+    ) -> Self
+    where
+        F: Fn() -> Result<u64, Error> + Send + 'static,
+    {
+        self.slow = true;
+        self.space_guard = Some(SpaceGuard {
+            min,
+            interval,
+            action,
+            query: Box::new(query),
+            next: None,
+            low: false,
+        });
+        self
+    }
+
+    /// It's time to check the free space or the operations are to be refused.
+    #[inline]
+    pub(crate) fn free_space_due(&self, now: Instant) -> bool {
+        match self.space_guard {
+            Some(ref guard) => {
+                let due = match guard.next {
+                    Some(next) => now >= next,
+                    None => true,
+                };
+                due || (guard.low && guard.action == LowSpaceAction::Refuse)
+            }
+            None => false,
+        }
+    }
+
+    /// Checks the free space if it's time to.
+    ///
+    /// Fails if the operations are to be refused.
+    pub(crate) fn poll_free_space(&mut self) -> Result<(), Error> {
+        let now = self.clock.now();
+        let dropped = match self.space_guard.as_mut() {
+            Some(guard) => {
+                let due = match guard.next {
+                    Some(next) => now >= next,
+                    None => true,
+                };
+                let mut dropped = false;
+                if due {
+                    guard.next = Some(now + guard.interval);
+                    if let Ok(free) = (guard.query)() {
+                        let low = free < guard.min;
+                        dropped = low && !guard.low;
+                        guard.low = low;
+                    }
+                }
+                if guard.low && guard.action == LowSpaceAction::Refuse {
+                    return Err(low_space_error());
+                }
+                dropped && guard.action == LowSpaceAction::Reopen
+            }
+            None => return Ok(()),
+        };
+        if dropped {
+            self.request_inside();
+        }
+        Ok(())
+    }
+
+    /// The operations are refused because of low space.
+    pub(crate) fn space_refused(&self) -> bool {
+        match self.space_guard.as_ref() {
+            Some(guard) => guard.low && guard.action == LowSpaceAction::Refuse,
+            None => false,
+        }
+    }
+
+    /// Makes the next operation check the space.
+    pub(crate) fn recheck_space(&mut self) {
+        if let Some(guard) = self.space_guard.as_mut() {
+            guard.next = None;
+        }
+    }
+}
+
+#[cfg(all(any(unix, windows), feature = "free-space"))]
+impl Reopen<File> {
+    /// Watches the free space on the disk with the file.
+    ///
+    /// This is the same as [`with_min_free_space_by`][Reopen::with_min_free_space_by], asking the
+    /// OS about the free space on the disk with the directory of the file (`statvfs` on unix,
+    /// `GetDiskFreeSpaceExW` on windows). The space available to unprivileged users is what
+    /// counts.
+    ///
+    /// The file needs to be opened by one of the path-based helpers (like
+    /// [`append_to`][Reopen::append_to]). Otherwise the path is not known and this has no
+    /// effect.
+    ///
+    /// # Platform support
+    ///
+    /// This is available on unix and windows with the `free-space` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::{Error, Write};
+    /// # use std::time::Duration;
+    /// # use reopen::{LowSpaceAction, Reopen};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut log = Reopen::append_to("/log/file")?.with_min_free_space(
+    ///     100 * 1024 * 1024,
+    ///     Duration::from_secs(10),
+    ///     LowSpaceAction::Refuse,
+    /// );
+    /// writeln!(log, "Hello")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn with_min_free_space(self, min: u64, interval: Duration, action: LowSpaceAction) -> Self {
+        let dir = self.path.as_ref().map(|path| match path.parent() {
+            Some(parent) if parent != Path::new("") => parent.to_path_buf(),
+            _ => Path::new(".").to_path_buf(),
+        });
+        self.with_min_free_space_by(min, interval, action, move || match dir {
+            Some(ref dir) => free_space(dir),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                "Path of the file not known",
+            )),
+        })
+    }
+}
+
+/// The space available to unprivileged users on the disk with the path.
+#[cfg(all(unix, feature = "free-space"))]
+#[allow(unsafe_code)]
+fn free_space(path: &Path) -> Result<u64, Error> {
+    use std::ffi::CString;
+    use std::mem;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+    // SAFETY: all zeroes is a valid statvfs, which the call then fills in.
+    let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+    // SAFETY: the path is zero-terminated and the stat is valid for writing.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(Error::last_os_error());
+    }
+    // The types differ between platforms.
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// The space available to the current user on the disk with the path.
+#[cfg(all(windows, feature = "free-space"))]
+#[allow(unsafe_code)]
+fn free_space(path: &Path) -> Result<u64, Error> {
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr;
+
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let path = path
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<_>>();
+    let mut free = 0;
+    // SAFETY: the path is zero-terminated and we ask only for one number.
+    let ok =
+        unsafe { GetDiskFreeSpaceExW(path.as_ptr(), &mut free, ptr::null_mut(), ptr::null_mut()) };
+    if ok == 0 {
+        return Err(Error::last_os_error());
+    }
+    Ok(free)
+}
+
+// Error::other is too new for our MSRV.
+#[allow(clippy::io_other_error)]
+fn low_space_error() -> Error {
+    Error::new(ErrorKind::Other, LOW_FREE_SPACE)
+}
@@ -0,0 +1,79 @@
+use std::io::{Error, Read};
+
+use super::Reopen;
+
+/// A [`Reopen`] reader for which EOF is final.
+///
+/// Created by [`Reopen::fuse`]. Once a read reaches EOF (returns `Ok(0)` for a non-empty
+/// buffer), all further reads return `Ok(0)` too, even if a reopen would make the data readable
+/// again. This is the usual behaviour of readers, for consumers relying on it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::{Cursor, Error, Read};
+/// # use reopen::Reopen;
+/// # fn main() -> Result<(), Error> {
+/// let mut reader = Reopen::new(Box::new(|| Ok(Cursor::new(b"hello".to_vec()))))?.fuse();
+/// let handle = reader.get_ref().handle();
+/// let mut content = String::new();
+/// reader.read_to_string(&mut content)?;
+/// handle.reopen();
+/// reader.read_to_string(&mut content)?;
+/// assert_eq!("hello", content);
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct FusedReopen<FD> {
+    reopen: Reopen<FD>,
+    done: bool,
+}
+
+impl<FD: Read> Reopen<FD> {
+    /// Makes the first EOF final.
+    ///
+    /// See [`FusedReopen`].
+    pub fn fuse(self) -> FusedReopen<FD> {
+        FusedReopen {
+            reopen: self,
+            done: false,
+        }
+    }
+}
+
+impl<FD> FusedReopen<FD> {
+    /// The wrapped [`Reopen`].
+    pub fn get_ref(&self) -> &Reopen<FD> {
+        &self.reopen
+    }
+
+    /// The wrapped [`Reopen`].
+    ///
+    /// Reading from it directly is not affected by the fusing.
+    pub fn get_mut(&mut self) -> &mut Reopen<FD> {
+        &mut self.reopen
+    }
+
+    /// Unwraps the [`Reopen`].
+    pub fn into_inner(self) -> Reopen<FD> {
+        self.reopen
+    }
+
+    /// Checks if EOF was reached already.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+impl<FD: Read> Read for FusedReopen<FD> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if self.done {
+            return Ok(0);
+        }
+        let read = self.reopen.read(buf)?;
+        if read == 0 && !buf.is_empty() {
+            self.done = true;
+        }
+        Ok(read)
+    }
+}
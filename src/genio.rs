@@ -0,0 +1,99 @@
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Error;
+
+use genio::error::ReadExactError;
+use genio::{Read, Write};
+
+use super::Reopen;
+
+/// An error of an operation through the [`genio`] traits.
+///
+/// The [`genio`] traits have their own error types, so the errors of opening the FD (which are
+/// always [`std::io::Error`]s) need to be told apart from the errors of the FD itself.
+///
+/// # Features
+///
+/// This is available only with the `genio` feature enabled.
+#[derive(Debug)]
+pub enum GenioError<E> {
+    /// Opening the FD failed.
+    Open(Error),
+    /// The operation on the FD failed.
+    Fd(E),
+}
+
+impl<E: Display> Display for GenioError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            GenioError::Open(e) => write!(f, "Failed to open: {}", e),
+            GenioError::Fd(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for GenioError<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            GenioError::Open(e) => Some(e),
+            GenioError::Fd(e) => Some(e),
+        }
+    }
+}
+
+/// Like [`std::io::Read`], a reopen requested in the middle of `read_exact` waits for it to
+/// finish.
+impl<FD: Read> Read for Reopen<FD> {
+    type ReadError = GenioError<FD::ReadError>;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::ReadError> {
+        let fd = self.lock().map_err(GenioError::Open)?;
+        fd.read(buf).map_err(GenioError::Fd)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), ReadExactError<Self::ReadError>> {
+        let fd = self
+            .lock()
+            .map_err(|e| ReadExactError::Other(GenioError::Open(e)))?;
+        fd.read_exact(buf).map_err(|e| match e {
+            ReadExactError::Other(e) => ReadExactError::Other(GenioError::Fd(e)),
+            ReadExactError::UnexpectedEnd => ReadExactError::UnexpectedEnd,
+        })
+    }
+}
+
+/// Like [`std::io::Write`], a reopen requested in the middle of `write_all` waits for it to
+/// finish.
+impl<FD: Write> Write for Reopen<FD> {
+    type WriteError = GenioError<FD::WriteError>;
+    type FlushError = GenioError<FD::FlushError>;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::WriteError> {
+        let fd = self.lock().map_err(GenioError::Open)?;
+        fd.write(buf).map_err(GenioError::Fd)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::FlushError> {
+        let fd = self.lock().map_err(GenioError::Open)?;
+        fd.flush().map_err(GenioError::Fd)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::WriteError> {
+        let fd = self.lock().map_err(GenioError::Open)?;
+        fd.write_all(buf).map_err(GenioError::Fd)
+    }
+
+    fn size_hint(&mut self, bytes: usize) {
+        // A failure to open is reported by the following write.
+        if let Ok(fd) = self.lock() {
+            fd.size_hint(bytes);
+        }
+    }
+
+    fn uses_size_hint(&self) -> bool {
+        match self.fd.as_ref() {
+            Some(fd) => fd.uses_size_hint(),
+            None => false,
+        }
+    }
+}
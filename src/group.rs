@@ -0,0 +1,213 @@
+use std::fs::File;
+use std::io::{Error, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::{Handle, Reopen};
+
+#[derive(Debug)]
+struct Inner {
+    master: Mutex<Reopen<File>>,
+    /// Bumped each time the master is reopened.
+    ///
+    /// This is bumped only after the master actually reopened (a request that is held back by a
+    /// pause or the reopen limit doesn't count), still under the lock of the master.
+    epoch: AtomicUsize,
+}
+
+impl Inner {
+    /// Reopens the master if requested and provides a duplicate of its current file.
+    ///
+    /// Nothing is duplicated if the caller already holds a duplicate from the `known` epoch.
+    fn dup(&self, known: Option<usize>) -> Result<Option<(File, usize)>, Error> {
+        let mut master = self.master.lock().unwrap_or_else(PoisonError::into_inner);
+        let generation = master.generation;
+        master.lock()?;
+        if master.generation != generation {
+            self.epoch.fetch_add(1, Ordering::Release);
+        }
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        if known == Some(epoch) {
+            return Ok(None);
+        }
+        let file = master.lock()?.try_clone()?;
+        Ok(Some((file, epoch)))
+    }
+}
+
+/// A group of writers sharing one reopenable file, each with its own file descriptor.
+///
+/// For heavy logging from many threads, even an uncontended mutex on each write may be too much.
+/// This allows each thread to have its own [`Member`], holding a duplicate (see `dup(2)`) of the
+/// file descriptor of the master [`Reopen`]. Writing through a member needs no locking, just a
+/// check if the master was asked to reopen. If so, the master is reopened (once for all the
+/// members) and the members then lazily pick up duplicates of the new file descriptor on their
+/// next write.
+///
+/// The file should be opened in append mode (eg. by [`Reopen::append_to`]). Then each single
+/// write is placed at the end of the file atomically, so the data from different members don't
+/// overwrite each other. Note that formatting macros like `writeln!` usually split the output into
+/// multiple writes, so format the whole record first if it must stay in one piece.
+///
+/// # Platform support
+///
+/// This is available only on unix.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # use std::thread;
+/// # use reopen::{Reopen, ReopenGroup};
+/// # fn main() -> Result<(), Error> {
+/// let group = ReopenGroup::new(Reopen::append_to("/log/file")?);
+/// let mut member = group.member()?;
+/// thread::spawn(move || {
+///     writeln!(member, "Hello from a thread").unwrap();
+/// });
+/// group.handle().reopen();
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReopenGroup(Arc<Inner>);
+
+impl ReopenGroup {
+    /// Creates a group around the given master.
+    pub fn new(master: Reopen<File>) -> Self {
+        ReopenGroup(Arc::new(Inner {
+            master: Mutex::new(master),
+            epoch: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Returns a handle to signal the master to reopen.
+    ///
+    /// The members notice it on their next write.
+    pub fn handle(&self) -> Handle {
+        self.0
+            .master
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .handle()
+    }
+
+    /// Creates a new member of the group.
+    ///
+    /// # Errors
+    ///
+    /// This fails if the master needs reopening and it fails or if duplicating the file
+    /// descriptor fails.
+    pub fn member(&self) -> Result<Member, Error> {
+        let (file, epoch) = self.0.dup(None)?.expect("Nothing known yet");
+        let handle = self.handle();
+        Ok(Member {
+            group: Arc::clone(&self.0),
+            handle,
+            file: Some(file),
+            epoch,
+        })
+    }
+}
+
+/// A single writer of a [`ReopenGroup`].
+///
+/// This is meant to be owned by a single thread.
+#[derive(Debug)]
+pub struct Member {
+    group: Arc<Inner>,
+    handle: Handle,
+    file: Option<File>,
+    epoch: usize,
+}
+
+impl Member {
+    /// Provides the up to date file.
+    fn file(&mut self) -> Result<&mut File, Error> {
+        let requested = self.handle.0.maybe_requested();
+        let stale = self.group.epoch.load(Ordering::Acquire) != self.epoch;
+        if requested || stale || self.file.is_none() {
+            let known = match self.file {
+                Some(_) if !stale => Some(self.epoch),
+                _ => None,
+            };
+            match self.group.dup(known) {
+                Ok(Some((file, epoch))) => {
+                    self.file = Some(file);
+                    self.epoch = epoch;
+                }
+                Ok(None) => (),
+                Err(e) => {
+                    // Don't keep writing into a file that might be stale.
+                    self.file.take();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+}
+
+impl Write for Member {
+    fn flush(&mut self) -> Result<(), Error> {
+        self.file()?.flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.file()?.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        self.file()?.write_all(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn group() -> (TempDir, ReopenGroup, Handle) {
+        let dir = TempDir::new().unwrap();
+        let master = Reopen::append_to(dir.path().join("log"))
+            .unwrap()
+            .with_max_reopens(1);
+        let group = ReopenGroup::new(master);
+        let handle = group.handle();
+        (dir, group, handle)
+    }
+
+    /// A request held back by a pause doesn't make the members duplicate the file again.
+    #[test]
+    fn paused() {
+        let (_dir, group, handle) = group();
+        let mut member = group.member().unwrap();
+        handle.pause();
+        handle.reopen();
+        for _ in 0..10 {
+            member.write_all(b"Hello\n").unwrap();
+        }
+        assert_eq!(0, group.0.epoch.load(Ordering::Relaxed));
+        assert_eq!(0, member.epoch);
+
+        handle.resume();
+        member.write_all(b"Hello\n").unwrap();
+        assert_eq!(1, group.0.epoch.load(Ordering::Relaxed));
+        assert_eq!(1, member.epoch);
+    }
+
+    /// A request over the limit fails the writes, but doesn't count as a reopen.
+    #[test]
+    fn limit_reached() {
+        let (_dir, group, handle) = group();
+        let mut member = group.member().unwrap();
+        handle.reopen();
+        member.write_all(b"Hello\n").unwrap();
+        assert_eq!(1, member.epoch);
+        handle.reopen();
+        for _ in 0..10 {
+            assert!(member.write_all(b"Hello\n").is_err());
+        }
+        assert_eq!(1, group.0.epoch.load(Ordering::Relaxed));
+    }
+}
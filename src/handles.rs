@@ -0,0 +1,103 @@
+use std::iter::FromIterator;
+
+use super::Handle;
+
+/// A collection of [`Handle`]s, to reopen many things at once.
+///
+/// Programs with many files (eg. a log file per subsystem) usually want to reopen all of them at
+/// once. The handles are not tied to the type of the FD, so [`Reopen`][crate::Reopen]s of
+/// different types can be put into the same group.
+///
+/// Note that this is unrelated to the [`ReopenGroup`][crate::ReopenGroup], which shares a single
+/// file between multiple writers.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::{Cursor, Error};
+/// # use reopen::{HandleGroup, Reopen};
+/// # fn main() -> Result<(), Error> {
+/// let mut log = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?;
+/// let mut input = Reopen::new(Box::new(|| Ok(Cursor::new(b"data"))))?;
+/// let mut group = HandleGroup::new();
+/// group.add(log.handle());
+/// group.add(input.handle());
+/// group.reopen_all();
+/// # let _ = (log.lock()?, input.lock()?);
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HandleGroup {
+    handles: Vec<Handle>,
+}
+
+impl HandleGroup {
+    /// Creates an empty group.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds another handle into the group.
+    pub fn add(&mut self, handle: Handle) {
+        self.handles.push(handle);
+    }
+
+    /// Number of handles in the group.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Checks if the group is empty.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Asks all the [`Reopen`][crate::Reopen]s in the group to reopen.
+    ///
+    /// Each one reopens on its next operation, see [`Handle::reopen`].
+    pub fn reopen_all(&self) {
+        for handle in &self.handles {
+            handle.reopen();
+        }
+    }
+
+    /// Registers all the handles in the group to a signal.
+    ///
+    /// This is like calling [`Handle::register_signal`] on each of them, the same notes apply.
+    /// Only the handles currently in the group are registered, handles added later need to be
+    /// registered separately.
+    ///
+    /// # Errors
+    ///
+    /// If registering any of the handles fails, the error is returned. The handles registered
+    /// before that stay registered.
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `signals` feature enabled.
+    #[cfg(all(feature = "signals", not(loom)))]
+    pub fn register_signal_all<S: Into<std::os::raw::c_int>>(
+        &self,
+        signal: S,
+    ) -> Result<Vec<crate::signals::SigId>, std::io::Error> {
+        let signal = signal.into();
+        self.handles
+            .iter()
+            .map(|handle| handle.register_signal(signal))
+            .collect()
+    }
+}
+
+impl FromIterator<Handle> for HandleGroup {
+    fn from_iter<I: IntoIterator<Item = Handle>>(iter: I) -> Self {
+        HandleGroup {
+            handles: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Handle> for HandleGroup {
+    fn extend<I: IntoIterator<Item = Handle>>(&mut self, iter: I) {
+        self.handles.extend(iter);
+    }
+}
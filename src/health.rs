@@ -0,0 +1,74 @@
+//! A snapshot of the whole state, for status reporting.
+
+use std::time::Instant;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use super::Reopen;
+
+/// The state of a [`Reopen`] at some point in time.
+///
+/// Returned by [`Reopen::health`]. With the `serde` feature, it can be serialized (except for
+/// the [`opened_at`][ReopenHealth::opened_at], which has no meaning outside of the process).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[non_exhaustive]
+pub struct ReopenHealth {
+    /// There's an FD currently open.
+    pub open: bool,
+    /// A reopen is requested but didn't happen yet (see [`Reopen::is_pending`]).
+    pub pending: bool,
+    /// Reopens done since creation or the last [`reset`][Reopen::reset].
+    ///
+    /// These are the ones counted against the [limit][Reopen::with_max_reopens].
+    pub reopen_count: u64,
+    /// How many times a new FD was opened after the construction.
+    ///
+    /// The same as in [`ReopenEvent::OpenSucceeded`][crate::ReopenEvent::OpenSucceeded].
+    pub generation: u64,
+    /// The error of the last failed open, if no open succeeded since.
+    pub last_error: Option<String>,
+    /// Bytes written into the current FD since it was opened.
+    pub bytes_written: u64,
+    /// The [label][Reopen::with_label], if any.
+    pub label: Option<String>,
+    /// When the current FD was opened, if there's one.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub opened_at: Option<Instant>,
+}
+
+impl<FD> Reopen<FD> {
+    /// Returns a snapshot of the current state.
+    ///
+    /// This is meant for status endpoints and similar diagnostics, to get everything in one
+    /// call. It doesn't do any IO, nor does it do a pending reopen.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?.with_label("test");
+    /// writer.write_all(b"Hello")?;
+    /// let health = writer.health();
+    /// assert!(health.open);
+    /// assert_eq!(5, health.bytes_written);
+    /// assert_eq!(Some("test"), health.label.as_ref().map(String::as_str));
+    /// # Ok(()) }
+    /// ```
+    pub fn health(&self) -> ReopenHealth {
+        let open = self.fd.is_some();
+        ReopenHealth {
+            open,
+            pending: self.is_pending(),
+            reopen_count: self.reopens as u64,
+            generation: self.generation,
+            last_error: self.last_error.clone(),
+            bytes_written: self.bytes_written.get(),
+            label: self.label.clone(),
+            opened_at: if open { self.opened_at } else { None },
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Adopting descriptors inherited from the parent process.
+
+use std::env;
+use std::fs::File;
+use std::io::{Error, ErrorKind};
+use std::mem::{self, ManuallyDrop};
+use std::os::unix::io::FromRawFd;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::Reopen;
+
+/// The first descriptor passed by the LISTEN_FDS protocol.
+const LISTEN_FDS_START: usize = 3;
+
+/// The descriptors already adopted by [`Reopen::from_listen_fds`], indexed from
+/// `LISTEN_FDS_START`.
+///
+/// Adopting the same one twice would close it twice.
+static CLAIMED: AtomicUsize = AtomicUsize::new(0);
+
+fn not_found(msg: &str) -> Error {
+    Error::new(ErrorKind::NotFound, msg)
+}
+
+/// Finds the index of the descriptor with the given name.
+fn listen_fd_index(name: &str) -> Result<usize, Error> {
+    let pid = env::var("LISTEN_PID").map_err(|_| not_found("No LISTEN_PID"))?;
+    if pid.parse::<u32>().ok() != Some(process::id()) {
+        return Err(not_found("The LISTEN_PID is for another process"));
+    }
+    let count = env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|count| count.parse::<usize>().ok())
+        .ok_or_else(|| not_found("No valid LISTEN_FDS"))?;
+    let names = env::var("LISTEN_FDNAMES").unwrap_or_default();
+    names
+        .split(':')
+        .take(count)
+        .position(|n| n == name)
+        .ok_or_else(|| not_found("No inherited descriptor of that name"))
+}
+
+impl Reopen<File> {
+    /// Creates a [`Reopen`] adopting a descriptor inherited from the parent process.
+    ///
+    /// The descriptor is used until the first reopen, the constructor opens the new ones (eg.
+    /// by asking the supervisor again, or by opening a path). Unlike [`with_fd`][Reopen::with_fd],
+    /// this makes sure the adopted descriptor is not inherited further by our own children
+    /// (close-on-exec), which a descriptor passed by a parent usually isn't.
+    ///
+    /// This takes an owned value (a [`File`], or an `OwnedFd` on new enough compilers), so
+    /// adopting a raw descriptor number is left to the caller, who knows it's really theirs to
+    /// take.
+    ///
+    /// # Errors
+    ///
+    /// If the descriptor can't be duplicated.
+    ///
+    /// # Platform support
+    ///
+    /// This is available only on unix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::fs::File;
+    /// # use std::io::Error;
+    /// # use std::os::unix::io::FromRawFd;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// // SAFETY: the supervisor passes the log sink as fd 3 and nothing else uses it.
+    /// let inherited = unsafe { File::from_raw_fd(3) };
+    /// let log = Reopen::from_inherited_fd(inherited, Box::new(|| File::create("/log/file")))?;
+    /// # let _ = log;
+    /// # Ok(()) }
+    /// ```
+    pub fn from_inherited_fd<F: Into<File>>(
+        fd: F,
+        constructor: Box<dyn Fn() -> Result<File, Error> + Send>,
+    ) -> Result<Self, Error> {
+        // The duplicate is created with close-on-exec. The original is closed here.
+        let fd = fd.into().try_clone()?;
+        Ok(Self::with_fd(fd, constructor))
+    }
+
+    /// Creates a [`Reopen`] adopting a named descriptor passed by the `LISTEN_FDS` protocol.
+    ///
+    /// The service managers (eg. systemd with `FileDescriptorName=` or `sd_pid_notify_with_fds`
+    /// stored descriptors) pass the descriptors starting from 3, their count in `LISTEN_FDS`,
+    /// their names in `LISTEN_FDNAMES` (separated by `:`) and the process they are meant for in
+    /// `LISTEN_PID`. This finds the one with the given name and adopts it as in
+    /// [`from_inherited_fd`][Reopen::from_inherited_fd]. The environment is left intact, so
+    /// other descriptors can be picked up by other code.
+    ///
+    /// # Errors
+    ///
+    /// * [`NotFound`][ErrorKind::NotFound] if the variables are not set, are for another process
+    ///   or there's no descriptor of that name.
+    /// * [`InvalidInput`][ErrorKind::InvalidInput] if the descriptor is not open or was already
+    ///   adopted by this function.
+    ///
+    /// # Platform support
+    ///
+    /// This is available only on unix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::fs::File;
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let log = Reopen::from_listen_fds("log", Box::new(|| File::create("/log/file")))?;
+    /// # let _ = log;
+    /// # Ok(()) }
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn from_listen_fds(
+        name: &str,
+        constructor: Box<dyn Fn() -> Result<File, Error> + Send>,
+    ) -> Result<Self, Error> {
+        let index = listen_fd_index(name)?;
+        if index >= mem::size_of::<usize>() * 8 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Too many inherited descriptors",
+            ));
+        }
+        let bit = 1 << index;
+        if CLAIMED.fetch_or(bit, Ordering::Relaxed) & bit != 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The inherited descriptor was already adopted",
+            ));
+        }
+        let raw = (LISTEN_FDS_START + index) as _;
+        // SAFETY: the LISTEN_FDS protocol hands the descriptor over to this process and we make
+        // sure to adopt it only once. It is not closed until we check it is actually open.
+        let file = ManuallyDrop::new(unsafe { File::from_raw_fd(raw) });
+        if let Err(e) = file.metadata() {
+            let msg = format!("The inherited descriptor is not usable: {}", e);
+            return Err(Error::new(ErrorKind::InvalidInput, msg));
+        }
+        Self::from_inherited_fd(ManuallyDrop::into_inner(file), constructor)
+    }
+}
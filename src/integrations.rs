@@ -0,0 +1,217 @@
+//! Adapters plugging a [`Reopen`] into other crates.
+//!
+//! Each adapter is behind a feature of the same name as the crate it adapts to.
+
+#[cfg(feature = "slog")]
+use std::fmt::{Arguments, Write as FmtWrite};
+#[cfg(feature = "env_logger")]
+use std::io::Error;
+#[cfg(any(feature = "fern", feature = "slog"))]
+use std::io::Write;
+#[cfg(feature = "slog")]
+use std::panic::AssertUnwindSafe;
+#[cfg(feature = "env_logger")]
+use std::path::PathBuf;
+#[cfg(feature = "slog")]
+use std::sync::Arc;
+#[cfg(feature = "slog")]
+use std::time::UNIX_EPOCH;
+
+#[cfg(feature = "slog")]
+use slog::{Drain, Key, Never, OwnedKVList, Record, Serializer, KV};
+
+#[cfg(any(feature = "env_logger", feature = "fern"))]
+use super::trace;
+#[cfg(any(feature = "env_logger", feature = "slog"))]
+use super::Handle;
+#[cfg(any(feature = "env_logger", feature = "fern", feature = "slog"))]
+use super::Reopen;
+#[cfg(feature = "slog")]
+use super::{Clock, SyncReopen};
+
+/// Turns the [`Reopen`] into an output of the [`fern`](https://docs.rs/fern) logger.
+///
+/// The result can be passed to `Dispatch::chain`. Each record is written with `\n` as the line
+/// separator and the [`Reopen`] is flushed after it.
+///
+/// Fern itself accepts a `Reopen<File>` with its `reopen-1` feature (on unix). This works with
+/// any FD and on any platform, without the need to coordinate the features of the two crates.
+///
+/// Take the [`handle`][Reopen::handle] before passing the [`Reopen`] in, to request the reopens
+/// later on (eg. by registering a signal).
+///
+/// # Features
+///
+/// This is available only with the `fern` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use reopen::Reopen;
+/// # use reopen::integrations::fern_output;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let log = Reopen::append_to("/log/file")?;
+/// let handle = log.handle();
+/// fern::Dispatch::new()
+///     .level(log::LevelFilter::Info)
+///     .chain(fern_output(log))
+///     .apply()?;
+/// // Later on, eg. on a signal
+/// handle.reopen();
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "fern")]
+pub fn fern_output<FD: Write + Send + 'static>(reopen: Reopen<FD>) -> fern::Output {
+    fern::Output::writer(trace::logger_output(reopen), "\n")
+}
+
+/// Opens the file for appending and turns it into a target of the
+/// [`env_logger`](https://docs.rs/env_logger) logger.
+///
+/// The target is meant for `Builder::target`. The returned [`Handle`] requests the reopens
+/// (eg. from a signal handler), as the [`Reopen`] itself is moved into the logger. The file is
+/// opened as in [`Reopen::append_to`].
+///
+/// # Errors
+///
+/// If the file can't be opened.
+///
+/// # Features
+///
+/// This is available only with the `env_logger` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use reopen::integrations::env_logger_target;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let (target, handle) = env_logger_target("/log/file")?;
+/// env_logger::Builder::from_default_env()
+///     .target(target)
+///     .try_init()?;
+/// // Later on, eg. on a signal
+/// handle.reopen();
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "env_logger")]
+pub fn env_logger_target<P: Into<PathBuf>>(
+    path: P,
+) -> Result<(env_logger::fmt::Target, Handle), Error> {
+    let reopen = Reopen::append_to(path.into())?;
+    let handle = reopen.handle();
+    Ok((
+        env_logger::fmt::Target::Pipe(trace::logger_output(reopen)),
+        handle,
+    ))
+}
+
+/// A [`slog`](https://docs.rs/slog) drain writing through a [`Reopen`].
+///
+/// Each record is written as a single line of plain text:
+///
+/// ```text
+/// 1700000000.123 INFO my_app::module: The message, key=value, other=value
+/// ```
+///
+/// The timestamp is the number of seconds since the unix epoch, with milliseconds, the level uses
+/// the short slog names (eg. `WARN`, `DEBG`). The key-value pairs of the record come first, the
+/// ones of the logger after them.
+///
+/// The errors are ignored, as there's nobody to report them to (following the usual convention
+/// for the final drains). Writes from different threads don't interleave.
+///
+/// For other formats (eg. JSON by `slog-json`), pass the [`Reopen`] (it is a [`Write`]) to the
+/// drain of that crate directly. Just take its [`handle`][Reopen::handle] first.
+///
+/// # Features
+///
+/// This is available only with the `slog` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use reopen::Reopen;
+/// # use reopen::integrations::ReopenDrain;
+/// # use slog::{info, o, Logger};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let drain = ReopenDrain::new(Reopen::append_to("/log/file")?);
+/// let handle = drain.handle();
+/// let logger = Logger::root(drain, o!("app" => "example"));
+/// info!(logger, "Started"; "version" => 1);
+/// // Later on, eg. on a signal
+/// handle.reopen();
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "slog")]
+#[derive(Debug)]
+pub struct ReopenDrain<FD> {
+    // Slog wants the drains unwind safe. A panic in the middle of a write doesn't leave the
+    // Reopen in an inconsistent state (see SyncReopen).
+    reopen: AssertUnwindSafe<SyncReopen<FD>>,
+    // Only reads the time.
+    clock: AssertUnwindSafe<Arc<dyn Clock>>,
+}
+
+#[cfg(feature = "slog")]
+impl<FD> ReopenDrain<FD> {
+    /// Creates the drain.
+    ///
+    /// The timestamps come from the [clock][Reopen::with_clock] of the [`Reopen`].
+    pub fn new(reopen: Reopen<FD>) -> Self {
+        Self {
+            clock: AssertUnwindSafe(Arc::clone(&reopen.clock)),
+            reopen: AssertUnwindSafe(SyncReopen::new(reopen)),
+        }
+    }
+
+    /// Returns a handle to request a reopen.
+    pub fn handle(&self) -> Handle {
+        self.reopen.handle()
+    }
+
+    /// Unwraps the [`Reopen`].
+    pub fn into_inner(self) -> Reopen<FD> {
+        self.reopen.0.into_inner()
+    }
+}
+
+/// Formats the key-value pairs as `, key=value`.
+#[cfg(feature = "slog")]
+struct PlainSerializer(String);
+
+#[cfg(feature = "slog")]
+impl Serializer for PlainSerializer {
+    fn emit_arguments(&mut self, key: Key, val: &Arguments<'_>) -> slog::Result {
+        // Writing into a String can't fail.
+        let _ = write!(self.0, ", {}={}", key, val);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "slog")]
+impl<FD: Write> Drain for ReopenDrain<FD> {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record<'_>, values: &OwnedKVList) -> Result<(), Never> {
+        let since_epoch = self
+            .clock
+            .system_now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut line = PlainSerializer(format!(
+            "{}.{:03} {} {}: {}",
+            since_epoch.as_secs(),
+            since_epoch.subsec_millis(),
+            record.level().as_short_str(),
+            record.module(),
+            record.msg()
+        ));
+        let _ = record.kv().serialize(record, &mut line);
+        let _ = values.serialize(record, &mut line);
+        line.0.push('\n');
+        // The whole line at once, not to interleave with other threads.
+        let _ = (&*self.reopen).write_all(line.0.as_bytes());
+        Ok(())
+    }
+}
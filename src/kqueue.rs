@@ -0,0 +1,210 @@
+//! Watching the path on macOS and the BSDs.
+//!
+//! The windows version of the same API lives in `src/windows/watch.rs`.
+
+use std::ffi::CString;
+use std::io::{Error, ErrorKind};
+use std::mem;
+use std::os::raw::c_int;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::ptr;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::Handle;
+
+/// How often to look if the file appeared again, after it went away.
+const REARM_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Opens the file only to watch it.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const OPEN_FLAGS: c_int = libc::O_EVTONLY | libc::O_CLOEXEC;
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+const OPEN_FLAGS: c_int = libc::O_RDONLY | libc::O_CLOEXEC;
+
+/// A file descriptor closed on drop.
+struct Fd(c_int);
+
+impl Fd {
+    fn check(fd: c_int) -> Result<Self, Error> {
+        if fd == -1 {
+            Err(Error::last_os_error())
+        } else {
+            Ok(Fd(fd))
+        }
+    }
+}
+
+impl Drop for Fd {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        // SAFETY: we own the descriptor. Closing it also removes its events from the kqueue.
+        unsafe { libc::close(self.0) };
+    }
+}
+
+/// Creates a change to register the given event.
+#[allow(unsafe_code)]
+fn event(ident: c_int, filter: i64, fflags: u32) -> libc::kevent {
+    // SAFETY: all zeroes is a valid (empty) kevent.
+    let mut ev: libc::kevent = unsafe { mem::zeroed() };
+    ev.ident = ident as _;
+    ev.filter = filter as _;
+    ev.flags = (libc::EV_ADD | libc::EV_CLEAR) as _;
+    ev.fflags = fflags as _;
+    ev
+}
+
+/// Adds the event to the kqueue and waits for one.
+///
+/// Returns `true` if it was the stop request.
+#[allow(unsafe_code)]
+fn wait(
+    kq: &Fd,
+    change: Option<libc::kevent>,
+    stop: c_int,
+    timeout: Option<Duration>,
+) -> Result<bool, Error> {
+    let changes = change.as_ref().map_or(ptr::null(), |c| c as *const _);
+    let timeout = timeout.map(|t| libc::timespec {
+        tv_sec: t.as_secs() as _,
+        tv_nsec: t.subsec_nanos() as _,
+    });
+    let timeout = timeout.as_ref().map_or(ptr::null(), |t| t as *const _);
+    let nchanges = if change.is_some() { 1 } else { 0 };
+    // SAFETY: all zeroes is a valid (empty) kevent.
+    let mut out: libc::kevent = unsafe { mem::zeroed() };
+    loop {
+        // SAFETY: all the pointers point to valid structures (or are null) for the whole call.
+        let result = unsafe { libc::kevent(kq.0, changes, nchanges, &mut out, 1, timeout) };
+        match result {
+            -1 => {
+                let e = Error::last_os_error();
+                if e.kind() != ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            }
+            0 => return Ok(false),
+            // Failing to register the change is reported as an event.
+            _ if out.flags as u32 & libc::EV_ERROR as u32 != 0 => {
+                return Err(Error::from_raw_os_error(out.data as i32));
+            }
+            _ => return Ok(out.ident as c_int == stop),
+        }
+    }
+}
+
+/// Watches the file until asked to stop.
+#[allow(unsafe_code)]
+fn watch(path: CString, handle: Handle, stop: UnixStream) -> Result<(), Error> {
+    // SAFETY: plain FFI call.
+    let kq = Fd::check(unsafe { libc::kqueue() })?;
+    let stop_fd = stop.as_raw_fd();
+    // The other end gets closed by the guard, which makes this readable.
+    if wait(
+        &kq,
+        Some(event(stop_fd, libc::EVFILT_READ as _, 0)),
+        stop_fd,
+        Some(Duration::from_secs(0)),
+    )? {
+        return Ok(());
+    }
+    loop {
+        // SAFETY: the path is a valid C string.
+        let file = match Fd::check(unsafe { libc::open(path.as_ptr(), OPEN_FLAGS) }) {
+            Ok(file) => file,
+            // Not there (yet), the reopen will create it. Look again a bit later.
+            Err(_) => {
+                if wait(&kq, None, stop_fd, Some(REARM_INTERVAL))? {
+                    return Ok(());
+                }
+                continue;
+            }
+        };
+        let gone = event(
+            file.0,
+            libc::EVFILT_VNODE as _,
+            (libc::NOTE_DELETE | libc::NOTE_RENAME) as _,
+        );
+        if wait(&kq, Some(gone), stop_fd, None)? {
+            return Ok(());
+        }
+        // The only other thing we are watching is the file.
+        handle.reopen();
+    }
+}
+
+impl Handle {
+    /// Reopens when the file at the given path is renamed or deleted.
+    ///
+    /// This watches the file (by the `kqueue` vnode filter) in a helper thread. Whenever the
+    /// file is renamed away or deleted (usually by a log rotation tool), a reopen is requested,
+    /// so the next operation of the [`Reopen`][crate::Reopen] opens a fresh file at the original
+    /// path. This needs no cooperation from the rotation tool.
+    ///
+    /// After the file goes away, the watching is re-armed on the new file at the same path as
+    /// soon as it appears (it is looked for every 100 milliseconds). Usually that is when the
+    /// [`Reopen`][crate::Reopen] opens it on its next operation. If the file doesn't exist when
+    /// this is called, it is looked for the same way.
+    ///
+    /// The helper thread is stopped when the returned guard is dropped.
+    ///
+    /// # Errors
+    ///
+    /// If the path contains a null byte or the helper thread can't be started.
+    ///
+    /// # Platform support
+    ///
+    /// This is available with the `watch` feature on windows (there it watches the directory
+    /// instead, see the windows documentation), macOS, iOS, FreeBSD, NetBSD, OpenBSD and
+    /// DragonFly.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let log = Reopen::append_to("/log/file")?;
+    /// let _guard = log.handle().watch_path("/log/file")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn watch_path<P: AsRef<Path>>(&self, path: P) -> Result<WatchGuard, Error> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let (stop_read, stop_write) = UnixStream::pair()?;
+        let handle = self.clone();
+        let thread = thread::Builder::new()
+            .name("reopen-watch".to_owned())
+            .spawn(move || {
+                // Nobody to report the error to, the watching just ends.
+                let _ = watch(path, handle, stop_read);
+            })?;
+        Ok(WatchGuard {
+            stop: Some(stop_write),
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Keeps watching a path.
+///
+/// See [`Handle::watch_path`]. Dropping it stops the watching.
+#[derive(Debug)]
+pub struct WatchGuard {
+    stop: Option<UnixStream>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        // Closing our end wakes the thread up.
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
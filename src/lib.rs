@@ -1,7 +1,19 @@
 #![doc(test(attr(deny(warnings))))]
 #![warn(missing_docs)]
-// Forbid unsafe code in the actual code, but tests use libc::kill.
-#![cfg_attr(not(test), forbid(unsafe_code))]
+// No unsafe code in the actual code, but tests use libc::kill. The exceptions are allowed on the
+// individual functions, each unsafe block with its SAFETY justification:
+//
+// * Calling into the windows API (the console handler, named pipes, events, watching the
+//   directory, the free space).
+// * Calling into libc on unix: duplicating the descriptors in redirect, switching the fifo back
+//   to blocking mode, linking the tmpfile into place, kqueue, the free space (statvfs) and the
+//   monotonic clock for systemd (clock_gettime).
+// * Adopting the inherited descriptors (from_raw_fd).
+// * Mapping the file by mmap.
+#![cfg_attr(not(test), deny(unsafe_code))]
+#![warn(clippy::undocumented_unsafe_blocks)]
+// Detected by build.rs, available only on nightly.
+#![cfg_attr(read_buf, feature(read_buf, core_io_borrowed_buf))]
 
 //!  A tiny `Read`/`Write` wrapper that can reopen the underlying IO object.
 //!
@@ -15,12 +27,116 @@
 //!
 //! The amount of supported traits is somewhat limited. For example, [BufRead][std::io::BufRead] or
 //! [Seek][std::io::Seek] are not implemented, because the behavior across reopens would be
-//! confusing if not outright wrong.
+//! confusing if not outright wrong. The FD can still be moved through [`Reopen::lock`] and
+//! [`Reopen::preserve_position`] carries its position over reopens.
+//!
+//! On nightly compilers, the `read_buf` and `read_buf_exact` methods of [`Read`] (reading into
+//! uninitialized buffers) are forwarded too. This is detected automatically.
 //!
 //! # Features
 //!
 //! The `signals` feature adds support to registering a reopening as a result of received a signal
-//! (for example the `SIGHUP` one).
+//! (for example the `SIGHUP` one). On platforms without signals (eg. WASI), registering them fails
+//! (see [`Reopen::with_trigger_file`] for an alternative). On the BSDs and macOS, it also adds
+//! status reporting on `SIGINFO` (`Handle::register_siginfo_status`).
+//!
+//! The `sd-notify` feature (implying `signals`) adds reopening on `SIGHUP` following the systemd
+//! reload protocol (`Handle::register_systemd_reload`, unix only).
+//!
+//! The `parking_lot` feature makes [`SyncReopen`] use the faster mutex from the `parking_lot`
+//! crate internally.
+//!
+//! The `arc-swap` feature adds the [`SwapReopen`] for lock-free read-mostly access.
+//!
+//! The `metrics` feature exports some statistics through the [`metrics`](https://docs.rs/metrics)
+//! crate (see [`Reopen::with_label`]).
+//!
+//! The `tracing` feature emits [`tracing`](https://docs.rs/tracing) events about the reopening,
+//! at the debug level with the `reopen` target: when a reopen is requested, a span around each
+//! open (followed by an event with its duration and outcome) and when the old FD is closed.
+//! Requests made by signal handlers can't emit anything, so they show only as the open that
+//! follows. The first open of [`Reopen::new`] happens before the [`Reopen`] exists and isn't
+//! traced either. Beware of a subscriber writing through the very same [`Reopen`]: the events are
+//! emitted with the [`Reopen`] borrowed (and, in case of [`SyncReopen`], locked), so such
+//! subscriber deadlocks. Events emitted while the subscriber itself runs on the same thread are
+//! dropped, which prevents infinite recursion, but not the deadlock.
+//!
+//! The `log` feature logs through the [`log`](https://docs.rs/log) crate, with the `reopen`
+//! target: a warning when an open fails (with the error) and a debug record when a new FD is
+//! installed (with the generation). The same caveat about a logger writing through the same
+//! [`Reopen`] applies, records emitted while the logger itself runs on the same thread are
+//! dropped. The loggers provided by this crate (the `logger` feature, which implies this one, and
+//! the `fern` and `env_logger` adapters) mute these records while writing, so they don't deadlock
+//! on their own lock.
+//!
+//! The `test-util` feature adds helpers for testing code using [`Reopen`], the `ManualClock`
+//! and the mock FDs and constructors in the `test_util` module.
+//!
+//! The `windows-console` feature adds reopening on console control events (like `Ctrl+Break`)
+//! on windows. The `windows-event` feature adds reopening on signalled named event objects, the
+//! `windows-pipe` feature a named pipe control channel. The `windows-service` feature adds a
+//! handler of service control codes for the `windows-service` crate (all in the `windows`
+//! module).
+//!
+//! The `genio` feature implements the [`genio`](https://docs.rs/genio) `Read` and `Write` traits
+//! for [`Reopen`], with the same semantics as the `std::io` ones. The errors are wrapped in
+//! `GenioError`.
+//!
+//! The `serde` feature adds the [`ReopenConfig`], to create a [`Reopen`] from a configuration
+//! file.
+//!
+//! The `watch` feature adds reopening when the file is renamed or deleted on macOS and the BSDs
+//! (`Handle::watch_path`, by `kqueue`) and on windows (by watching the directory).
+//!
+//! The `logger` feature adds a minimal logger for the [`log`](https://docs.rs/log) crate, writing
+//! through a [`Reopen`] (the `logger` module). Together with the `signals` feature, it can be
+//! installed with reopening on a signal in one call (`install_rotating_logger`).
+//!
+//! The `fern` feature adds an adapter to use a [`Reopen`] as an output of the
+//! [`fern`](https://docs.rs/fern) logger (the `integrations` module).
+//!
+//! The `slog` feature adds a [`slog`](https://docs.rs/slog) drain writing through a [`Reopen`]
+//! (in the `integrations` module).
+//!
+//! The `env_logger` feature adds a helper creating an [`env_logger`](https://docs.rs/env_logger)
+//! target writing into a file through a [`Reopen`] (in the `integrations` module).
+//!
+//! The `net` feature adds constructors of TCP connections, resolving the address again on each
+//! reopen, of UDP sockets bound again on each reopen and of unix stream sockets (in the `net`
+//! module).
+//!
+//! The `rustls` feature adds a constructor of TLS connections by
+//! [`rustls`](https://docs.rs/rustls), handshaking again on each reopen (in the `net` module).
+//!
+//! The `flate2` feature adds writing into gzip-compressed files, finished properly on each reopen
+//! (the `compress` module).
+//!
+//! The `zstd` feature does the same for zstd-compressed files (the `compress` module). It builds
+//! the zstd C library, so it needs a C compiler for the target.
+//!
+//! The `mmap` feature adds writing into memory-mapped files of a fixed size, switching to a new
+//! file once the current one is full (the `mmap` module).
+//!
+//! The `tmpfile` feature adds writing into anonymous files (`O_TMPFILE`), which get their name
+//! only once complete (the `tmpfile` module, on Linux only).
+//!
+//! The `free-space` feature adds watching the free space on the disk with the file on unix
+//! and windows (`Reopen::with_min_free_space`).
+//!
+//! The `fifo` feature adds a writer into a named pipe that tolerates the reader going away for a
+//! while (the `fifo` module, on unix only).
+//!
+//! The `redirect` feature adds redirecting the standard output and error of the whole process
+//! into a file, following the reopens (the `redirect` module, on unix only).
+//!
+//! The `serial` feature adds a constructor finding a serial device anew on each reopen (the
+//! `device` module, on unix only).
+//!
+//! The `syslog` feature adds a writer sending datagrams to a unix socket (eg. `/dev/log`), in
+//! the `net` module.
+//!
+//! The `tokio` feature adds reopening on a notification from the [`tokio`](https://tokio.rs)
+//! `Notify` (`Handle::spawn_notified`).
 //!
 //! # Examples
 //!
@@ -42,9 +158,9 @@
 //! }
 //!
 //! fn main() -> Result<(), Error> {
-//!     let file = Reopen::new(Box::new(&open))?;
+//!     let file = Reopen::from_fn(open)?;
 //! # #[cfg(all(feature = "signals", not(windows)))]
-//!     file.handle().register_signal(signal_hook::consts::SIGHUP)?;
+//!     file.handle().register_signal(reopen::signals::consts::SIGHUP)?;
 //!     simple_logging::log_to(file, log::LevelFilter::Debug);
 //!     info!("Hey, it's logging");
 //!     Ok(())
@@ -55,35 +171,204 @@
 //!
 //! If you find another use case for it, I'd like to hear about it.
 
+use std::cell::Cell;
+use std::cmp;
 use std::fmt::{self, Debug, Formatter, Result as FmtResult};
-use std::io::{Error, Read, Write};
+#[cfg(read_buf)]
+use std::io::BorrowedCursor;
+use std::io::{Error, ErrorKind, Read, Write};
 #[cfg(vectored)]
 use std::io::{IoSlice, IoSliceMut};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::num::NonZeroUsize;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
-#[cfg(feature = "signals")]
-mod signals;
+mod background;
+mod buffered;
+mod checksum;
+mod child;
+mod clock;
+#[cfg(any(feature = "flate2", feature = "zstd"))]
+pub mod compress;
+#[cfg(feature = "serde")]
+mod config;
+#[cfg(any(all(windows, feature = "windows-pipe"), test))]
+mod control;
+#[cfg(all(feature = "serial", unix))]
+pub mod device;
+mod events;
+#[cfg(all(feature = "fifo", unix))]
+pub mod fifo;
+mod file;
+mod flush_timeout;
+mod free_space;
+mod fused;
+#[cfg(feature = "genio")]
+mod genio;
+#[cfg(unix)]
+mod group;
+mod handles;
+mod health;
+#[cfg(unix)]
+mod inherited;
+#[cfg(any(feature = "env_logger", feature = "fern", feature = "slog"))]
+pub mod integrations;
+#[cfg(kqueue)]
+mod kqueue;
+#[cfg(file_lock)]
+mod lock_file;
+#[cfg(feature = "logger")]
+pub mod logger;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(any(feature = "net", feature = "rustls", all(feature = "syslog", unix)))]
+pub mod net;
+mod nonblocking;
+mod open_error;
+mod open_time;
+mod position;
+pub mod process;
+mod records;
+#[cfg(all(feature = "redirect", unix))]
+pub mod redirect;
+mod reopenable;
+mod rotation;
+mod shared;
+#[cfg(all(feature = "signals", not(loom)))]
+pub mod signals;
+mod stats;
+#[cfg(siginfo)]
+mod status;
+#[cfg(feature = "arc-swap")]
+mod swap;
+mod sync;
+#[cfg(all(feature = "sd-notify", unix, not(loom)))]
+mod systemd;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod timing;
+#[cfg(all(feature = "tmpfile", target_os = "linux"))]
+pub mod tmpfile;
+#[cfg(feature = "tokio")]
+mod tokio;
+mod trace;
+mod trigger;
+#[cfg(all(windows, not(loom)))]
+pub mod windows;
+
+use background::Background;
+pub use buffered::BufferedReopen;
+pub use checksum::ChecksumWriter;
+pub use child::ChildWriter;
+#[cfg(feature = "test-util")]
+pub use clock::ManualClock;
+pub use clock::{Clock, RealClock};
+#[cfg(feature = "serde")]
+pub use config::{Compression, ReopenConfig};
+pub use events::ReopenEvent;
+use flush_timeout::FlushTimeout;
+pub use free_space::LowSpaceAction;
+use free_space::SpaceGuard;
+pub use fused::FusedReopen;
+#[cfg(feature = "genio")]
+pub use genio::GenioError;
+#[cfg(unix)]
+pub use group::{Member, ReopenGroup};
+pub use handles::HandleGroup;
+pub use health::ReopenHealth;
+#[cfg(kqueue)]
+pub use kqueue::WatchGuard;
+#[cfg(all(feature = "logger", feature = "signals", not(loom)))]
+pub use logger::install_rotating_logger;
+use nonblocking::ApplyNonblocking;
+pub use nonblocking::SetNonblocking;
+pub use open_error::OpenError;
+use open_time::OpenTimes;
+use position::Position;
+pub use records::RecordWriter;
+pub use reopenable::Reopenable;
+use rotation::SizeLimit;
+use shared::Shared;
+use stats::Stats;
+#[cfg(siginfo)]
+pub use status::StatusSnapshot;
+#[cfg(feature = "arc-swap")]
+pub use swap::SwapReopen;
+pub use sync::SyncReopen;
+pub use timing::CheckTiming;
+use trigger::Trigger;
+
+/// If this build forwards the vectored IO (`read_vectored`, `write_vectored`) to the FD.
+///
+/// This is detected at build time. If `false` (on compilers too old to have vectored IO), the
+/// [`Reopen`] uses the default implementations of these methods, doing one operation per slice.
+pub const HAS_VECTORED: bool = cfg!(vectored);
 
 /// A handle to signal a companion [`Reopen`] object to do a reopen on its next operation.
 ///
 /// Cloning creates interchangeable handles (they all control the same [`Reopen`]). Cloning is
 /// cheap (it's only an [`Arc`] in disguise).
 #[derive(Clone, Debug)]
-pub struct Handle(Arc<AtomicBool>);
+pub struct Handle(Arc<Shared>);
 
 impl Handle {
     /// Signals the companion [`Reopen`](struct.Reopen.html) object to do a reopen on its next
     /// operation.
     pub fn reopen(&self) {
-        self.0.store(true, Ordering::Relaxed);
+        trace::requested(None, "handle");
+        self.0.request();
+    }
+
+    /// Holds back the reopens until [`resume`][Handle::resume].
+    ///
+    /// The requests made in the meantime (or still pending from before) are not lost, they are
+    /// merged and handled by the first operation after the resume. This is useful when replacing
+    /// the files takes several steps (eg. rotating a whole set of logs), so the [`Reopen`] doesn't
+    /// pick up a half-done state.
+    ///
+    /// The pauses nest, each needs its own resume. A reopen already in progress when this is called
+    /// still finishes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Write;
+    /// # use reopen::Reopen;
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::new()))).unwrap();
+    /// let handle = writer.handle();
+    /// handle.pause();
+    /// handle.reopen();
+    /// writer.write_all(b"Hello").unwrap();
+    /// handle.resume();
+    /// // Reopened here, the data is gone with the old FD.
+    /// writer.write_all(b"World").unwrap();
+    /// assert_eq!(b"World", &writer.lock().unwrap()[..]);
+    /// ```
+    pub fn pause(&self) {
+        self.0.pause();
+    }
+
+    /// Ends a [`pause`][Handle::pause].
+    ///
+    /// Everything done before the last resume is visible to the constructor of the reopen it
+    /// releases.
+    ///
+    /// # Panics
+    ///
+    /// If there's no pause to end.
+    pub fn resume(&self) {
+        self.0.resume();
     }
 
     /// Creates an unpaired handle, not connected to any ['Reopen'].
     ///
     /// It can be added to a new [`Reopen`] later on with [`with_handle`][Reopen::with_handle].
     pub fn stub() -> Self {
-        Handle(Arc::new(AtomicBool::new(false)))
+        Handle(Arc::new(Shared::new()))
     }
 }
 
@@ -121,14 +406,113 @@ impl Handle {
 ///
 /// While this reaches the end of the currently opened FD and further read operations would still
 /// produce EOF, reopening the FD may lead to it being readable again. Therefore, reaching EOF is
-/// not necessarily final for [`Reopen`].
+/// not necessarily final for [`Reopen`]. Use [`fuse`][Reopen::fuse] if it should be.
 pub struct Reopen<FD> {
-    signal: Arc<AtomicBool>,
-    constructor: Box<dyn Fn() -> Result<FD, Error> + Send>,
+    shared: Arc<Shared>,
+    /// Nobody else can request a reopen, because the shared state was created inside and no
+    /// [`Handle`] to it was handed out.
+    ///
+    /// The request flag doesn't need to be checked then. This is turned off for good by handing
+    /// out a handle, by taking one from the outside (it may already be registered to signals and
+    /// other sources holding only parts of it) or by requesting a reopen from the inside.
+    solo: Cell<bool>,
+    /// Something besides the request flag might need a look before handing out the FD.
+    ///
+    /// This is set whenever a feature needing it is configured or the FD goes away (or needs its
+    /// hook) and cleared by the slow path once nothing needs it. Without it, handing out the FD
+    /// is just a relaxed load of the request flag.
+    slow: bool,
+    /// Check the request flag only on every n-th operation.
+    check_every: usize,
+    check_timing: CheckTiming,
+    /// Operations to go before the next check of the flag.
+    until_check: usize,
+    generation: u64,
+    constructor: Arc<Mutex<Constructor<FD>>>,
     fd: Option<FD>,
+    /// Opening the new FDs in a helper thread, if turned on.
+    background: Option<Background<FD>>,
+    position: Option<Position<FD>>,
+    /// Watching for a trigger file, if turned on.
+    trigger: Option<Trigger>,
+    /// Watching the free space, if turned on.
+    space_guard: Option<SpaceGuard>,
+    reopen_on_write_zero: bool,
+    reopen_on_broken_pipe: bool,
+    reopen_on_disconnect: bool,
+    /// The raw OS errors of any operation that request a reopen.
+    reopen_on_os_errors: Vec<i32>,
+    /// How many times write_record may reopen.
+    record_retries: usize,
+    /// The buffer for formatting, if turned on.
+    fmt_buffer: Option<Vec<u8>>,
+    /// The limit of reopens, see [`with_max_reopens`][Reopen::with_max_reopens].
+    max_reopens: Option<usize>,
+    /// Reopens done since creation or the last [`reset`][Reopen::reset].
+    reopens: usize,
+    /// Run on each freshly opened FD before it is handed out.
+    open_hook: Option<Hook<FD>>,
+    /// The current FD still needs the open hook to run.
+    open_hook_pending: bool,
+    /// Run on the old FD before it is dropped.
+    close_hook: Option<Hook<FD>>,
+    /// Reopening once the FD grows over a limit, if turned on.
+    size_limit: Option<SizeLimit<FD>>,
+    /// Flushing the old FDs with a time limit, if turned on.
+    flush_timeout: Option<FlushTimeout<FD>>,
+    /// The mode to switch each new FD to, see [`set_nonblocking`][Reopen::set_nonblocking].
+    nonblocking: Option<(bool, ApplyNonblocking<FD>)>,
+    /// The path the constructor opens, if known.
+    path: Option<PathBuf>,
+    /// Create the parent directories of the path before opening.
+    create_parents: bool,
+    /// A name of this instance, for diagnostics.
+    label: Option<String>,
+    /// Bytes written into the current FD.
+    bytes_written: Cell<u64>,
+    /// When the current FD was opened.
+    opened_at: Option<Instant>,
+    open_times: OpenTimes,
+    /// The error of the last failed open, until an open succeeds.
+    last_error: Option<String>,
+    stats: Stats,
+    clock: Arc<dyn Clock>,
+    /// The signal handlers registered for this instance, unregistered on drop.
+    #[cfg(all(feature = "signals", not(loom)))]
+    signals: Vec<signals::SigId>,
 }
 
+type Constructor<FD> = Box<dyn Fn() -> Result<FD, Error> + Send>;
+
+type Hook<FD> = Box<dyn Fn(&mut FD) -> Result<(), Error> + Send>;
+
 impl<FD> Reopen<FD> {
+    /// Creates a new instance from a constructor that is not boxed yet.
+    ///
+    /// This is a convenience wrapper around [`new`][Reopen::new], boxing the constructor
+    /// internally. It is handy especially with free functions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// fn open() -> Result<Vec<u8>, Error> {
+    ///     Ok(Vec::new())
+    /// }
+    ///
+    /// # fn main() -> Result<(), Error> {
+    /// let writer = Reopen::from_fn(open)?;
+    /// # let _ = writer;
+    /// # Ok(()) }
+    /// ```
+    pub fn from_fn<F>(constructor: F) -> Result<Self, Error>
+    where
+        F: Fn() -> Result<FD, Error> + Send + 'static,
+    {
+        Self::new(Box::new(constructor))
+    }
+
     /// Creates a new instance.
     pub fn new(constructor: Box<dyn Fn() -> Result<FD, Error> + Send>) -> Result<Self, Error> {
         Self::with_handle(Handle::stub(), constructor)
@@ -161,16 +545,572 @@ impl<FD> Reopen<FD> {
         constructor: Box<dyn Fn() -> Result<FD, Error> + Send>,
     ) -> Result<Self, Error> {
         let fd = constructor()?;
-        Ok(Self {
-            signal: handle.0,
-            constructor,
-            fd: Some(fd),
-        })
+        Ok(Self::build(Some(handle), constructor, Some(fd)))
+    }
+
+    /// Creates a new instance without opening the FD yet.
+    ///
+    /// Unlike [`new`][Reopen::new], this doesn't call the constructor right away, the FD is
+    /// opened on the first use. Therefore this can't fail, but the first operation can (and
+    /// reading or writing to a FD that doesn't open returns the error). This is useful if the
+    /// file may not be available at startup, see also
+    /// [`block_until_openable`][Reopen::block_until_openable].
+    pub fn lazy(constructor: Box<dyn Fn() -> Result<FD, Error> + Send>) -> Self {
+        Self::build(None, constructor, None)
+    }
+
+    /// Creates a new instance, opening the FD right away if possible.
+    ///
+    /// This is a middle ground between [`new`][Reopen::new] and [`lazy`][Reopen::lazy]. If the
+    /// first open succeeds, this is the same as [`new`][Reopen::new]. If it fails, the
+    /// [`Reopen`] is still returned, without an FD, and the next operation tries to open it
+    /// again (as with [`lazy`][Reopen::lazy]). The error of the first open is returned next to
+    /// it, so it can be logged, and it also shows in the [`health`][Reopen::health].
+    ///
+    /// This is useful at startup, where a transient failure (eg. a log directory on a network
+    /// filesystem not mounted yet) shouldn't prevent the program from starting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, ErrorKind, Write};
+    /// # use reopen::Reopen;
+    /// let (mut writer, error) = Reopen::<Vec<u8>>::new_or_lazy(Box::new(|| {
+    ///     Err(Error::new(ErrorKind::NotFound, "Not mounted yet"))
+    /// }));
+    /// if let Some(e) = error {
+    ///     eprintln!("Can't open the log yet: {}", e);
+    /// }
+    /// // Tries again
+    /// assert!(writer.write_all(b"Hello").is_err());
+    /// ```
+    pub fn new_or_lazy(
+        constructor: Box<dyn Fn() -> Result<FD, Error> + Send>,
+    ) -> (Self, Option<Error>) {
+        match constructor() {
+            Ok(fd) => (Self::build(None, constructor, Some(fd)), None),
+            Err(e) => {
+                let mut reopen = Self::build(None, constructor, None);
+                reopen.open_failed(&e);
+                (reopen, Some(e))
+            }
+        }
+    }
+
+    /// Creates a new instance adopting an already open FD.
+    ///
+    /// The FD is used until the first reopen, the constructor is called only for the reopens.
+    /// This is useful when the initial FD comes from somewhere else (eg. it was inherited from
+    /// the parent process).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use reopen::Reopen;
+    /// let writer = Reopen::with_fd(b"initial".to_vec(), Box::new(|| Ok(Vec::new())));
+    /// # let _ = writer;
+    /// ```
+    pub fn with_fd(fd: FD, constructor: Box<dyn Fn() -> Result<FD, Error> + Send>) -> Self {
+        Self::build(None, constructor, Some(fd))
+    }
+
+    /// Puts the instance together.
+    ///
+    /// Without a `handle`, a fresh one is created and nobody else can request a reopen until one
+    /// is handed out.
+    fn build(handle: Option<Handle>, constructor: Constructor<FD>, fd: Option<FD>) -> Self {
+        let solo = handle.is_none();
+        let handle = handle.unwrap_or_else(Handle::stub);
+        let clock: Arc<dyn Clock> = Arc::new(RealClock);
+        let opened_at = if fd.is_some() {
+            Some(clock.now())
+        } else {
+            None
+        };
+        Self {
+            solo: Cell::new(solo),
+            slow: true,
+            shared: handle.0,
+            check_every: 1,
+            check_timing: CheckTiming::Before,
+            until_check: 0,
+            generation: 0,
+            constructor: Arc::new(Mutex::new(constructor)),
+            fd,
+            background: None,
+            position: None,
+            trigger: None,
+            space_guard: None,
+            reopen_on_write_zero: false,
+            reopen_on_broken_pipe: false,
+            reopen_on_disconnect: false,
+            reopen_on_os_errors: Vec::new(),
+            record_retries: 1,
+            fmt_buffer: None,
+            max_reopens: None,
+            reopens: 0,
+            open_hook: None,
+            open_hook_pending: false,
+            close_hook: None,
+            size_limit: None,
+            flush_timeout: None,
+            nonblocking: None,
+            path: None,
+            create_parents: false,
+            label: None,
+            bytes_written: Cell::new(0),
+            opened_at,
+            open_times: OpenTimes::default(),
+            last_error: None,
+            stats: Stats::new(None),
+            clock,
+            #[cfg(all(feature = "signals", not(loom)))]
+            signals: Vec::new(),
+        }
     }
 
     /// Returns a handle to signal this `Reopen` to perform the reopening.
     pub fn handle(&self) -> Handle {
-        Handle(Arc::clone(&self.signal))
+        self.solo.set(false);
+        Handle(Arc::clone(&self.shared))
+    }
+
+    /// Pairs this with a different handle.
+    ///
+    /// From now on, this is controlled by the given handle (and its clones), for example to switch
+    /// from one trigger of reopens to another. The handles obtained before (and signals
+    /// registered through them) no longer control this [`Reopen`]. Similarly, the receivers from
+    /// [`subscribe`][Reopen::subscribe] obtained before stay with the old handle and get no more
+    /// events.
+    ///
+    /// If `keep_pending` is set and a reopen was requested through the old handle and didn't
+    /// happen yet, it is carried over. Otherwise, only the requests of the new handle count.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::{Handle, Reopen};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?;
+    /// let old = writer.handle();
+    /// let new = Handle::stub();
+    /// writer.set_handle(new.clone(), false);
+    /// old.reopen();
+    /// writer.write_all(b"Not reopened")?;
+    /// assert_eq!(b"Not reopened", &writer.lock()?[..]);
+    /// # Ok(()) }
+    /// ```
+    pub fn set_handle(&mut self, handle: Handle, keep_pending: bool) {
+        let pending = self.shared.requested();
+        // Whoever passed the handle in may have registered it (eg. to a signal) already.
+        self.solo.set(false);
+        self.shared = handle.0;
+        self.until_check = 0;
+        if pending && keep_pending {
+            self.shared.restore_request();
+        }
+    }
+
+    /// Replaces the current FD with the given one.
+    ///
+    /// The old FD (if any) is closed, the same way as on a reopen, and the new one becomes the
+    /// current one. A pending reopen is cleared, as the new FD takes its place. This is useful
+    /// when the replacement comes from somewhere else (eg. passed over a unix socket by the
+    /// previous instance during a graceful restart).
+    ///
+    /// The FD is adopted the same way as in [`with_fd`][Reopen::with_fd], so the
+    /// [header][Reopen::with_header] is not written into it. It doesn't count as a reopen towards
+    /// [`with_max_reopens`][Reopen::with_max_reopens] and it doesn't clear the
+    /// [poisoning][Reopen::is_poisoned]; use [`reset`][Reopen::reset] for that.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?;
+    /// writer.set_fd(b"Adopted ".to_vec());
+    /// writer.write_all(b"data")?;
+    /// assert_eq!(b"Adopted data", &writer.lock()?[..]);
+    /// # Ok(()) }
+    /// ```
+    pub fn set_fd(&mut self, fd: FD) {
+        self.close();
+        self.shared.take_request();
+        self.install(fd);
+        self.open_hook_pending = false;
+    }
+
+    /// The path the FD is opened from.
+    ///
+    /// This is known only if the [`Reopen`] was created by one of the path-based helpers (like
+    /// [`append_to`][Reopen::append_to]). For ones created from an arbitrary constructor, this
+    /// returns `None`.
+    #[allow(clippy::option_as_ref_deref)] // as_deref is too new for our rustc version policy
+    pub fn current_path(&self) -> Option<&Path> {
+        self.path.as_ref().map(PathBuf::as_path)
+    }
+
+    /// Names this instance.
+    ///
+    /// The label shows in the [`Debug`] output. With the `metrics` feature, it is also used to
+    /// tag the exported metrics.
+    ///
+    /// # Metrics
+    ///
+    /// With the `metrics` feature turned on, these metrics are exported through the
+    /// [`metrics`](https://docs.rs/metrics) crate:
+    ///
+    /// * `reopen.requests_total` (counter): number of reopen requests acted upon. Requests that
+    ///   come before the [`Reopen`] gets to them are merged into one, and a request retried after
+    ///   a failed open counts only once.
+    /// * `reopen.reopens_total` (counter): number of successful reopens.
+    /// * `reopen.open_failures_total` (counter): number of failed attempts to reopen.
+    /// * `reopen.bytes_written_total` (counter): number of bytes written through the [`Reopen`].
+    /// * `reopen.open_duration_seconds` (histogram): how long the attempts to open took (see
+    ///   [`last_open_duration`][Reopen::last_open_duration]).
+    /// * `reopen.pending` (gauge): `1` while a request is being acted upon but the reopen didn't
+    ///   succeed yet (because the opens fail), `0` otherwise. This is updated only by the
+    ///   operations, a request nobody acted upon yet doesn't show.
+    ///
+    /// If a label is set, the metrics have a `label` tag with it. The metrics are registered
+    /// with the recorder when the [`Reopen`] is created and again when a label is set, so the
+    /// recorder needs to be installed before that.
+    pub fn with_label<L: Into<String>>(mut self, label: L) -> Self {
+        let label = label.into();
+        self.stats = Stats::new(Some(&label));
+        self.label = Some(label);
+        self
+    }
+
+    /// Checks for reopen requests only on every n-th operation.
+    ///
+    /// Checking if a reopen was requested is cheap, but not free. For very hot writers, it may be
+    /// worth to check only once in a while.
+    ///
+    /// This comes at the cost of staleness. A requested reopen may be delayed by up to `n`
+    /// operations (every use of [`lock`][Reopen::lock] counts as one, that includes each
+    /// [`write`][Write::write] and similar). For a writer that is idle after the request, that may
+    /// be an arbitrarily long time in the old file. Each operation is also done in the old file
+    /// until then.
+    ///
+    /// The first operation after creation and the first operation after an error always check.
+    ///
+    /// The default is 1 (check on every operation).
+    pub fn check_every(mut self, n: NonZeroUsize) -> Self {
+        self.check_every = n.get();
+        self.slow = true;
+        self.until_check = 0;
+        self
+    }
+
+    /// Sets if the reopen happens before or after the operations.
+    ///
+    /// By default ([`CheckTiming::Before`]), a pending reopen is done before the next operation,
+    /// so that operation already goes to the new FD. With [`CheckTiming::After`], the current FD
+    /// is used up first (eg. a reader reads the rest of the old file before switching to the new
+    /// one), see its documentation for the details.
+    ///
+    /// With [`CheckTiming::After`], the [`check_every`][Reopen::check_every] has no effect, the
+    /// request is checked after each operation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Cursor, Error, Read};
+    /// # use reopen::{CheckTiming, Reopen};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut reader = Reopen::new(Box::new(|| Ok(Cursor::new(b"data"))))?
+    ///     .check_timing(CheckTiming::After);
+    /// let mut start = [0; 2];
+    /// reader.read_exact(&mut start)?;
+    /// reader.handle().reopen();
+    /// // Reads the rest of the old one, then continues in the new one.
+    /// let mut rest = [0; 4];
+    /// reader.read_exact(&mut rest)?;
+    /// assert_eq!(b"tada", &rest);
+    /// # Ok(()) }
+    /// ```
+    pub fn check_timing(mut self, timing: CheckTiming) -> Self {
+        self.check_timing = timing;
+        self
+    }
+
+    /// Sets the source of time.
+    ///
+    /// All the time-based behavior of this [`Reopen`] uses this clock. By default, it's the
+    /// [`RealClock`], tests may want to replace it with one they can control.
+    ///
+    /// If there's an FD open already, its [age][ReopenHealth::opened_at] is measured from now on.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        if self.opened_at.is_some() {
+            self.opened_at = Some(clock.now());
+        }
+        self.clock = clock;
+        self
+    }
+
+    /// The clock this [`Reopen`] uses (see [`with_clock`][Reopen::with_clock]).
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    /// Waits until the FD can be opened.
+    ///
+    /// This calls the constructor repeatedly, every `poll`, until it succeeds or until `timeout`
+    /// elapses. The opened FD replaces the current one (if any). It is meant for startup, when a
+    /// file may be waiting for its filesystem to be mounted, together with
+    /// [`lazy`][Reopen::lazy].
+    ///
+    /// The waiting uses the [clock][Reopen::with_clock].
+    ///
+    /// # Errors
+    ///
+    /// If the timeout elapses, the error of the last attempt is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::fs::OpenOptions;
+    /// # use std::io::Error;
+    /// # use std::time::Duration;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut log = Reopen::lazy(Box::new(|| {
+    ///     OpenOptions::new().create(true).append(true).open("/mnt/log/file")
+    /// }));
+    /// log.block_until_openable(Duration::from_secs(30), Duration::from_millis(100))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn block_until_openable(&mut self, timeout: Duration, poll: Duration) -> Result<(), Error> {
+        let deadline = self.clock.now() + timeout;
+        loop {
+            match self.construct() {
+                Ok(fd) => {
+                    self.close();
+                    self.install(fd);
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.open_failed(&e);
+                    let now = self.clock.now();
+                    if now >= deadline {
+                        return Err(e);
+                    }
+                    self.clock.sleep(cmp::min(poll, deadline - now));
+                }
+            }
+        }
+    }
+
+    /// Checks if a reopen was requested and is not done yet.
+    ///
+    /// A request stays pending until a new FD is successfully opened as a result. If opening
+    /// fails, the request stays pending and the next operation tries again.
+    pub fn is_pending(&self) -> bool {
+        self.shared.requested()
+    }
+
+    /// Limits how many times this may reopen.
+    ///
+    /// This is a safety valve against something requesting reopens all the time (eg. a bug in
+    /// the application). Once `max` reopens happened, further reopen requests are not satisfied.
+    /// Instead, all the operations fail with an error until [`reset`][Reopen::reset] is called
+    /// (and then the pending request is satisfied).
+    ///
+    /// Only reopens as a result of requests count, opening a new FD after a failure (eg. in
+    /// [`write_record`][Reopen::write_record]) doesn't.
+    ///
+    /// There's no limit by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?.with_max_reopens(1);
+    /// writer.handle().reopen();
+    /// writer.write_all(b"Reopened once")?;
+    /// writer.handle().reopen();
+    /// assert!(writer.write_all(b"Not again").is_err());
+    /// writer.reset();
+    /// writer.write_all(b"Reopened again")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn with_max_reopens(mut self, max: usize) -> Self {
+        self.max_reopens = Some(max);
+        self
+    }
+
+    /// Checks if the operations fail because of some limiting policy.
+    ///
+    /// This is the case when the [reopen limit][Reopen::with_max_reopens] was reached and
+    /// another reopen is pending (with [`check_every`][Reopen::check_every], the operations may
+    /// notice only after a while). The poisoning is lifted by [`reset`][Reopen::reset].
+    ///
+    /// It is also the case while there's not enough free space and the
+    /// [guard][Reopen::with_min_free_space_by] is set to [`LowSpaceAction::Refuse`]. This is
+    /// lifted once a later check finds enough space.
+    ///
+    /// Errors of the FD itself or failures to open a new one don't poison (the next operation
+    /// simply tries again).
+    pub fn is_poisoned(&self) -> bool {
+        self.poison_reason().is_some()
+    }
+
+    /// Describes why this is [poisoned][Reopen::is_poisoned], if it is.
+    ///
+    /// The description is the same as the message of the errors the operations fail with.
+    pub fn poison_reason(&self) -> Option<&'static str> {
+        if self.shared.requested() && self.reopen_limit_reached() {
+            Some(REOPEN_LIMIT_EXCEEDED)
+        } else if self.space_refused() {
+            Some(free_space::LOW_FREE_SPACE)
+        } else {
+            None
+        }
+    }
+
+    /// Lifts the [poisoning][Reopen::is_poisoned].
+    ///
+    /// This clears the counters behind the policies (eg. the count of reopens for
+    /// [`with_max_reopens`][Reopen::with_max_reopens]), so the next operation satisfies a pending
+    /// request and opens a fresh FD (even with [`check_every`][Reopen::check_every]). The next
+    /// operation also checks the [free space][Reopen::with_min_free_space_by] right away.
+    pub fn reset(&mut self) {
+        self.reopens = 0;
+        self.until_check = 0;
+        self.recheck_space();
+    }
+
+    /// Subscribes to a stream of [`ReopenEvent`]s.
+    ///
+    /// Each call creates a new independent receiver. The events are never allowed to slow down
+    /// the IO operations; if the receiver doesn't keep up and has too many (currently 128) events
+    /// queued, further events are dropped for it and counted (see
+    /// [`dropped_events`][Reopen::dropped_events]).
+    ///
+    /// Dropping the receiver unsubscribes it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::{Reopen, ReopenEvent};
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?;
+    /// let events = writer.subscribe();
+    /// writer.handle().reopen();
+    /// writer.write_all(b"Hello")?;
+    ///
+    /// let received = events.try_iter().collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     vec![
+    ///         ReopenEvent::ReopenRequested,
+    ///         ReopenEvent::Closed,
+    ///         ReopenEvent::OpenSucceeded { generation: 1 },
+    ///     ],
+    ///     received
+    /// );
+    /// # Ok(()) }
+    /// ```
+    pub fn subscribe(&self) -> Receiver<ReopenEvent> {
+        self.shared.events.subscribe()
+    }
+
+    /// How many events were dropped because some subscriber didn't keep up.
+    ///
+    /// This is a total across all the subscribers.
+    pub fn dropped_events(&self) -> usize {
+        self.shared.events.dropped()
+    }
+
+    /// Treat a write of zero bytes as a sign the FD is dead.
+    ///
+    /// A [`write`][Write::write] returning `Ok(0)` for a non-empty buffer usually means the other
+    /// side can't accept any more data (for example a closed pipe). If this is turned on, such a
+    /// write requests a reopen before the next operation, as if [`Handle::reopen`] was called.
+    ///
+    /// The zero-sized write (or the [`WriteZero`][std::io::ErrorKind::WriteZero] error from
+    /// [`write_all`][Write::write_all]) is still returned to the caller; no retry happens
+    /// internally. Therefore, if the new FD also refuses the data, it is simply reported again and
+    /// another reopen is scheduled.
+    ///
+    /// This is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?
+    ///     .reopen_on_write_zero(true);
+    /// # let _ = writer;
+    /// # Ok(()) }
+    /// ```
+    pub fn reopen_on_write_zero(mut self, enable: bool) -> Self {
+        self.reopen_on_write_zero = enable;
+        self
+    }
+
+    /// Treat a [`BrokenPipe`][std::io::ErrorKind::BrokenPipe] error from a write as a sign the FD
+    /// is dead.
+    ///
+    /// This is the same as [`reopen_on_write_zero`][Reopen::reopen_on_write_zero], but for the
+    /// error a pipe or a socket returns when the other side went away (eg. a child process
+    /// reading the data exited). The error is still returned to the caller and a reopen is
+    /// requested before the next operation.
+    ///
+    /// This is off by default.
+    pub fn reopen_on_broken_pipe(mut self, enable: bool) -> Self {
+        self.reopen_on_broken_pipe = enable;
+        self
+    }
+
+    /// Treat the errors of a socket that lost its peer as a sign the FD is dead.
+    ///
+    /// This is the same as [`reopen_on_broken_pipe`][Reopen::reopen_on_broken_pipe], but for
+    /// the [`ConnectionRefused`][ErrorKind::ConnectionRefused],
+    /// [`ConnectionReset`][ErrorKind::ConnectionReset],
+    /// [`ConnectionAborted`][ErrorKind::ConnectionAborted] and
+    /// [`NotConnected`][ErrorKind::NotConnected] errors. These come from a connected socket when
+    /// the server on the other side restarted (eg. a datagram socket to a syslog daemon returns
+    /// `ECONNREFUSED` forever after that). The error is still returned to the caller and a
+    /// reopen (usually connecting again) is requested before the next operation.
+    ///
+    /// This is off by default.
+    pub fn reopen_on_disconnect(mut self, enable: bool) -> Self {
+        self.reopen_on_disconnect = enable;
+        self
+    }
+
+    /// Treat the given OS errors as a sign the FD is dead.
+    ///
+    /// When any operation (reading or writing) fails with one of these raw OS error codes (see
+    /// [`Error::raw_os_error`]), a reopen is requested before the next operation. The error is
+    /// still returned to the caller. This is for the errors that have no
+    /// [`ErrorKind`] of their own, eg. `EIO` or `ENXIO` from a device that got disconnected.
+    ///
+    /// This replaces the codes set before. None are set by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// // EIO on Linux
+    /// let writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?.reopen_on_os_errors(vec![5]);
+    /// # let _ = writer;
+    /// # Ok(()) }
+    /// ```
+    pub fn reopen_on_os_errors<I: IntoIterator<Item = i32>>(mut self, codes: I) -> Self {
+        self.reopen_on_os_errors = codes.into_iter().collect();
+        self
     }
 
     /// Lock the [`Reopen`] against reopening in the middle of operation.
@@ -211,79 +1151,882 @@ impl<FD> Reopen<FD> {
     /// assert_eq!(b"Another message", &writer.lock()?[..]);
     /// # Ok(()) }
     /// ```
+    #[inline]
     pub fn lock(&mut self) -> Result<&mut FD, Error> {
-        if self.signal.swap(false, Ordering::Relaxed) {
+        self.open_current().map_err(Error::from)
+    }
+
+    /// Provides the current FD for an operation of our own.
+    ///
+    /// Unlike [`lock`][Reopen::lock], the FD is not handed out to the user, so whatever is
+    /// written into it is counted.
+    #[inline]
+    fn current(&mut self) -> Result<&mut FD, Error> {
+        self.prepared().map_err(Error::from)
+    }
+
+    /// Provides the current FD, opening a new one first if needed.
+    ///
+    /// This is the same as [`lock`][Reopen::lock], but the failure to get the FD ready is
+    /// reported as a dedicated [`OpenError`]. This allows the callers to tell it apart from the
+    /// errors of the operations on the FD itself, eg. to report that the log sink is not
+    /// available at all.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, ErrorKind};
+    /// # use reopen::Reopen;
+    /// let mut writer = Reopen::lazy(Box::new(|| -> Result<Vec<u8>, Error> {
+    ///     Err(Error::new(ErrorKind::NotFound, "No sink"))
+    /// }));
+    /// let err = writer.open_current().unwrap_err();
+    /// assert_eq!(ErrorKind::NotFound, err.kind());
+    /// ```
+    #[inline]
+    pub fn open_current(&mut self) -> Result<&mut FD, OpenError> {
+        // Whatever the user writes into it is not counted.
+        self.size_handed_out();
+        self.prepared()
+    }
+
+    /// Gets the current FD ready, opening a new one if needed.
+    #[inline]
+    fn prepared(&mut self) -> Result<&mut FD, OpenError> {
+        // The common case is there's nothing to do and we just hand out the current FD. Keep
+        // that cheap, everything else happens out of line.
+        if !self.quiet() {
+            if let Err(e) = self.prepare() {
+                self.until_check = 0;
+                return Err(OpenError(e));
+            }
+        }
+        Ok(self.fd.as_mut().expect("Prepared FD missing"))
+    }
+
+    /// Checks there's nothing to do before handing out the FD.
+    ///
+    /// The request is checked with a relaxed load only, the real (synchronizing) take happens in
+    /// [`prepare`][Reopen::prepare] if this says it's set. Everything else is behind a single
+    /// flag, see [`quiet_slow`][Reopen::quiet_slow].
+    #[inline]
+    fn quiet(&mut self) -> bool {
+        if self.slow {
+            self.quiet_slow()
+        } else {
+            !self.shared.maybe_requested()
+        }
+    }
+
+    /// The [`quiet`][Reopen::quiet] when some other feature needs a look.
+    ///
+    /// The request is not checked at all if nobody could have set it or if it's not its turn
+    /// (see [`check_every`][Reopen::check_every]). The background open, the trigger file and the
+    /// free space guard take the slow path of [`prepare`][Reopen::prepare] only when there's
+    /// something for them to do.
+    fn quiet_slow(&mut self) -> bool {
+        if self.fd.is_none()
+            || self.open_hook_pending
+            || self.background_pending()
+            || self.polls_due()
+        {
+            return false;
+        }
+        self.slow = self.background.is_some()
+            || self.trigger.is_some()
+            || self.space_guard.is_some()
+            || self.check_every > 1
+            || self.solo.get();
+        if self.solo.get() {
+            return true;
+        }
+        if self.until_check > 0 {
+            self.until_check -= 1;
+            return true;
+        }
+        self.until_check = self.check_every - 1;
+        !self.shared.maybe_requested()
+    }
+
+    /// The trigger file or the free space is to be checked.
+    ///
+    /// Reads the clock only if any of them is set.
+    #[inline]
+    fn polls_due(&self) -> bool {
+        if self.trigger.is_none() && self.space_guard.is_none() {
+            return false;
+        }
+        let now = self.clock.now();
+        self.trigger_due(now) || self.free_space_due(now)
+    }
+
+    /// Does all the reopening, opening and initialization needed for the FD to be used.
+    ///
+    /// With [`CheckTiming::After`], the requests are left for after the operation (unless there's
+    /// no FD to operate on anyway).
+    #[cold]
+    fn prepare(&mut self) -> Result<(), Error> {
+        let requests = self.check_timing == CheckTiming::Before || self.fd.is_none();
+        self.prepare_with(requests)
+    }
+
+    /// Reopens after an operation, see [`CheckTiming::After`].
+    ///
+    /// The result of the operation is what the caller gets, so a failed reopen stays pending and
+    /// the next operation retries and reports it.
+    #[inline]
+    fn check_after(&mut self) {
+        if self.check_timing == CheckTiming::After && self.shared.maybe_requested() {
+            self.switch();
+        }
+    }
+
+    /// Reopens after a read that produced no data, see [`CheckTiming::After`].
+    ///
+    /// Returns if the read should be repeated on the new FD.
+    #[inline]
+    fn switch_after_read<T>(&mut self, result: &Result<T, Error>, at_end: bool) -> bool {
+        if self.check_timing == CheckTiming::Before || !self.shared.maybe_requested() {
+            return false;
+        }
+        let (drained, repeat) = match *result {
+            Ok(_) => (at_end, true),
+            Err(ref e) => (
+                e.kind() != ErrorKind::Interrupted,
+                e.kind() == ErrorKind::WouldBlock,
+            ),
+        };
+        drained && self.switch() && repeat
+    }
+
+    /// Handles the request right away, returns if it succeeded.
+    #[cold]
+    fn switch(&mut self) -> bool {
+        let ok = self.prepare_with(true).is_ok();
+        if !ok {
+            self.until_check = 0;
+        }
+        ok
+    }
+
+    #[cold]
+    fn prepare_with(&mut self, requests: bool) -> Result<(), Error> {
+        // Whatever happens here, have a look next time.
+        self.slow = true;
+        self.poll_trigger();
+        self.poll_background();
+        self.poll_free_space()?;
+        if requests && self.shared.maybe_requested() && self.reopen_limit_reached() {
+            // Leave the request pending, for after a reset.
+            return Err(reopen_limit_error());
+        }
+        let requested = requests && self.shared.take_request();
+        if requested {
+            self.stats.requested();
+            if self.fd.is_some() && self.background.is_some() {
+                self.start_background(true);
+            } else {
+                self.close();
+            }
+        }
+        let wait = self.background.as_ref().and_then(|b| b.wait);
+        if let (None, Some(wait)) = (self.fd.as_ref(), wait) {
+            self.open_background(requested, wait)?;
+        }
+        if self.fd.is_none() {
+            let result = self.construct();
+            match result {
+                Ok(fd) => {
+                    if requested {
+                        self.reopens += 1;
+                    }
+                    self.install(fd);
+                }
+                Err(e) => {
+                    self.open_failed(&e);
+                    if requested {
+                        // The request is acknowledged only by a successful open. Any further
+                        // requests that came in the meantime are merged into it, as the next
+                        // attempt happens after them.
+                        self.shared.restore_request();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        if let Err(e) = self.restore_position() {
             self.fd.take();
+            return Err(e);
+        }
+        let fd = self.fd.as_mut().unwrap();
+        if self.open_hook_pending {
+            if let Some((nonblocking, apply)) = self.nonblocking {
+                if let Err(e) = apply(fd, nonblocking) {
+                    self.fd.take();
+                    return Err(e);
+                }
+            }
+            if let Some(hook) = self.open_hook.as_ref() {
+                if let Err(e) = hook(fd) {
+                    // Half-initialized FD is of no use, try again with a new one next time.
+                    self.fd.take();
+                    return Err(e);
+                }
+            }
+            self.open_hook_pending = false;
         }
+        Ok(())
+    }
+}
+
+impl<FD: Read> Reopen<FD> {
+    /// Reads from the current FD and then reopens.
+    ///
+    /// This runs the `read_fn` on the current FD, without checking for reopen requests first.
+    /// Then it reopens, so further operations use a new FD. This is for readers that decide
+    /// themselves when to switch to a new file, after consuming whatever is left in the old one.
+    ///
+    /// If the `read_fn` fails, the error is returned and no reopen happens. If the reopen fails,
+    /// the result of the `read_fn` is still returned and the reopen stays pending (see
+    /// [`is_pending`][Reopen::is_pending]), the next operation tries again and returns the error
+    /// if that fails too.
+    ///
+    /// If there's no current FD (eg. after a failed open), a new one is opened first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Cursor, Error, Read};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut reader = Reopen::new(Box::new(|| Ok(Cursor::new(b"data"))))?;
+    /// let mut start = [0; 2];
+    /// reader.read_exact(&mut start)?;
+    /// let mut rest = Vec::new();
+    /// reader.peek_then_reopen(|fd| fd.read_to_end(&mut rest))?;
+    /// assert_eq!(b"ta", &rest[..]);
+    /// // Starts over in the new one.
+    /// reader.read_exact(&mut start)?;
+    /// assert_eq!(b"da", &start);
+    /// # Ok(()) }
+    /// ```
+    pub fn peek_then_reopen<T, F>(&mut self, read_fn: F) -> Result<T, Error>
+    where
+        F: FnOnce(&mut FD) -> Result<T, Error>,
+    {
         if self.fd.is_none() {
-            self.fd = Some((self.constructor)()?);
+            self.current()?;
         }
-        Ok(self.fd.as_mut().unwrap())
+        let result = read_fn(self.fd.as_mut().expect("Opened FD missing"));
+        self.check_error(&result);
+        let value = result?;
+        self.request_inside();
+        self.switch();
+        Ok(value)
+    }
+}
+
+#[cfg(unix)]
+impl<FD: AsRawFd> Reopen<FD> {
+    /// Runs a closure with the raw file descriptor of the current FD.
+    ///
+    /// This is for calling things not available through the FD itself (eg. `posix_fallocate`,
+    /// `posix_fadvise` or `fcntl`). The FD is opened (or reopened) first if needed, the same as
+    /// with [`lock`][Reopen::lock], and no reopen happens while the closure runs. Therefore the
+    /// descriptor stays valid for the whole duration of the closure (but not after that, so it
+    /// should not be stored anywhere).
+    ///
+    /// # Platform support
+    ///
+    /// This is available only on unix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut log = Reopen::append_to("/log/file")?;
+    /// let fd = log.with_raw_fd(|fd| Ok(fd))?;
+    /// # let _ = fd;
+    /// # Ok(()) }
+    /// ```
+    pub fn with_raw_fd<T, F>(&mut self, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(RawFd) -> Result<T, Error>,
+    {
+        let fd = self.lock()?.as_raw_fd();
+        f(fd)
+    }
+}
+
+impl<FD: Write> Reopen<FD> {
+    /// Sets how many times [`write_record`][Reopen::write_record] may reopen.
+    ///
+    /// The default is 1.
+    pub fn record_retries(mut self, retries: usize) -> Self {
+        self.record_retries = retries;
+        self
+    }
+
+    /// Formats into a buffer first in [`write_fmt`][Write::write_fmt].
+    ///
+    /// Formatting writes each fragment (each literal piece and each argument) separately. This
+    /// is fine for buffered FDs, but if each write is expensive (eg. an unbuffered file or a
+    /// socket), it is better to format the whole thing into a buffer first and write it at once.
+    /// When this is turned on, a buffer is kept around (and reused) for that.
+    ///
+    /// As a side effect, if formatting fails, nothing is written.
+    ///
+    /// This is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?.buffer_fmt(true);
+    /// writeln!(writer, "Hello {}", "world")?;
+    /// assert_eq!(b"Hello world\n", &writer.lock()?[..]);
+    /// # Ok(()) }
+    /// ```
+    pub fn buffer_fmt(mut self, enable: bool) -> Self {
+        self.fmt_buffer = if enable { Some(Vec::new()) } else { None };
+        self
+    }
+
+    /// Writes a whole record, reopening and starting over on failure.
+    ///
+    /// This is similar to [`write_all`][Write::write_all], but if the write fails in the middle,
+    /// the FD is thrown away, a new one is opened and the *whole* record is written again into it
+    /// (a partial record is usually of no use). This is attempted at most as many times as set by
+    /// [`record_retries`][Reopen::record_retries], then the error is returned.
+    ///
+    /// Only the errors meaning the FD won't accept any more data lead to a reopen (the same ones
+    /// as in [`write_all_with_reopen_retry`][Reopen::write_all_with_reopen_retry]). Other errors
+    /// are returned right away and the FD is kept, as a new one would most likely fail the same
+    /// way.
+    ///
+    /// # Duplication
+    ///
+    /// A failed attempt might have written part of the record (or even all of it, in case the
+    /// error came only after that) into the old FD. That part is not taken back, therefore the
+    /// data may be duplicated across the old and the new FD. This trades duplication for
+    /// delivery of the whole record.
+    ///
+    /// # Errors
+    ///
+    /// The last write error, if all the attempts failed or the error is not one to reopen on. If
+    /// opening a new FD fails, that error is returned right away.
+    pub fn write_record(&mut self, buf: &[u8]) -> Result<(), Error> {
+        let mut attempt = 0;
+        loop {
+            match self.current()?.write_all(buf) {
+                Ok(()) => {
+                    self.written(buf.len());
+                    return Ok(());
+                }
+                Err(e) => {
+                    if attempt >= self.record_retries || !self.is_dead_error(&e) {
+                        return Err(e);
+                    }
+                    attempt += 1;
+                    self.close();
+                }
+            }
+        }
+    }
+
+    /// Writes the whole buffer, reopening and starting over if the FD turns out to be dead.
+    ///
+    /// This is like [`write_record`][Reopen::write_record], but the number of reopens is given
+    /// for each call and only the errors that mean the FD won't accept any more data lead to a
+    /// reopen: [`BrokenPipe`][ErrorKind::BrokenPipe], [`WriteZero`][ErrorKind::WriteZero], the
+    /// lost connections ([`ConnectionReset`][ErrorKind::ConnectionReset],
+    /// [`ConnectionAborted`][ErrorKind::ConnectionAborted],
+    /// [`ConnectionRefused`][ErrorKind::ConnectionRefused],
+    /// [`NotConnected`][ErrorKind::NotConnected]) and the OS errors set by
+    /// [`reopen_on_os_errors`][Reopen::reopen_on_os_errors]. Other errors are returned right
+    /// away, as a new FD would most likely fail the same way.
+    ///
+    /// The same as with [`write_record`][Reopen::write_record], the data may get duplicated
+    /// across the old and the new FD. Use this only for sinks where that's acceptable.
+    ///
+    /// # Errors
+    ///
+    /// The error of the last attempt, if it still failed after `max_reopens` reopens or it was
+    /// not an error to reopen on. If opening a new FD fails, that error is returned right away.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?;
+    /// writer.write_all_with_reopen_retry(b"Hello\n", 3)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn write_all_with_reopen_retry(
+        &mut self,
+        buf: &[u8],
+        max_reopens: usize,
+    ) -> Result<(), Error> {
+        let mut reopens = 0;
+        loop {
+            let generation = self.generation;
+            let e = match self.write_all(buf) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            if reopens >= max_reopens || !self.is_dead_error(&e) {
+                return Err(e);
+            }
+            reopens += 1;
+            // It may have reopened already (with CheckTiming::After).
+            if self.generation == generation {
+                self.request_inside();
+                if let Err(e) = self.prepare_with(true) {
+                    self.until_check = 0;
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Checks if the error means the FD won't accept any more data.
+    fn is_dead_error(&self, e: &Error) -> bool {
+        match e.kind() {
+            ErrorKind::BrokenPipe
+            | ErrorKind::WriteZero
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected => true,
+            _ => match e.raw_os_error() {
+                Some(code) => self.reopen_on_os_errors.contains(&code),
+                None => false,
+            },
+        }
+    }
+
+    /// Writes a header at the start of each newly opened FD.
+    ///
+    /// The header is written before any user data reaches the FD, including the one opened
+    /// during construction (therefore, this should be called before the first use of the
+    /// [`Reopen`]). If writing the header fails, the error is returned from the current operation
+    /// and the FD is thrown away, so a new one is opened next time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?
+    ///     .with_header("# log format 1\n");
+    /// writer.write_all(b"Hello\n")?;
+    /// assert_eq!(b"# log format 1\nHello\n", &writer.lock()?[..]);
+    /// # Ok(()) }
+    /// ```
+    pub fn with_header<B: Into<Vec<u8>>>(mut self, header: B) -> Self {
+        let header = header.into();
+        self.open_hook = Some(Box::new(move |fd: &mut FD| fd.write_all(&header)));
+        self.open_hook_pending = true;
+        self.slow = true;
+        self
+    }
+
+    /// Writes a footer at the end of each FD before it is closed.
+    ///
+    /// This happens when the FD is being replaced by a new one as part of a reopen and when the
+    /// whole [`Reopen`] is dropped. The FD is flushed after writing the footer.
+    ///
+    /// As the FD is going away anyway, errors while writing the footer are ignored.
+    pub fn with_footer<B: Into<Vec<u8>>>(mut self, footer: B) -> Self {
+        let footer = footer.into();
+        self.close_hook = Some(Box::new(move |fd: &mut FD| {
+            fd.write_all(&footer)?;
+            fd.flush()
+        }));
+        self
+    }
+}
+
+impl<FD> Reopen<FD> {
+    fn construct(&mut self) -> Result<FD, Error> {
+        self.create_parents()?;
+        let span = trace::open_span(self.label());
+        let _enter = span.enter();
+        let start = self.clock.now();
+        let result = match self.background.as_ref().and_then(|b| b.wait) {
+            Some(wait) => self.construct_background(wait),
+            None if self.background_pending() => {
+                self.take_background().expect("Background open in progress")
+            }
+            None => {
+                let constructor = self
+                    .constructor
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                constructor()
+            }
+        };
+        let duration = self.clock.now() - start;
+        trace::open_finished(self.label(), &result, duration);
+        self.open_times.record(duration);
+        self.stats.opened_in(duration);
+        result
+    }
+
+    /// Starts using a freshly opened FD.
+    fn install(&mut self, fd: FD) {
+        self.generation += 1;
+        trace::reopened(self.label(), self.generation);
+        self.stats.reopened();
+        #[cfg(siginfo)]
+        self.shared.status.opened(self.generation);
+        self.shared.events.emit(ReopenEvent::OpenSucceeded {
+            generation: self.generation,
+        });
+        self.fd = Some(fd);
+        self.size_opened();
+        self.bytes_written.set(0);
+        self.opened_at = Some(self.clock.now());
+        self.last_error = None;
+        self.open_hook_pending = true;
+        self.slow = true;
+    }
+
+    /// Counts the bytes written into the current FD.
+    fn written(&self, bytes: usize) {
+        self.bytes_written
+            .set(self.bytes_written.get() + bytes as u64);
+        self.stats.written(bytes);
+        #[cfg(siginfo)]
+        self.shared.status.written(bytes);
+        self.size_written(bytes);
+    }
+
+    fn open_failed(&mut self, e: &Error) {
+        self.last_error = Some(e.to_string());
+        self.stats.open_failed();
+        self.shared.events.emit(ReopenEvent::OpenFailed {
+            error_kind: e.kind(),
+        });
+    }
+
+    /// Drops the current FD (if any), running the close hook on it first.
+    fn close(&mut self) {
+        self.slow = true;
+        self.save_position();
+        if let Some(mut fd) = self.fd.take() {
+            if let Some(hook) = self.close_hook.as_ref() {
+                let _ = hook(&mut fd);
+            }
+            self.finish(fd);
+            trace::closed(self.label(), self.generation);
+            self.shared.events.emit(ReopenEvent::Closed);
+        }
+    }
+
+    /// Makes sure the next operation checks for requests after an error.
+    #[inline]
+    fn check_error<R>(&mut self, result: &Result<R, Error>) {
+        if let Err(e) = result {
+            self.until_check = 0;
+            if !self.reopen_on_os_errors.is_empty() {
+                self.check_os_error(e);
+            }
+        }
+    }
+
+    /// Schedules a reopen if the error is one of the configured OS errors.
+    #[cold]
+    fn check_os_error(&mut self, e: &Error) {
+        if let Some(code) = e.raw_os_error() {
+            if self.reopen_on_os_errors.contains(&code) {
+                self.request_inside();
+            }
+        }
+    }
+
+    fn reopen_limit_reached(&self) -> bool {
+        match self.max_reopens {
+            Some(max) => self.reopens >= max,
+            None => false,
+        }
+    }
+
+    /// Schedules a reopen if the result looks like the FD refuses to accept more data.
+    fn check_write_zero<R>(&mut self, result: &Result<R, Error>, written_nothing: bool) {
+        self.check_error(result);
+        let dead = match result {
+            Ok(_) => self.reopen_on_write_zero && written_nothing,
+            Err(e) => match e.kind() {
+                ErrorKind::WriteZero => self.reopen_on_write_zero,
+                ErrorKind::BrokenPipe => self.reopen_on_broken_pipe,
+                ErrorKind::ConnectionRefused
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::NotConnected => self.reopen_on_disconnect,
+                _ => false,
+            },
+        };
+        if dead {
+            self.request_inside();
+        }
+    }
+
+    /// Requests a reopen from the inside of this [`Reopen`].
+    fn request_inside(&self) {
+        trace::requested(self.label(), "internal");
+        self.solo.set(false);
+        self.shared.request();
+    }
+
+    #[allow(clippy::option_as_ref_deref)] // as_deref is too new for our rustc version policy
+    fn label(&self) -> Option<&str> {
+        self.label.as_ref().map(String::as_str)
+    }
+}
+
+const REOPEN_LIMIT_EXCEEDED: &str = "reopen limit exceeded";
+
+// Error::other is too new for our MSRV.
+#[allow(clippy::io_other_error)]
+fn reopen_limit_error() -> Error {
+    Error::new(ErrorKind::Other, REOPEN_LIMIT_EXCEEDED)
+}
+
+impl<FD> Drop for Reopen<FD> {
+    fn drop(&mut self) {
+        #[cfg(all(feature = "signals", not(loom)))]
+        for id in self.signals.drain(..) {
+            signals::unregister(id);
+        }
+        self.close();
     }
 }
 
 impl<FD: Debug> Debug for Reopen<FD> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.debug_struct("Reopen")
-            .field("signal", &self.signal)
+            .field("label", &self.label)
+            .field("pending", &self.shared.requested())
+            .field("open", &self.fd.is_some())
+            .field("generation", &self.generation)
+            .field("reopens", &self.reopens)
             .field("fd", &self.fd)
+            .field("reopen_on_write_zero", &self.reopen_on_write_zero)
+            .field("reopen_on_broken_pipe", &self.reopen_on_broken_pipe)
+            .field("reopen_on_disconnect", &self.reopen_on_disconnect)
+            .field("reopen_on_os_errors", &self.reopen_on_os_errors)
+            .field("check_timing", &self.check_timing)
+            .field("double_buffered", &self.background.is_some())
+            .field(
+                "background_open",
+                &self.background.as_ref().and_then(|b| b.wait),
+            )
+            .field("preserve_position", &self.position.is_some())
+            .field(
+                "flush_timeout",
+                &self.flush_timeout.as_ref().map(|f| f.timeout),
+            )
             .field("constructor", &"...")
             .finish()
     }
 }
 
 impl<FD: Read> Read for Reopen<FD> {
+    #[inline]
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-        let fd = self.lock()?;
-        fd.read(buf)
+        let result = self.current()?.read(buf);
+        self.check_error(&result);
+        let at_end = match result {
+            Ok(0) => !buf.is_empty(),
+            _ => false,
+        };
+        if self.switch_after_read(&result, at_end) {
+            return self.read(buf);
+        }
+        result
     }
 
+    #[inline]
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
-        let fd = self.lock()?;
-        fd.read_exact(buf)
+        if self.check_timing == CheckTiming::After {
+            // Needs to go through our read to continue in the new FD after the end of the old.
+            return read_exact_by_parts(self, buf);
+        }
+        let result = self.current()?.read_exact(buf);
+        self.check_error(&result);
+        result
     }
 
+    #[inline]
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize, Error> {
-        let fd = self.lock()?;
-        fd.read_to_end(buf)
+        let result = self.current()?.read_to_end(buf);
+        self.check_error(&result);
+        if self.switch_after_read(&result, true) {
+            let read = *result.as_ref().unwrap_or(&0);
+            return self.read_to_end(buf).map(|more| read + more);
+        }
+        result
     }
 
+    #[inline]
     fn read_to_string(&mut self, buf: &mut String) -> Result<usize, Error> {
-        let fd = self.lock()?;
-        fd.read_to_string(buf)
+        let result = self.current()?.read_to_string(buf);
+        self.check_error(&result);
+        if self.switch_after_read(&result, true) {
+            let read = *result.as_ref().unwrap_or(&0);
+            return self.read_to_string(buf).map(|more| read + more);
+        }
+        result
+    }
+
+    #[cfg(read_buf)]
+    #[inline]
+    fn read_buf(&mut self, mut buf: BorrowedCursor<'_>) -> Result<(), Error> {
+        let written = buf.written();
+        let result = self.current()?.read_buf(buf.reborrow());
+        self.check_error(&result);
+        let at_end = buf.written() == written && buf.capacity() > 0;
+        if self.switch_after_read(&result, at_end) {
+            return self.read_buf(buf);
+        }
+        result
+    }
+
+    #[cfg(read_buf)]
+    #[inline]
+    fn read_buf_exact(&mut self, mut buf: BorrowedCursor<'_>) -> Result<(), Error> {
+        if self.check_timing == CheckTiming::After {
+            while buf.capacity() > 0 {
+                let written = buf.written();
+                match self.read_buf(buf.reborrow()) {
+                    Ok(()) if buf.written() == written => return Err(unexpected_eof()),
+                    Ok(()) => (),
+                    Err(ref e) if e.kind() == ErrorKind::Interrupted => (),
+                    Err(e) => return Err(e),
+                }
+            }
+            return Ok(());
+        }
+        let result = self.current()?.read_buf_exact(buf);
+        self.check_error(&result);
+        result
     }
 
     #[cfg(vectored)]
+    #[inline]
     fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, Error> {
-        let fd = self.check()?;
-        fd.read_vectored(bufs)
+        let result = self.current()?.read_vectored(bufs);
+        self.check_error(&result);
+        let at_end = match result {
+            Ok(0) => bufs.iter().any(|buf| !buf.is_empty()),
+            _ => false,
+        };
+        if self.switch_after_read(&result, at_end) {
+            return self.read_vectored(bufs);
+        }
+        result
     }
 }
 
+/// The same as the default [`Read::read_exact`], going through [`Read::read`].
+fn read_exact_by_parts<R: Read>(reader: &mut R, mut buf: &mut [u8]) -> Result<(), Error> {
+    while !buf.is_empty() {
+        match reader.read(buf) {
+            Ok(0) => return Err(unexpected_eof()),
+            Ok(n) => {
+                let rest = buf;
+                buf = &mut rest[n..];
+            }
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn unexpected_eof() -> Error {
+    Error::new(ErrorKind::UnexpectedEof, "failed to fill whole buffer")
+}
+
 impl<FD: Write> Write for Reopen<FD> {
+    #[inline]
     fn flush(&mut self) -> Result<(), Error> {
-        let fd = self.lock()?;
-        fd.flush()
+        let result = self.current()?.flush();
+        self.check_error(&result);
+        self.check_after();
+        result
     }
 
+    #[inline]
     fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
-        let fd = self.lock()?;
-        fd.write(buf)
+        let fd = self.current()?;
+        let result = fd.write(buf);
+        let written_nothing = match result {
+            Ok(0) => !buf.is_empty(),
+            Ok(written) => {
+                self.written(written);
+                false
+            }
+            _ => false,
+        };
+        self.check_write_zero(&result, written_nothing);
+        self.check_after();
+        result
     }
 
+    #[inline]
     fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
-        let fd = self.lock()?;
-        fd.write_all(buf)
+        let fd = self.current()?;
+        let result = fd.write_all(buf);
+        if result.is_ok() {
+            self.written(buf.len());
+        }
+        self.check_write_zero(&result, false);
+        self.check_after();
+        result
     }
 
+    #[inline]
     fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> Result<(), Error> {
-        let fd = self.lock()?;
-        fd.write_fmt(fmt)
+        if let Some(mut buffer) = self.fmt_buffer.take() {
+            buffer.clear();
+            let result = buffer.write_fmt(fmt).and_then(|()| self.write_all(&buffer));
+            self.fmt_buffer = Some(buffer);
+            return result;
+        }
+        // The size of the formatted output isn't known up front, count it on the way through.
+        let mut counting = stats::Counting::new(self.current()?);
+        let result = counting.write_fmt(fmt);
+        let written = counting.written;
+        self.written(written);
+        self.check_write_zero(&result, false);
+        self.check_after();
+        result
     }
 
     #[cfg(vectored)]
+    #[inline]
     fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize, Error> {
-        let fd = self.check()?;
-        fd.write_vectored(bufs)
+        let fd = self.current()?;
+        let result = fd.write_vectored(bufs);
+        let written_nothing = match result {
+            Ok(0) => bufs.iter().any(|buf| !buf.is_empty()),
+            Ok(written) => {
+                self.written(written);
+                false
+            }
+            _ => false,
+        };
+        self.check_write_zero(&result, written_nothing);
+        self.check_after();
+        result
     }
 }
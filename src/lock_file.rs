@@ -0,0 +1,84 @@
+//! Coordinating the reopens of several processes by a lock file.
+
+use std::fs::OpenOptions;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::PoisonError;
+
+use super::{Constructor, Reopen};
+
+impl<FD: 'static> Reopen<FD> {
+    /// Holds an exclusive lock on the given file while opening a new FD.
+    ///
+    /// When several processes write into the same log and the constructor rotates it (eg.
+    /// renames the full file away before opening a fresh one), two processes reopening at the
+    /// same time could both rename, one of them moving the other's fresh file over the rotated
+    /// one. With this, the constructor runs only while holding the lock, so the processes take
+    /// turns and each one sees what the one before it did.
+    ///
+    /// Taking turns is not enough on its own, the second one would still rotate the fresh file
+    /// created by the first one. Therefore, the constructor needs to decide if the file still
+    /// needs rotating, by looking at the file that is there (eg. by its size, its age or by
+    /// comparing its device and inode numbers with the file it opened the last time). The
+    /// constructor is always called, whatever it does (eg. custom open options or date-based
+    /// paths), it does so under the lock.
+    ///
+    /// The lock is taken only around the constructor, not during the writes. The lock file is
+    /// created if it doesn't exist and is left in place afterwards. Use a separate file for it,
+    /// not the log itself (that one gets renamed). Failing to open or lock it fails the open.
+    ///
+    /// Note that [`new`][Reopen::new] opens the first FD right away, before this can be set.
+    /// Create the [`Reopen`] lazily (eg. [`lazy`][Reopen::lazy]) to lock that one too.
+    ///
+    /// # Platform support
+    ///
+    /// This uses [`File::lock`][std::fs::File::lock] and is available only with compilers that
+    /// have it (Rust 1.89 and newer).
+    ///
+    /// * On unix, this is an advisory `flock`. It coordinates only processes that use the same
+    ///   lock file this way, others are free to touch the log. It might not work across
+    ///   machines on network file systems.
+    /// * On windows, this is `LockFileEx`, locking the lock file (only) for everyone.
+    /// * On platforms without file locking (eg. WASI), every open fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::fs::{self, OpenOptions};
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut log = Reopen::lazy(Box::new(|| {
+    ///     // Someone else might have rotated it already, rotate only a full one.
+    ///     if fs::metadata("/log/file").map(|m| m.len() > 1024 * 1024).unwrap_or(false) {
+    ///         fs::rename("/log/file", "/log/file.old")?;
+    ///     }
+    ///     OpenOptions::new().create(true).append(true).open("/log/file")
+    /// }))
+    /// .with_lock_file("/log/file.lock");
+    /// writeln!(log, "Hello")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn with_lock_file<P: Into<PathBuf>>(self, path: P) -> Self {
+        let path = path.into();
+        {
+            let mut constructor = self
+                .constructor
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let placeholder: Constructor<FD> = Box::new(|| unreachable!());
+            let inner = mem::replace(&mut *constructor, placeholder);
+            *constructor = Box::new(move || {
+                let lock = OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .truncate(false)
+                    .open(&path)?;
+                lock.lock()?;
+                // Closing the lock file releases the lock.
+                inner()
+            });
+        }
+        self
+    }
+}
@@ -0,0 +1,188 @@
+//! A minimal logger writing through a [`Reopen`].
+//!
+//! For small programs that want to log into a reopenable file without pulling in a whole logging
+//! framework. Each record is written as a single line:
+//!
+//! ```text
+//! 1700000000.123 INFO my_app::module: The message
+//! ```
+//!
+//! The timestamp is the number of seconds since the unix epoch, with milliseconds.
+//!
+//! # Features
+//!
+//! This is available only with the `logger` feature enabled.
+
+#[cfg(all(feature = "signals", not(loom)))]
+use std::fs::File;
+use std::io::Write;
+#[cfg(all(feature = "signals", not(loom)))]
+use std::io::{Error, ErrorKind};
+#[cfg(all(feature = "signals", not(loom)))]
+use std::os::raw::c_int;
+#[cfg(all(feature = "signals", not(loom)))]
+use std::path::Path;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+use super::{trace, Clock, Handle, Reopen, SyncReopen};
+
+/// A logger writing through a [`Reopen`].
+///
+/// The records up to the given level are written, in the format described in the
+/// [module][self] documentation, the rest is discarded. Usually installed by [`init`] (or
+/// [`install_rotating_logger`]), but it can be installed in any other way (eg. boxed by
+/// `log::set_boxed_logger`) or combined with other loggers.
+///
+/// Take the [`handle`][RotatingLogger::handle] before installing it, to request the reopens.
+#[derive(Debug)]
+pub struct RotatingLogger<FD> {
+    reopen: SyncReopen<FD>,
+    level: LevelFilter,
+    clock: Arc<dyn Clock>,
+}
+
+impl<FD> RotatingLogger<FD> {
+    /// Creates the logger.
+    ///
+    /// The timestamps come from the [clock][Reopen::with_clock] of the [`Reopen`].
+    pub fn new(reopen: Reopen<FD>, level: LevelFilter) -> Self {
+        Self {
+            clock: Arc::clone(&reopen.clock),
+            reopen: SyncReopen::new(reopen),
+            level,
+        }
+    }
+
+    /// Returns a handle to request a reopen.
+    pub fn handle(&self) -> Handle {
+        self.reopen.handle()
+    }
+
+    /// The level of the records that are written.
+    pub fn level(&self) -> LevelFilter {
+        self.level
+    }
+}
+
+impl<FD: Write + Send> Log for RotatingLogger<FD> {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let since_epoch = self
+            .clock
+            .system_now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        // It's a logger, there's nowhere to report the errors to. Our own records about the
+        // Reopen are muted, they would come back here and deadlock on the lock.
+        let _ = trace::muted(|| {
+            writeln!(
+                &self.reopen,
+                "{}.{:03} {} {}: {}",
+                since_epoch.as_secs(),
+                since_epoch.subsec_millis(),
+                record.level(),
+                record.target(),
+                record.args()
+            )
+        });
+    }
+
+    fn flush(&self) {
+        let _ = trace::muted(|| (&self.reopen).flush());
+    }
+}
+
+/// Installs a logger writing through the [`Reopen`].
+///
+/// The records up to the given level are written by a [`RotatingLogger`], the rest is discarded.
+/// The returned handle can be used to request the reopens (eg. by registering a signal).
+///
+/// # Errors
+///
+/// If another logger is already installed.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use log::{info, LevelFilter};
+/// # use reopen::Reopen;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let handle = reopen::logger::init(Reopen::append_to("/log/file")?, LevelFilter::Info)?;
+/// info!("Started");
+/// // Later on, eg. when asked by logrotate
+/// handle.reopen();
+/// # Ok(()) }
+/// ```
+pub fn init<FD>(reopen: Reopen<FD>, level: LevelFilter) -> Result<Handle, SetLoggerError>
+where
+    FD: Write + Send + 'static,
+{
+    let logger = RotatingLogger::new(reopen, level);
+    let handle = logger.handle();
+    // The logger stays installed for the rest of the program, so leaking it is fine.
+    log::set_logger(Box::leak(Box::new(logger)))?;
+    log::set_max_level(level);
+    Ok(handle)
+}
+
+/// Installs a logger appending to the file and reopening it on the signal.
+///
+/// This is the whole logrotate integration in one call: the file at the path is opened for
+/// appending (see [`Reopen::append_to`]), the records up to the given level are written into it
+/// by a [`RotatingLogger`] and the file is reopened whenever the signal (usually `SIGHUP`)
+/// comes. The returned handle can request further reopens.
+///
+/// # Errors
+///
+/// If the file can't be opened, the signal can't be registered or another logger is already
+/// installed (with the [`AlreadyExists`][ErrorKind::AlreadyExists] kind).
+///
+/// # Features
+///
+/// This is available only with both the `logger` and `signals` features enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use log::{info, LevelFilter};
+/// # use reopen::signals::consts::SIGHUP;
+/// # fn main() -> Result<(), std::io::Error> {
+/// reopen::install_rotating_logger("/log/file", LevelFilter::Info, SIGHUP)?;
+/// info!("Started");
+/// # Ok(()) }
+/// ```
+#[cfg(all(feature = "signals", not(loom)))]
+pub fn install_rotating_logger<P, S>(
+    path: P,
+    level: LevelFilter,
+    signal: S,
+) -> Result<Handle, Error>
+where
+    P: AsRef<Path>,
+    S: Into<c_int>,
+{
+    let reopen: Reopen<File> = Reopen::append_to(path)?;
+    let logger = RotatingLogger::new(reopen, level);
+    let handle = logger.handle();
+    let id = handle.register_signal(signal)?;
+    // The logger stays installed for the rest of the program, so leaking it is fine.
+    if let Err(e) = log::set_logger(Box::leak(Box::new(logger))) {
+        // Elsewhere, registering the signal fails before getting here.
+        #[cfg(any(unix, windows))]
+        signal_hook::low_level::unregister(id);
+        #[cfg(not(any(unix, windows)))]
+        let _ = id;
+        return Err(Error::new(ErrorKind::AlreadyExists, e.to_string()));
+    }
+    log::set_max_level(level);
+    Ok(handle)
+}
@@ -0,0 +1,178 @@
+//! Writing into memory-mapped files.
+//!
+//! # Features
+//!
+//! This is available only with the `mmap` feature enabled.
+
+use std::cmp;
+use std::fs::OpenOptions;
+use std::io::{Error, ErrorKind, Write};
+use std::path::Path;
+
+use memmap2::{MmapMut, MmapOptions};
+
+use super::{Handle, Reopen};
+
+/// A writer into a memory-mapped file of a fixed size.
+///
+/// The file is created with the given size (filled with zeroes) and mapped into memory. The
+/// writes copy the data into the mapping, at an internal cursor, which is as cheap as writing
+/// into a memory buffer. The kernel takes care of getting the data into the file eventually,
+/// even if the process crashes.
+///
+/// This is designed to be the FD inside a [`Reopen`], see [`mmap_file`]. Once the mapping is
+/// full, it asks for a reopen (if it was given a [handle][MmapWriter::reopen_when_full]), so the
+/// next write goes into a fresh file.
+///
+/// # Records
+///
+/// The records are not split between files. A write that doesn't fit into the rest of the
+/// mapping writes nothing (returns `Ok(0)`) and asks for a reopen. Only if the mapping is empty,
+/// the part that fits is written (a record larger than the whole mapping can't be written
+/// whole anyway).
+///
+/// Therefore, [`write_all`][Write::write_all] fails with
+/// [`WriteZero`][std::io::ErrorKind::WriteZero] at the end of the file. Use
+/// [`write_record`][Reopen::write_record], which retries the record in the new file.
+///
+/// # Flushing
+///
+/// The [`flush`][Write::flush] only starts writing the data to the disk (`msync` with
+/// `MS_ASYNC`), it doesn't wait for it. The [`sync`][MmapWriter::sync] waits. The [`Reopen`]
+/// created by [`mmap_file`] syncs the old file before switching to a new one.
+///
+/// The part of the file that wasn't written yet stays filled with zeroes.
+#[derive(Debug)]
+pub struct MmapWriter {
+    map: MmapMut,
+    pos: usize,
+    full: Option<Handle>,
+}
+
+impl MmapWriter {
+    /// Creates the file with the given size and maps it.
+    ///
+    /// If the file exists, it is truncated first.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be created, resized or mapped, or if the size is zero.
+    #[allow(unsafe_code)]
+    pub fn create<P: AsRef<Path>>(path: P, size: usize) -> Result<Self, Error> {
+        if size == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Size of the mapping must not be zero",
+            ));
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(size as u64)?;
+        // SAFETY: we created the file, so it has the right size. Someone else could still truncate
+        // it, but that's the usual risk of mapping files.
+        let map = unsafe { MmapOptions::new().len(size).map_mut(&file)? };
+        Ok(MmapWriter {
+            map,
+            pos: 0,
+            full: None,
+        })
+    }
+
+    /// Asks for a reopen through the handle once the mapping is full.
+    pub fn reopen_when_full(mut self, handle: Handle) -> Self {
+        self.full = Some(handle);
+        self
+    }
+
+    /// How many bytes were written into the mapping.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The size of the mapping (and the file).
+    pub fn capacity(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Writes the data to the disk and waits for it (`msync` with `MS_SYNC`).
+    pub fn sync(&self) -> Result<(), Error> {
+        self.map.flush()
+    }
+
+    fn filled(&self) {
+        if let Some(handle) = self.full.as_ref() {
+            handle.reopen();
+        }
+    }
+}
+
+impl Write for MmapWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let free = self.map.len() - self.pos;
+        if buf.len() > free && self.pos > 0 {
+            // Keep the record whole, it goes into the next file.
+            self.filled();
+            return Ok(0);
+        }
+        let len = cmp::min(free, buf.len());
+        self.map[self.pos..self.pos + len].copy_from_slice(&buf[..len]);
+        self.pos += len;
+        if self.pos == self.map.len() {
+            self.filled();
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.map.flush_async()
+    }
+}
+
+/// Creates a [`Reopen`] writing into memory-mapped files of the given size.
+///
+/// The `path` is called for each new file to give its name (eg. with a counter or a timestamp).
+/// When the current file is full, a reopen is requested and the next write goes into a new
+/// file. The reopens can also be requested through the [`handle`][Reopen::handle] as usual.
+///
+/// Before switching to a new file (and when the [`Reopen`] is dropped), the old one is synced to
+/// the disk and unmapped. See [`MmapWriter`] for the details about the writing.
+///
+/// # Errors
+///
+/// If the first file can't be created.
+///
+/// # Features
+///
+/// This is available only with the `mmap` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::Error;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// # fn main() -> Result<(), Error> {
+/// let counter = AtomicUsize::new(0);
+/// let mut log = reopen::mmap::mmap_file(
+///     move || format!("/log/file.{}", counter.fetch_add(1, Ordering::Relaxed)),
+///     1024 * 1024,
+/// )?;
+/// log.write_record(b"Hello\n")?;
+/// # Ok(()) }
+/// ```
+pub fn mmap_file<F, P>(path: F, size: usize) -> Result<Reopen<MmapWriter>, Error>
+where
+    F: Fn() -> P + Send + 'static,
+    P: AsRef<Path>,
+{
+    let handle = Handle::stub();
+    let mut reopen = Reopen::with_handle(
+        handle.clone(),
+        Box::new(move || Ok(MmapWriter::create(path(), size)?.reopen_when_full(handle.clone()))),
+    )?;
+    reopen.close_hook = Some(Box::new(|writer: &mut MmapWriter| writer.sync()));
+    Ok(reopen)
+}
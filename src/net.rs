@@ -0,0 +1,694 @@
+//! Constructors of network FDs, connecting again on each reopen.
+//!
+//! # Features
+//!
+//! The TCP connections, UDP sockets and unix stream sockets are available only with the `net`
+//! feature enabled (the latter on unix only). The unix datagram sockets are available only with the
+//! `syslog` feature enabled, on unix. The TLS connections are available only with the `rustls`
+//! feature enabled.
+
+#[cfg(all(feature = "net", unix))]
+use std::cmp;
+use std::io::Error;
+#[cfg(any(feature = "net", feature = "rustls"))]
+use std::io::ErrorKind;
+#[cfg(any(feature = "net", all(feature = "syslog", unix)))]
+use std::io::Write;
+#[cfg(feature = "net")]
+use std::net::UdpSocket;
+#[cfg(any(feature = "net", feature = "rustls"))]
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(all(feature = "net", unix))]
+use std::os::unix::io::OwnedFd;
+#[cfg(all(feature = "syslog", unix))]
+use std::os::unix::net::UnixDatagram;
+#[cfg(all(feature = "net", unix))]
+use std::os::unix::net::UnixStream;
+#[cfg(all(feature = "syslog", unix))]
+use std::path::Path;
+#[cfg(all(any(feature = "net", feature = "syslog"), unix))]
+use std::path::PathBuf;
+#[cfg(feature = "rustls")]
+use std::sync::Arc;
+#[cfg(any(feature = "net", feature = "rustls"))]
+use std::time::Duration;
+
+#[cfg(feature = "rustls")]
+use rustls::pki_types::ServerName;
+#[cfg(feature = "rustls")]
+use rustls::{ClientConfig, ClientConnection, StreamOwned};
+#[cfg(all(feature = "net", unix))]
+use socket2::{Domain, SockAddr, Socket, Type};
+#[cfg(all(feature = "net", any(unix, windows)))]
+use socket2::{SockRef, TcpKeepalive};
+
+#[cfg(any(feature = "net", all(feature = "syslog", unix)))]
+use super::Reopen;
+
+/// Writes into a connected unix datagram socket.
+///
+/// Each [`write`][Write::write] sends one datagram. Therefore, the [`Reopen`]
+/// using it should have [`buffer_fmt`][crate::Reopen::buffer_fmt] turned on, so a formatted
+/// message (eg. by `writeln!`) goes out as a single datagram, not a fragment at a time.
+///
+/// Created by the constructor from [`unix_datagram`].
+#[cfg(all(feature = "syslog", unix))]
+#[derive(Debug)]
+pub struct UnixDatagramWriter {
+    socket: UnixDatagram,
+}
+
+#[cfg(all(feature = "syslog", unix))]
+impl UnixDatagramWriter {
+    /// Wraps an already connected socket.
+    pub fn new(socket: UnixDatagram) -> Self {
+        Self { socket }
+    }
+
+    /// The socket.
+    pub fn get_ref(&self) -> &UnixDatagram {
+        &self.socket
+    }
+}
+
+#[cfg(all(feature = "syslog", unix))]
+impl Write for UnixDatagramWriter {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.socket.send(buf)
+    }
+}
+
+/// Creates a constructor connecting a unix datagram socket to the given path.
+///
+/// Each call of the constructor creates a new socket and connects it, so a reopen picks up the
+/// new socket of a restarted server (eg. a syslog daemon listening on `/dev/log`). Until the
+/// reopen, the old one fails each send with `ECONNREFUSED`. Turn on
+/// [`reopen_on_disconnect`][crate::Reopen::reopen_on_disconnect] to reopen on that error. If
+/// the server is not up yet, the reopen fails and is tried again on the next write.
+///
+/// Note that this only sends the bytes as they are. Formatting them as syslog messages (eg.
+/// `<14>my-app: The message`) is up to the caller.
+///
+/// # Features
+///
+/// This is available only with the `syslog` feature enabled, on unix.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # use reopen::Reopen;
+/// # use reopen::net::unix_datagram;
+/// # fn main() -> Result<(), Error> {
+/// let mut syslog = Reopen::from_fn(unix_datagram("/dev/log".into()))?
+///     .reopen_on_disconnect(true)
+///     .buffer_fmt(true);
+/// writeln!(syslog, "<14>my-app: Hello")?;
+/// # Ok(()) }
+/// ```
+#[cfg(all(feature = "syslog", unix))]
+pub fn unix_datagram(path: PathBuf) -> impl Fn() -> Result<UnixDatagramWriter, Error> {
+    move || connect_datagram(&path).map(UnixDatagramWriter::new)
+}
+
+/// Creates a new unix datagram socket connected to the path.
+#[cfg(all(feature = "syslog", unix))]
+fn connect_datagram(path: &Path) -> Result<UnixDatagram, Error> {
+    let socket = UnixDatagram::unbound()?;
+    socket.connect(path)?;
+    Ok(socket)
+}
+
+#[cfg(all(feature = "syslog", unix))]
+impl Reopen<UnixDatagram> {
+    /// Creates a [`Reopen`] of a unix datagram socket connected to the given path.
+    ///
+    /// This is an alternative to the [`UnixDatagramWriter`], for sending through the socket
+    /// directly by [`send`][Reopen::send]. The socket is connected again on each reopen and
+    /// [`reopen_on_disconnect`][Reopen::reopen_on_disconnect] is turned on, so a restart of the
+    /// server (eg. the syslog daemon on `/dev/log`) is handled by the next send.
+    ///
+    /// # Errors
+    ///
+    /// If the socket can't be created or connected (eg. the server doesn't run).
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `syslog` feature enabled, on unix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut syslog = Reopen::unix_datagram("/dev/log")?;
+    /// syslog.send(b"<14>my-app: Hello")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn unix_datagram<P: Into<PathBuf>>(path: P) -> Result<Self, Error> {
+        let path = path.into();
+        let reopen = Self::from_fn(move || connect_datagram(&path))?;
+        Ok(reopen.reopen_on_disconnect(true))
+    }
+
+    /// Sends a datagram on the connected socket.
+    ///
+    /// A datagram socket has no stream to write into, so this is the way to use it. It does the
+    /// pending reopen first and requests another one if the send fails in a way configured by
+    /// [`reopen_on_disconnect`][Reopen::reopen_on_disconnect] (or
+    /// [`reopen_on_broken_pipe`][Reopen::reopen_on_broken_pipe]).
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `syslog` feature enabled, on unix.
+    pub fn send(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let result = self.current()?.send(buf);
+        self.sent(result)
+    }
+
+    /// Sends a datagram to the given address.
+    ///
+    /// This is for sockets that are not connected, otherwise the same as
+    /// [`send`][Reopen::send].
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `syslog` feature enabled, on unix.
+    pub fn send_to<P: AsRef<Path>>(&mut self, buf: &[u8], path: P) -> Result<usize, Error> {
+        let result = self.current()?.send_to(buf, path);
+        self.sent(result)
+    }
+}
+
+#[cfg(any(feature = "net", all(feature = "syslog", unix)))]
+impl<FD> Reopen<FD> {
+    /// Accounts for a datagram sent through the FD.
+    fn sent(&mut self, result: Result<usize, Error>) -> Result<usize, Error> {
+        if let Ok(sent) = result {
+            self.written(sent);
+        }
+        self.check_write_zero(&result, false);
+        result
+    }
+}
+
+/// Connects to the first of the addresses that accepts the connection.
+#[cfg(any(feature = "net", feature = "rustls"))]
+fn connect<I>(addrs: I, connect_timeout: Duration) -> Result<TcpStream, Error>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let mut last_error = None;
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, connect_timeout) {
+            Ok(stream) => {
+                stream.set_nodelay(true)?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error
+        .unwrap_or_else(|| Error::new(ErrorKind::InvalidInput, "The address resolved to nothing")))
+}
+
+/// Creates a constructor connecting to the given address over TCP.
+///
+/// The address (eg. `logs.internal:514`) is resolved on each call of the constructor, so a
+/// reopen follows the changes of the DNS records instead of connecting to the old IP address
+/// again. All the resolved addresses are tried in turn, each with the `connect_timeout`, until
+/// one of them connects. The stream has `TCP_NODELAY` set, as the writes are usually whole log
+/// records that should go out right away.
+///
+/// Combine it with [`reopen_on_disconnect`][crate::Reopen::reopen_on_disconnect] and
+/// [`reopen_on_broken_pipe`][crate::Reopen::reopen_on_broken_pipe], so a connection closed by
+/// the other side is replaced by a new one. There's no backoff between the attempts to
+/// connect: a failed reopen stays pending and the next write tries again, which (with an
+/// unreachable server) takes up to the `connect_timeout` for each address. Write less often
+/// or wrap the constructor to delay the attempts if that's too much.
+///
+/// With `keepalive` set, the TCP keepalive probes start after the connection is idle for that
+/// long. A peer that disappeared without closing the connection (eg. it crashed or a firewall
+/// dropped the state) is then eventually noticed even when there's little to write. To set any
+/// other socket option, wrap the constructor and configure the stream.
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # use std::time::Duration;
+/// # use reopen::Reopen;
+/// # use reopen::net::tcp;
+/// # fn main() -> Result<(), Error> {
+/// let connect = tcp(
+///     "logs.internal:514".to_owned(),
+///     Duration::from_secs(5),
+///     Some(Duration::from_secs(60)),
+/// );
+/// let mut log = Reopen::from_fn(connect)?
+///     .reopen_on_disconnect(true)
+///     .reopen_on_broken_pipe(true)
+///     .buffer_fmt(true);
+/// writeln!(log, "Hello")?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "net")]
+pub fn tcp(
+    addr: String,
+    connect_timeout: Duration,
+    keepalive: Option<Duration>,
+) -> impl Fn() -> Result<TcpStream, Error> {
+    move || {
+        let stream = connect(addr.to_socket_addrs()?, connect_timeout)?;
+        set_keepalive(&stream, keepalive)?;
+        Ok(stream)
+    }
+}
+
+/// Creates a constructor connecting over TCP to the address returned by the closure.
+///
+/// This is like [`tcp`] (including the `keepalive`), but without the DNS. The closure is called on each call of the
+/// constructor, so it can return a different address each time (eg. read from a service
+/// discovery or a configuration).
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled.
+#[cfg(feature = "net")]
+pub fn tcp_to<F>(
+    addr: F,
+    connect_timeout: Duration,
+    keepalive: Option<Duration>,
+) -> impl Fn() -> Result<TcpStream, Error>
+where
+    F: Fn() -> SocketAddr,
+{
+    move || {
+        let stream = connect(Some(addr()), connect_timeout)?;
+        set_keepalive(&stream, keepalive)?;
+        Ok(stream)
+    }
+}
+
+/// Turns on the TCP keepalive, if asked for.
+#[cfg(all(feature = "net", any(unix, windows)))]
+fn set_keepalive(stream: &TcpStream, keepalive: Option<Duration>) -> Result<(), Error> {
+    match keepalive {
+        Some(time) => SockRef::from(stream).set_tcp_keepalive(&TcpKeepalive::new().with_time(time)),
+        None => Ok(()),
+    }
+}
+
+/// Turns on the TCP keepalive, if asked for.
+///
+/// The socket options are not available on this platform, so asking for it always fails.
+#[cfg(all(feature = "net", not(any(unix, windows))))]
+// Error::other is too new for our MSRV.
+#[allow(clippy::io_other_error)]
+fn set_keepalive(_stream: &TcpStream, keepalive: Option<Duration>) -> Result<(), Error> {
+    match keepalive {
+        Some(_) => Err(Error::new(
+            ErrorKind::Other,
+            "TCP keepalive is not supported on this platform",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// What [`UdpWriter`] does with a write larger than the maximum datagram size.
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled.
+#[cfg(feature = "net")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OversizedDatagram {
+    /// Send only the first part that fits into a datagram.
+    ///
+    /// The [`write`][Write::write] returns the size of that part, as usual for a partial write.
+    /// Therefore, [`write_all`][Write::write_all] sends the rest in further datagrams.
+    Split,
+
+    /// Fail with [`InvalidInput`][ErrorKind::InvalidInput], sending nothing.
+    Fail,
+}
+
+/// Writes into a connected UDP socket.
+///
+/// Each [`write`][Write::write] sends one datagram. Therefore, the [`Reopen`] using it should
+/// have [`buffer_fmt`][crate::Reopen::buffer_fmt] turned on, so a formatted message (eg. by
+/// `writeln!`) goes out as a single datagram, not a fragment at a time.
+///
+/// The writes larger than the [maximum size][UdpWriter::max_datagram_size] (by default 65507
+/// bytes, the most that fits into an IPv4 datagram) are either split or refused.
+///
+/// Created by the constructors from [`udp`] and [`udp_to`].
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled.
+#[cfg(feature = "net")]
+#[derive(Debug)]
+pub struct UdpWriter {
+    socket: UdpSocket,
+    max_size: usize,
+    oversized: OversizedDatagram,
+}
+
+#[cfg(feature = "net")]
+impl UdpWriter {
+    /// Wraps an already connected socket.
+    pub fn new(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            max_size: 65507,
+            oversized: OversizedDatagram::Fail,
+        }
+    }
+
+    /// Sets the maximum size of a datagram and what to do with larger writes.
+    pub fn max_datagram_size(mut self, size: usize, oversized: OversizedDatagram) -> Self {
+        self.max_size = size;
+        self.oversized = oversized;
+        self
+    }
+
+    /// The socket.
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.socket
+    }
+}
+
+#[cfg(feature = "net")]
+impl Write for UdpWriter {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if buf.len() <= self.max_size {
+            return self.socket.send(buf);
+        }
+        match self.oversized {
+            OversizedDatagram::Split => self.socket.send(&buf[..self.max_size]),
+            OversizedDatagram::Fail => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The data don't fit into a datagram",
+            )),
+        }
+    }
+}
+
+/// Creates a new UDP socket connected to the first of the addresses.
+#[cfg(feature = "net")]
+fn connect_udp<I>(addrs: I) -> Result<UdpWriter, Error>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let addr = addrs
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "The address resolved to nothing"))?;
+    let local = if addr.is_ipv4() {
+        SocketAddr::from(([0, 0, 0, 0], 0))
+    } else {
+        SocketAddr::from(([0u16; 8], 0))
+    };
+    let socket = UdpSocket::bind(local)?;
+    socket.connect(addr)?;
+    Ok(UdpWriter::new(socket))
+}
+
+/// Creates a constructor of UDP sockets connected to the given address.
+///
+/// The address (eg. `logs.internal:514`) is resolved on each call of the constructor and the
+/// socket is connected to the first of the resolved addresses. As sending over UDP doesn't fail
+/// when nobody listens, the reopen is the only chance to follow the changes of the DNS records.
+/// Request it from time to time (eg. from a thread calling
+/// [`Handle::reopen`][crate::Handle::reopen] in a loop) to re-resolve periodically.
+///
+/// Some systems report an earlier datagram that didn't reach anyone as a
+/// [`ConnectionRefused`][ErrorKind::ConnectionRefused] error of a later send. Turn on
+/// [`reopen_on_disconnect`][crate::Reopen::reopen_on_disconnect] to re-resolve then too.
+///
+/// To change the maximum size of a datagram, wrap the constructor and set
+/// [`max_datagram_size`][UdpWriter::max_datagram_size] on the writer.
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # use reopen::Reopen;
+/// # use reopen::net::{udp, OversizedDatagram};
+/// # fn main() -> Result<(), Error> {
+/// let connect = udp("logs.internal:514".to_owned());
+/// let mut log = Reopen::from_fn(move || {
+///     connect().map(|writer| writer.max_datagram_size(1024, OversizedDatagram::Split))
+/// })?
+/// .reopen_on_disconnect(true)
+/// .buffer_fmt(true);
+/// writeln!(log, "<14>my-app: Hello")?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "net")]
+pub fn udp(addr: String) -> impl Fn() -> Result<UdpWriter, Error> {
+    move || connect_udp(addr.to_socket_addrs()?)
+}
+
+/// Creates a constructor of UDP sockets connected to the address returned by the closure.
+///
+/// This is like [`udp`], but without the DNS. The closure is called on each call of the
+/// constructor, so it can return a different address each time.
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled.
+#[cfg(feature = "net")]
+pub fn udp_to<F>(addr: F) -> impl Fn() -> Result<UdpWriter, Error>
+where
+    F: Fn() -> SocketAddr,
+{
+    move || connect_udp(Some(addr()))
+}
+
+/// Creates a constructor binding a UDP socket to the address returned by the closure.
+///
+/// The closure is called on each call of the constructor, so a reopen binds a new socket to
+/// the current address (eg. of an interface that changed when a VPN reconnected) and the old
+/// socket is closed. The socket is not connected, send through it with
+/// [`send_to`][crate::Reopen::send_to] (or anything else through [`lock`][crate::Reopen::lock]).
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::Error;
+/// # use std::net::SocketAddr;
+/// # use reopen::Reopen;
+/// # use reopen::net::udp_bound;
+/// # fn main() -> Result<(), Error> {
+/// let mut socket = Reopen::from_fn(udp_bound(|| SocketAddr::from(([10, 8, 0, 2], 0))))?;
+/// socket.send_to(b"Hello", "10.8.0.1:5140")?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "net")]
+pub fn udp_bound<F>(addr: F) -> impl Fn() -> Result<UdpSocket, Error>
+where
+    F: Fn() -> SocketAddr,
+{
+    move || UdpSocket::bind(addr())
+}
+
+#[cfg(feature = "net")]
+impl Reopen<UdpSocket> {
+    /// Sends a datagram to the given address.
+    ///
+    /// A UDP socket has no stream to write into, so this is the way to use it (other operations
+    /// are available through [`lock`][Reopen::lock]). It does the pending reopen (eg. binding
+    /// the socket again) first.
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `net` feature enabled.
+    pub fn send_to<A: ToSocketAddrs>(&mut self, buf: &[u8], addr: A) -> Result<usize, Error> {
+        let result = self.current()?.send_to(buf, addr);
+        self.sent(result)
+    }
+}
+
+/// Creates a constructor connecting a unix stream socket to the given path.
+///
+/// Each call of the constructor connects anew, so a reopen reaches the new socket of a
+/// restarted server (eg. a local daemon, or syslog with a stream `/dev/log`). Turn on
+/// [`reopen_on_broken_pipe`][crate::Reopen::reopen_on_broken_pipe] and
+/// [`reopen_on_disconnect`][crate::Reopen::reopen_on_disconnect], so a write failing because
+/// the server went away (with `EPIPE`, `ECONNRESET`, `ENOTCONN`, ...) requests the reconnect.
+/// As with [`tcp`], there's no backoff: a failed reconnect stays pending and the next write
+/// tries again.
+///
+/// The connecting blocks if the server is alive but doesn't accept the connections (its
+/// backlog is full). See [`unix_stream_timeout`] to limit that.
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled, on unix.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # use reopen::Reopen;
+/// # use reopen::net::unix_stream;
+/// # fn main() -> Result<(), Error> {
+/// let mut log = Reopen::from_fn(unix_stream("/run/collector.sock".into()))?
+///     .reopen_on_broken_pipe(true)
+///     .reopen_on_disconnect(true)
+///     .buffer_fmt(true);
+/// writeln!(log, "Hello")?;
+/// # Ok(()) }
+/// ```
+#[cfg(all(feature = "net", unix))]
+pub fn unix_stream(path: PathBuf) -> impl Fn() -> Result<UnixStream, Error> {
+    move || UnixStream::connect(&path)
+}
+
+/// Creates a constructor connecting a unix stream socket, giving up after a timeout.
+///
+/// This is like [`unix_stream`], but if the connection is not established within the
+/// `connect_timeout`, the constructor fails with [`TimedOut`][ErrorKind::TimedOut].
+///
+/// The connecting is bounded by the send timeout of the socket, no helper thread is involved.
+/// Connecting to a unix socket waits only for a place in the backlog of the server, which Linux
+/// does at most for the send timeout. Other systems refuse the connection with a full backlog
+/// right away, so the connecting doesn't wait there at all. The timeout is cleared once
+/// connected.
+///
+/// # Features
+///
+/// This is available only with the `net` feature enabled, on unix.
+#[cfg(all(feature = "net", unix))]
+pub fn unix_stream_timeout(
+    path: PathBuf,
+    connect_timeout: Duration,
+) -> impl Fn() -> Result<UnixStream, Error> {
+    // A zero timeout would mean no timeout at all for the socket.
+    let connect_timeout = cmp::max(connect_timeout, Duration::from_micros(1));
+    move || {
+        let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+        socket.set_write_timeout(Some(connect_timeout))?;
+        match socket.connect(&SockAddr::unix(&path)?) {
+            Ok(()) => (),
+            // Still no place in the backlog after the timeout.
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => {
+                return Err(Error::new(
+                    ErrorKind::TimedOut,
+                    "Timed out connecting to the unix socket",
+                ))
+            }
+            Err(e) => return Err(e),
+        }
+        socket.set_write_timeout(None)?;
+        Ok(UnixStream::from(OwnedFd::from(socket)))
+    }
+}
+
+/// Creates a constructor of TLS connections over TCP.
+///
+/// Each call of the constructor connects to the address (resolved anew each time, as in
+/// [`tcp`]) and completes the TLS handshake with the given server name, so a reopen gets a
+/// fresh connection and session. The `connect_timeout` limits both the connecting (for each
+/// address) and the handshake. The resulting stream is both [`Read`][std::io::Read] and
+/// [`Write`].
+///
+/// If the handshake fails (eg. the certificate of the server is not trusted), the constructor
+/// fails with an error describing it, including the address. Combine it with
+/// [`reopen_on_disconnect`][crate::Reopen::reopen_on_disconnect] and
+/// [`reopen_on_broken_pipe`][crate::Reopen::reopen_on_broken_pipe] to reconnect when the server
+/// goes away.
+///
+/// To check the negotiated connection (eg. the ALPN protocol), wrap the constructor and return
+/// an error from the wrapper when it doesn't fit.
+///
+/// This crate doesn't choose the crypto provider of rustls, it is the one in the `config`. Turn
+/// on a provider feature of `rustls` (eg. `aws-lc-rs` or `ring`) in your own dependency to get
+/// the default one.
+///
+/// # Features
+///
+/// This is available only with the `rustls` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::convert::TryInto;
+/// # use std::io::{Error, ErrorKind, Write};
+/// # use std::sync::Arc;
+/// # use std::time::Duration;
+/// # use reopen::Reopen;
+/// # use reopen::net::tls;
+/// # use rustls::{ClientConfig, RootCertStore};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let roots = RootCertStore::empty();
+/// let mut config = ClientConfig::builder()
+///     .with_root_certificates(roots)
+///     .with_no_client_auth();
+/// config.alpn_protocols = vec![b"logs/1".to_vec()];
+/// let connect = tls(
+///     Arc::new(config),
+///     "logs.internal".try_into()?,
+///     "logs.internal:6514".to_owned(),
+///     Duration::from_secs(5),
+/// );
+/// let mut log = Reopen::from_fn(move || {
+///     let stream = connect()?;
+///     if stream.conn.alpn_protocol() != Some(&b"logs/1"[..]) {
+///         return Err(Error::new(ErrorKind::InvalidData, "Unsupported protocol"));
+///     }
+///     Ok(stream)
+/// })?
+/// .reopen_on_disconnect(true)
+/// .reopen_on_broken_pipe(true)
+/// .buffer_fmt(true);
+/// writeln!(log, "Hello")?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "rustls")]
+pub fn tls(
+    config: Arc<ClientConfig>,
+    server_name: ServerName<'static>,
+    addr: String,
+    connect_timeout: Duration,
+) -> impl Fn() -> Result<StreamOwned<ClientConnection, TcpStream>, Error> {
+    move || {
+        let mut sock = connect(addr.to_socket_addrs()?, connect_timeout)?;
+        let mut conn = ClientConnection::new(Arc::clone(&config), server_name.clone())
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        sock.set_read_timeout(Some(connect_timeout))?;
+        sock.set_write_timeout(Some(connect_timeout))?;
+        while conn.is_handshaking() {
+            conn.complete_io(&mut sock).map_err(|e| {
+                let msg = format!("TLS handshake with {} failed: {}", addr, e);
+                Error::new(e.kind(), msg)
+            })?;
+        }
+        sock.set_read_timeout(None)?;
+        sock.set_write_timeout(None)?;
+        Ok(StreamOwned::new(conn, sock))
+    }
+}
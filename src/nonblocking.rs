@@ -0,0 +1,66 @@
+use std::io::Error;
+use std::net::{TcpStream, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::{UnixDatagram, UnixStream};
+
+use super::Reopen;
+
+/// FDs that can be switched between the blocking and nonblocking mode.
+///
+/// Used by [`Reopen::set_nonblocking`]. Implemented for the sockets of the standard library. It
+/// can be implemented for other types too (eg. a wrapper around a socket).
+pub trait SetNonblocking {
+    /// Switches the mode of the FD.
+    fn set_nonblocking(&self, nonblocking: bool) -> Result<(), Error>;
+}
+
+macro_rules! impl_set_nonblocking {
+    ($($ty: ty,)*) => {
+        $(
+            impl SetNonblocking for $ty {
+                fn set_nonblocking(&self, nonblocking: bool) -> Result<(), Error> {
+                    <$ty>::set_nonblocking(self, nonblocking)
+                }
+            }
+        )*
+    };
+}
+
+impl_set_nonblocking!(TcpStream, UdpSocket,);
+
+#[cfg(unix)]
+impl_set_nonblocking!(UnixStream, UnixDatagram,);
+
+/// Applies the mode to a freshly opened FD.
+pub(crate) type ApplyNonblocking<FD> = fn(&FD, bool) -> Result<(), Error>;
+
+impl<FD: SetNonblocking> Reopen<FD> {
+    /// Switches the FD to the nonblocking (or back to blocking) mode.
+    ///
+    /// This applies to the current FD (if it is open) and then to each new FD after a reopen, so
+    /// the mode persists. If switching a new FD fails, it is dropped and the operation returns the
+    /// error (the next one tries with yet another FD).
+    ///
+    /// # Errors
+    ///
+    /// If switching the current FD fails. The mode is remembered for the new FDs even then.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use std::net::TcpStream;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut conn = Reopen::new(Box::new(|| TcpStream::connect("localhost:5140")))?;
+    /// conn.set_nonblocking(true)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), Error> {
+        self.nonblocking = Some((nonblocking, FD::set_nonblocking));
+        match self.fd.as_ref() {
+            Some(fd) => fd.set_nonblocking(nonblocking),
+            None => Ok(()),
+        }
+    }
+}
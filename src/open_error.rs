@@ -0,0 +1,53 @@
+//! The error of opening the FD, distinct from the errors of the operations on it.
+
+use std::error::Error as StdError;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{Error, ErrorKind};
+
+/// Getting an FD ready failed.
+///
+/// Returned by [`Reopen::open_current`][crate::Reopen::open_current]. It wraps the error of the
+/// constructor, or of anything else that failed while preparing a new FD (eg. the
+/// [header][crate::Reopen::with_header] couldn't be written), or the refusal to reopen past the
+/// [limit][crate::Reopen::with_max_reopens].
+///
+/// Being a separate type allows telling the failures to open the sink apart from the failures
+/// of the operations on an already open one. It converts into the wrapped [`Error`], so `?`
+/// works in functions returning [`std::io::Result`].
+#[derive(Debug)]
+pub struct OpenError(pub(crate) Error);
+
+impl OpenError {
+    /// The kind of the wrapped error.
+    pub fn kind(&self) -> ErrorKind {
+        self.0.kind()
+    }
+
+    /// The wrapped error.
+    pub fn get_ref(&self) -> &Error {
+        &self.0
+    }
+
+    /// Unwraps the error.
+    pub fn into_inner(self) -> Error {
+        self.0
+    }
+}
+
+impl Display for OpenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "Failed to open: {}", self.0)
+    }
+}
+
+impl StdError for OpenError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl From<OpenError> for Error {
+    fn from(e: OpenError) -> Self {
+        e.0
+    }
+}
@@ -0,0 +1,65 @@
+//! Measuring how long the opens take.
+
+use std::cmp;
+use std::time::Duration;
+
+use super::Reopen;
+
+/// The durations of the constructor calls.
+#[derive(Debug, Default)]
+pub(crate) struct OpenTimes {
+    last: Option<Duration>,
+    total: Duration,
+    max: Duration,
+}
+
+impl OpenTimes {
+    pub(crate) fn record(&mut self, duration: Duration) {
+        self.last = Some(duration);
+        self.total += duration;
+        self.max = cmp::max(self.max, duration);
+    }
+}
+
+impl<FD> Reopen<FD> {
+    /// How long the last attempt to open an FD took.
+    ///
+    /// This measures the calls of the constructor (successful or not) done on reopens and lazy
+    /// opens, by the [clock][Reopen::with_clock]. A slow open (eg. on a laggy network filesystem)
+    /// stalls the operation that triggered it. The open done right away in
+    /// [`new`][Reopen::new] is not measured.
+    ///
+    /// Returns `None` if nothing was measured yet.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut writer = Reopen::lazy(Box::new(|| Ok(Vec::<u8>::new())));
+    /// assert!(writer.last_open_duration().is_none());
+    /// writer.write_all(b"Hello")?;
+    /// assert!(writer.last_open_duration().is_some());
+    /// # Ok(()) }
+    /// ```
+    pub fn last_open_duration(&self) -> Option<Duration> {
+        self.open_times.last
+    }
+
+    /// The total time spent opening FDs.
+    ///
+    /// This is the sum of all the durations measured as in
+    /// [`last_open_duration`][Reopen::last_open_duration].
+    pub fn total_open_duration(&self) -> Duration {
+        self.open_times.total
+    }
+
+    /// The longest time an open took.
+    ///
+    /// This is the maximum of all the durations measured as in
+    /// [`last_open_duration`][Reopen::last_open_duration].
+    pub fn max_open_duration(&self) -> Duration {
+        self.open_times.max
+    }
+}
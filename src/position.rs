@@ -0,0 +1,91 @@
+use std::io::{Error, Seek, SeekFrom};
+
+use super::Reopen;
+
+/// State of carrying the position in the file over a reopen.
+pub(crate) struct Position<FD> {
+    /// Reads the position of the FD.
+    ///
+    /// These are plain function pointers, so the `FD: Seek` bound is needed only when turning
+    /// the feature on.
+    save: fn(&mut FD) -> Result<u64, Error>,
+    /// Moves the FD to the given position.
+    restore: fn(&mut FD, u64) -> Result<u64, Error>,
+    /// The position of the last closed FD, waiting to be set on the new one.
+    saved: Option<u64>,
+}
+
+// stream_position is too new for our MSRV.
+#[allow(clippy::seek_from_current)]
+fn save<FD: Seek>(fd: &mut FD) -> Result<u64, Error> {
+    fd.seek(SeekFrom::Current(0))
+}
+
+fn restore<FD: Seek>(fd: &mut FD, pos: u64) -> Result<u64, Error> {
+    fd.seek(SeekFrom::Start(pos))
+}
+
+impl<FD: Seek> Reopen<FD> {
+    /// Keeps the position in the file across reopens.
+    ///
+    /// When the FD gets closed, its current position is remembered and the next opened FD is
+    /// moved to the same position before it is used. This is for applications writing at their
+    /// own offsets into a file that is reopened in place (eg. a preallocated circular buffer).
+    /// Files opened for appending don't need this, as each of their writes goes to the end
+    /// anyway.
+    ///
+    /// If the position can't be read from the old FD, the new one is left where it was opened.
+    /// If the new one can't be moved, the operation fails with that error and the FD is opened
+    /// again next time.
+    ///
+    /// The position is restored before the [header][Reopen::with_header] is written, so these
+    /// two don't mix well.
+    ///
+    /// This is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::{Cursor, Error, Seek, SeekFrom, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let mut file = Reopen::new(Box::new(|| Ok(Cursor::new(vec![0; 10]))))?.preserve_position(true);
+    /// file.lock()?.seek(SeekFrom::Start(4))?;
+    /// file.handle().reopen();
+    /// file.write_all(b"x")?;
+    /// assert_eq!(b"\0\0\0\0x\0\0\0\0\0", &file.lock()?.get_ref()[..]);
+    /// # Ok(()) }
+    /// ```
+    pub fn preserve_position(mut self, enable: bool) -> Self {
+        self.position = if enable {
+            Some(Position {
+                save: save::<FD>,
+                restore: restore::<FD>,
+                saved: None,
+            })
+        } else {
+            None
+        };
+        self
+    }
+}
+
+impl<FD> Reopen<FD> {
+    /// Remembers the position of the FD that is about to be closed.
+    pub(crate) fn save_position(&mut self) {
+        if let (Some(position), Some(fd)) = (self.position.as_mut(), self.fd.as_mut()) {
+            position.saved = (position.save)(fd).ok();
+        }
+    }
+
+    /// Moves the current FD to the remembered position, if there's one.
+    pub(crate) fn restore_position(&mut self) -> Result<(), Error> {
+        if let (Some(position), Some(fd)) = (self.position.as_mut(), self.fd.as_mut()) {
+            if let Some(pos) = position.saved {
+                (position.restore)(fd, pos)?;
+                position.saved = None;
+            }
+        }
+        Ok(())
+    }
+}
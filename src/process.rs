@@ -0,0 +1,80 @@
+//! Writing into the standard input of child processes.
+
+use std::io::Error;
+use std::process::Command;
+use std::time::Duration;
+
+use super::ChildWriter;
+
+/// Creates a constructor spawning a child process to write into.
+///
+/// Each call of the constructor spawns the command created by the closure, with a pipe to its
+/// standard input (eg. a log processor in the style of `svlogd` or `multilog`). This is what
+/// [`Reopen::spawn_child`][crate::Reopen::spawn_child] uses, but this allows to set up the
+/// [`Reopen`][crate::Reopen] in other ways (eg. [`lazy`][crate::Reopen::lazy]).
+///
+/// To replace a child that exited, turn on
+/// [`reopen_on_broken_pipe`][crate::Reopen::reopen_on_broken_pipe]. The write into a pipe
+/// nobody reads any more fails with [`BrokenPipe`][std::io::ErrorKind::BrokenPipe] and the next
+/// operation spawns a new child.
+///
+/// When the child is replaced (on a reopen) or the [`Reopen`][crate::Reopen] is dropped, its
+/// standard input is closed and it is waited for, so no zombies are left behind. It is not
+/// killed, to give it a chance to process everything written into it. See [`ChildWriter`] for
+/// the details.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # use std::process::Command;
+/// # use reopen::Reopen;
+/// # use reopen::process::piped;
+/// # fn main() -> Result<(), Error> {
+/// let mut log = Reopen::lazy(Box::new(piped(|| {
+///     let mut command = Command::new("svlogd");
+///     command.arg("/var/log/my-app");
+///     command
+/// })))
+/// .reopen_on_broken_pipe(true);
+/// writeln!(log, "Hello")?;
+/// # Ok(()) }
+/// ```
+pub fn piped<F>(command: F) -> impl Fn() -> Result<ChildWriter, Error>
+where
+    F: Fn() -> Command,
+{
+    move || ChildWriter::spawn(&mut command())
+}
+
+/// Creates a constructor spawning a child process, killed if it doesn't exit in time.
+///
+/// This is like [`piped`], but each child gets the `grace` period to exit once it is replaced
+/// (or the [`Reopen`][crate::Reopen] is dropped) and its standard input closed. Then it is
+/// killed, see [`ChildWriter::kill_after`]. This is for long-lived children (eg. a side-car
+/// process) that must not run twice at the same time, but might not exit on their own promptly
+/// (eg. while they are being upgraded).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::{Error, Write};
+/// # use std::process::Command;
+/// # use std::time::Duration;
+/// # use reopen::Reopen;
+/// # use reopen::process::piped_with_grace;
+/// # fn main() -> Result<(), Error> {
+/// let mut metrics = Reopen::from_fn(piped_with_grace(
+///     || Command::new("/usr/libexec/metrics-sidecar"),
+///     Duration::from_secs(5),
+/// ))?
+/// .reopen_on_broken_pipe(true);
+/// writeln!(metrics, "requests 42")?;
+/// # Ok(()) }
+/// ```
+pub fn piped_with_grace<F>(command: F, grace: Duration) -> impl Fn() -> Result<ChildWriter, Error>
+where
+    F: Fn() -> Command,
+{
+    move || ChildWriter::spawn(&mut command()).map(|writer| writer.kill_after(grace))
+}
@@ -0,0 +1,105 @@
+use std::io::{Error, Write};
+
+use super::Reopen;
+
+/// A writer over [`Reopen`] that never splits a record between two FDs.
+///
+/// The data are buffered until a record delimiter (`\n` by default) comes. Only whole records
+/// are passed to the FD, by [`write_record`][Reopen::write_record]. As a reopen happens only at
+/// the start of an operation on the [`Reopen`], a reopen requested in the middle of a record
+/// is deferred until the record is complete and the record then goes whole into the new FD.
+///
+/// The incomplete record at the end is kept in the buffer, even on [`flush`][Write::flush].
+/// It is written (without the delimiter) when the writer is dropped. Note that a long record
+/// without any delimiter grows the buffer without limit.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::{Error, Write};
+/// # use reopen::{RecordWriter, Reopen};
+/// # fn main() -> Result<(), Error> {
+/// let mut writer = RecordWriter::new(Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?);
+/// write!(writer, "Hello ")?;
+/// assert!(writer.get_mut().lock()?.is_empty());
+/// writeln!(writer, "world")?;
+/// assert_eq!(b"Hello world\n", &writer.get_mut().lock()?[..]);
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct RecordWriter<FD: Write> {
+    reopen: Reopen<FD>,
+    delimiter: u8,
+    buffer: Vec<u8>,
+}
+
+impl<FD: Write> RecordWriter<FD> {
+    /// Wraps the [`Reopen`], with `\n` as the delimiter.
+    pub fn new(reopen: Reopen<FD>) -> Self {
+        RecordWriter {
+            reopen,
+            delimiter: b'\n',
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Sets the byte ending each record.
+    ///
+    /// The delimiter is part of the record it ends.
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// The wrapped [`Reopen`].
+    pub fn get_ref(&self) -> &Reopen<FD> {
+        &self.reopen
+    }
+
+    /// The wrapped [`Reopen`].
+    ///
+    /// Writing into it directly bypasses the buffer.
+    pub fn get_mut(&mut self) -> &mut Reopen<FD> {
+        &mut self.reopen
+    }
+
+    /// The incomplete record waiting for its delimiter.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl<FD: Write> Write for RecordWriter<FD> {
+    fn flush(&mut self) -> Result<(), Error> {
+        self.reopen.flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let end = match buf.iter().rposition(|b| *b == self.delimiter) {
+            Some(pos) => pos + 1,
+            None => {
+                self.buffer.extend_from_slice(buf);
+                return Ok(buf.len());
+            }
+        };
+        let previous = self.buffer.len();
+        self.buffer.extend_from_slice(&buf[..end]);
+        if let Err(e) = self.reopen.write_record(&self.buffer) {
+            // Nothing of this buf was accepted
+            self.buffer.truncate(previous);
+            return Err(e);
+        }
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&buf[end..]);
+        Ok(buf.len())
+    }
+}
+
+impl<FD: Write> Drop for RecordWriter<FD> {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            // Nobody to report the error to.
+            let _ = self.reopen.write_record(&self.buffer);
+        }
+    }
+}
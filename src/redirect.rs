@@ -0,0 +1,173 @@
+//! Redirecting the standard output and error of the whole process into a reopened file.
+//!
+//! # Features
+//!
+//! This is available only with the `redirect` feature enabled, on unix.
+
+use std::fs::File;
+use std::io::{self, Error, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::PathBuf;
+use std::ptr;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use super::{Handle, Reopen};
+
+/// How often the helper thread looks for a pending reopen.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Which of the standard descriptors to redirect, see [`stdio`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StdioTargets {
+    /// Only the standard output.
+    Stdout,
+    /// Only the standard error output.
+    Stderr,
+    /// Both the standard output and the standard error output.
+    Both,
+}
+
+impl StdioTargets {
+    fn fds(self) -> &'static [RawFd] {
+        match self {
+            StdioTargets::Stdout => &[libc::STDOUT_FILENO],
+            StdioTargets::Stderr => &[libc::STDERR_FILENO],
+            StdioTargets::Both => &[libc::STDOUT_FILENO, libc::STDERR_FILENO],
+        }
+    }
+}
+
+fn check(result: libc::c_int) -> Result<libc::c_int, Error> {
+    if result == -1 {
+        Err(Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
+
+/// Puts the `src` descriptor in place of all the `targets`.
+///
+/// Whatever is still buffered for the old ones is written out first, so it ends up where it was
+/// meant to go.
+#[allow(unsafe_code)]
+fn redirect(src: RawFd, targets: &[RawFd]) -> Result<(), Error> {
+    // Nothing to do about the errors here, the data would be lost either way.
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+    for &target in targets {
+        // SAFETY: fflush(NULL) flushes all the C streams, dup2 only replaces one descriptor
+        // with a copy of another.
+        unsafe {
+            libc::fflush(ptr::null_mut());
+            check(libc::dup2(src, target))?;
+        }
+    }
+    Ok(())
+}
+
+/// Redirects the standard output and error into the file until dropped.
+///
+/// See [`stdio`].
+#[derive(Debug)]
+pub struct RedirectGuard {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+    /// The original descriptors, to put back.
+    saved: Vec<(RawFd, File)>,
+}
+
+impl Drop for RedirectGuard {
+    fn drop(&mut self) {
+        // Closing our end wakes the thread up.
+        drop(self.stop.take());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        for &(target, ref original) in &self.saved {
+            // Nobody to report the error to.
+            let _ = redirect(original.as_raw_fd(), &[target]);
+        }
+    }
+}
+
+/// Redirects the standard output and/or error of the whole process into a file.
+///
+/// Writing through a [`Reopen`] covers only the data written by Rust code that knows about it.
+/// Libraries (especially ones written in C) often write to the standard descriptors directly.
+/// This puts the file opened by a [`Reopen`] in place of the descriptors (by `dup2`), each time
+/// it is opened anew. So even these writes go into the current file after a reopen.
+///
+/// The file is opened for appending (as in [`append_to`][Reopen::append_to]) and redirected to
+/// right away. The returned [`Handle`] requests the reopens (eg. it can be registered with a
+/// signal). As there may be nothing writing through the [`Reopen`], a helper thread looks for
+/// the requests and does the reopen within a fraction of a second. If the new file can't be
+/// opened, the descriptors keep pointing to the old one and the reopen is retried later.
+///
+/// Before switching, the buffers of the standard library and of the C stdio are flushed, so the
+/// data written before the reopen go to the old file. Dropping the guard stops the thread and
+/// puts the original descriptors back.
+///
+/// # Errors
+///
+/// If the file can't be opened, the descriptors can't be duplicated or the helper thread can't
+/// be started. The original descriptors are kept in place then.
+///
+/// # Features
+///
+/// This is available only with the `redirect` feature enabled, on unix.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::Error;
+/// # use reopen::redirect::{stdio, StdioTargets};
+/// # fn main() -> Result<(), Error> {
+/// let (_guard, handle) = stdio("/log/everything", StdioTargets::Both)?;
+/// println!("Goes to the file");
+/// // The file got rotated, switch to a new one.
+/// handle.reopen();
+/// # Ok(()) }
+/// ```
+#[allow(unsafe_code)]
+pub fn stdio<P: Into<PathBuf>>(
+    path: P,
+    which: StdioTargets,
+) -> Result<(RedirectGuard, Handle), Error> {
+    let targets = which.fds();
+    let mut saved = Vec::with_capacity(targets.len());
+    for &target in targets {
+        // SAFETY: dup creates a new descriptor, which we then own.
+        let original = unsafe { File::from_raw_fd(check(libc::dup(target))?) };
+        saved.push((target, original));
+    }
+    let mut reopen = Reopen::lazy_append_to(path.into());
+    reopen.open_hook = Some(Box::new(move |file: &mut File| {
+        redirect(file.as_raw_fd(), targets)
+    }));
+    reopen.open_hook_pending = true;
+    reopen.slow = true;
+    let mut guard = RedirectGuard {
+        stop: None,
+        thread: None,
+        saved,
+    };
+    // Dropping the guard on error restores whatever got redirected already.
+    reopen.lock()?;
+    let handle = reopen.handle();
+    let (stop, stopped) = mpsc::channel::<()>();
+    let thread = thread::Builder::new()
+        .name("reopen-redirect".to_owned())
+        .spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(POLL_INTERVAL) {
+                if reopen.is_pending() {
+                    // Failed reopens stay pending and are retried next time.
+                    let _ = reopen.lock();
+                }
+            }
+        })?;
+    guard.stop = Some(stop);
+    guard.thread = Some(thread);
+    Ok((guard, handle))
+}
@@ -0,0 +1,55 @@
+use std::io::Error;
+
+use super::{Handle, Reopen};
+
+/// Something that can be reopened.
+///
+/// This is implemented by the [`Reopen`] and the wrappers around it
+/// ([`SyncReopen`][crate::SyncReopen], [`BufferedReopen`][crate::BufferedReopen] and, with the
+/// `arc-swap` feature, the `SwapReopen`). It allows code that only needs to control the reopening
+/// (eg. some logging infrastructure) to accept any of them. The trait is object safe, so
+/// `&mut dyn Reopenable` works too.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::Error;
+/// # use reopen::{Reopen, Reopenable, SyncReopen};
+/// fn rotated(target: &mut dyn Reopenable) -> Result<(), Error> {
+///     target.reopen_now()
+/// }
+///
+/// # fn main() -> Result<(), Error> {
+/// let mut plain = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?;
+/// let mut sync = SyncReopen::new(Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?);
+/// rotated(&mut plain)?;
+/// rotated(&mut sync)?;
+/// # Ok(()) }
+/// ```
+pub trait Reopenable {
+    /// Returns a handle to request a reopen.
+    fn handle(&self) -> Handle;
+
+    /// Reopens right away, without waiting for the next operation.
+    ///
+    /// # Errors
+    ///
+    /// If opening the new FD fails. The reopen then stays pending, so the next operation tries
+    /// again.
+    fn reopen_now(&mut self) -> Result<(), Error>;
+}
+
+impl<FD> Reopenable for Reopen<FD> {
+    fn handle(&self) -> Handle {
+        Reopen::handle(self)
+    }
+
+    fn reopen_now(&mut self) -> Result<(), Error> {
+        self.request_inside();
+        let result = self.prepare_with(true);
+        if result.is_err() {
+            self.until_check = 0;
+        }
+        result
+    }
+}
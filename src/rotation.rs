@@ -0,0 +1,207 @@
+use std::cell::Cell;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use super::file::append_options;
+use super::Reopen;
+
+/// Requesting a reopen once the current FD grows over a limit.
+pub(crate) struct SizeLimit<FD> {
+    limit: u64,
+    /// The size of the current FD, as far as we know.
+    size: Cell<u64>,
+    /// The FD was handed out, so the size may have changed without us knowing.
+    stale: Cell<bool>,
+    /// Finds out the size of a freshly opened FD (it may already contain something).
+    probe: fn(&FD) -> u64,
+}
+
+impl<FD> Reopen<FD> {
+    /// Starts counting the size of a newly installed FD.
+    pub(crate) fn size_opened(&self) {
+        if let (Some(limit), Some(fd)) = (self.size_limit.as_ref(), self.fd.as_ref()) {
+            limit.size.set((limit.probe)(fd));
+            limit.stale.set(false);
+        }
+    }
+
+    /// Counts the written bytes and asks for a reopen once over the limit.
+    pub(crate) fn size_written(&self, bytes: usize) {
+        if let Some(limit) = self.size_limit.as_ref() {
+            if limit.stale.get() {
+                // The bytes are already in there.
+                self.size_refresh(limit);
+            } else {
+                self.size_check(limit, limit.size.get() + bytes as u64);
+            }
+        }
+    }
+
+    /// Notes the FD is being handed out to the user.
+    ///
+    /// Nothing written through it is counted, so the size is found out from the FD itself before
+    /// the next decision to rotate. In particular, if it was handed out before, it is checked
+    /// right away (so a pending rotation happens before handing it out again).
+    pub(crate) fn size_handed_out(&self) {
+        if let Some(limit) = self.size_limit.as_ref() {
+            if limit.stale.get() {
+                self.size_refresh(limit);
+            }
+            limit.stale.set(true);
+        }
+    }
+
+    /// Finds out the real size of the current FD.
+    fn size_refresh(&self, limit: &SizeLimit<FD>) {
+        if let Some(fd) = self.fd.as_ref() {
+            limit.stale.set(false);
+            self.size_check(limit, (limit.probe)(fd));
+        }
+    }
+
+    fn size_check(&self, limit: &SizeLimit<FD>, size: u64) {
+        limit.size.set(size);
+        if size >= limit.limit {
+            self.request_inside();
+        }
+    }
+}
+
+/// Compressing the rotated files.
+#[derive(Clone, Copy)]
+pub(crate) struct Compress {
+    /// The suffix of the compressed files (eg. `.gz`).
+    pub(crate) suffix: &'static str,
+    /// Compresses the first file into the second one.
+    pub(crate) compress: fn(&Path, &Path) -> Result<(), Error>,
+}
+
+/// The path of the n-th rotated file (`path.n`, followed by the suffix).
+fn rotated(path: &Path, n: usize, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{}{}", n, suffix));
+    PathBuf::from(name)
+}
+
+/// Like the operation, but a missing file is fine.
+fn missing_ok(result: Result<(), Error>) -> Result<(), Error> {
+    match result {
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+/// Moves the file at path to `path.1`, shifting the older ones and dropping what's over `keep`.
+///
+/// With compression, the file is compressed into `path.1` (with the suffix) instead and then
+/// removed.
+fn rotate(path: &Path, keep: usize, compress: Option<Compress>) -> Result<(), Error> {
+    if keep == 0 {
+        return missing_ok(fs::remove_file(path));
+    }
+    let suffix = compress.map_or("", |c| c.suffix);
+    missing_ok(fs::remove_file(rotated(path, keep, suffix)))?;
+    for n in (1..keep).rev() {
+        missing_ok(fs::rename(
+            rotated(path, n, suffix),
+            rotated(path, n + 1, suffix),
+        ))?;
+    }
+    match compress {
+        Some(compress) => {
+            missing_ok((compress.compress)(path, &rotated(path, 1, suffix)))?;
+            missing_ok(fs::remove_file(path))
+        }
+        None => missing_ok(fs::rename(path, rotated(path, 1, suffix))),
+    }
+}
+
+impl Reopen<File> {
+    /// Creates a [`Reopen`] appending to a file, rotating it once it grows over a size limit.
+    ///
+    /// This is the "just give me a rotating log file" setup, without the need for any external
+    /// tool. The file is opened as in [`append_to`][Reopen::append_to]. Once `size_limit` bytes
+    /// are written into it, a reopen is requested. Before opening the file again, the full one
+    /// is renamed to `path.1`, the previous `path.1` to `path.2` and so on. Only `keep` rotated
+    /// files are kept, the oldest one is deleted (with `keep` being `0`, the full file is simply
+    /// deleted). A fresh file is then created at the `path`, so the writes always go there.
+    ///
+    /// The size includes whatever the file contained when opened (eg. from a previous run of the
+    /// program), so an already full file is rotated right away. The limit is checked
+    /// after each write, therefore the files can grow over the limit by the last write (which is
+    /// never split between two files). The writes done directly into the file handed out by
+    /// [`lock`][Reopen::lock] are not counted, the size of the file is read from its metadata
+    /// at the next write or `lock` instead. Reopens requested in other ways (eg. through the
+    /// [`handle`][Reopen::handle]) rotate the file only if it is full, otherwise they just open
+    /// it again.
+    ///
+    /// The rotation is done by renaming the files, so anything else that keeps them open (eg.
+    /// `tail -f`) keeps reading the old one. Only one [`Reopen`] (in one process) should rotate
+    /// the same file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be opened (or rotated, if it's already full). The `size_limit` of `0`
+    /// is refused with [`InvalidInput`][ErrorKind::InvalidInput].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::{Error, Write};
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// // Up to 10MB in app.log, and the older logs in app.log.1 .. app.log.5.
+    /// let mut log = Reopen::append_with_rotation("/log/app.log", 10 * 1024 * 1024, 5)?;
+    /// writeln!(log, "Hello")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn append_with_rotation<P: Into<PathBuf>>(
+        path: P,
+        size_limit: u64,
+        keep: usize,
+    ) -> Result<Self, Error> {
+        Self::rotating(path.into(), size_limit, keep, None)
+    }
+
+    /// The [`append_with_rotation`][Reopen::append_with_rotation], optionally compressing the
+    /// rotated files.
+    pub(crate) fn rotating(
+        path: PathBuf,
+        size_limit: u64,
+        keep: usize,
+        compress: Option<Compress>,
+    ) -> Result<Self, Error> {
+        if size_limit == 0 {
+            // It would rotate (and shift all the kept files) on every single write.
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "The size limit of rotation must not be 0",
+            ));
+        }
+        let options = append_options();
+        let mut reopen = Self::new(Box::new({
+            let path = path.clone();
+            move || {
+                let full = match fs::metadata(&path) {
+                    Ok(meta) => meta.len() >= size_limit,
+                    Err(_) => false,
+                };
+                if full {
+                    rotate(&path, keep, compress)?;
+                }
+                options.open(&path)
+            }
+        }))?;
+        reopen.path = Some(path);
+        reopen.size_limit = Some(SizeLimit {
+            limit: size_limit,
+            size: Cell::new(0),
+            stale: Cell::new(false),
+            probe: |file| file.metadata().map(|meta| meta.len()).unwrap_or(0),
+        });
+        reopen.size_opened();
+        Ok(reopen)
+    }
+}
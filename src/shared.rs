@@ -0,0 +1,258 @@
+//! The state shared between a [`Reopen`][crate::Reopen] and its [`Handle`][crate::Handle]s.
+//!
+//! All the concurrent interaction between the handles and the reopen goes through here, so it can
+//! be checked by [loom](https://docs.rs/loom) (run with
+//! `RUSTFLAGS="--cfg loom" cargo test --release --lib shared`).
+//!
+//! # Orderings
+//!
+//! The request flag doesn't protect any data in this crate, but the constructor may depend on
+//! something the requester did before asking for the reopen (eg. it may have changed where to
+//! open the new file). Therefore the request is a `Release` store and taking it is an `Acquire`
+//! operation. Anything done before [`Shared::request`] is visible to the constructor run as a
+//! result of that request.
+//!
+//! The hot path only peeks at the flag with a `Relaxed` load and does the `Acquire` swap only if
+//! it sees it set. The swap is what synchronizes with the request, so the above still holds.
+//!
+//! The pause counter holds the requests back, but never consumes them. Taking a request loads the
+//! counter with `Acquire` and [`Shared::resume`] decrements it with `Release`, so anything done
+//! before the last resume is visible to the constructor run after it (the same as with the request
+//! itself). The hot path peeks at the counter with `Relaxed`, as it does with the flag.
+
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(not(loom))]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::events::{ReopenEvent, Subscribers};
+#[cfg(siginfo)]
+use super::status::Status;
+
+#[derive(Debug)]
+pub(crate) struct Shared {
+    /// The flag requesting a reopen.
+    ///
+    /// This one is separately in an [`Arc`], because a signal handler may want to hold onto it.
+    signal: Arc<AtomicBool>,
+    /// How many pauses are in effect, requests are held back while non-zero.
+    paused: AtomicUsize,
+    pub(crate) events: Subscribers,
+    #[cfg(siginfo)]
+    pub(crate) status: Status,
+}
+
+impl Shared {
+    pub(crate) fn new() -> Self {
+        Shared {
+            signal: Arc::new(AtomicBool::new(false)),
+            paused: AtomicUsize::new(0),
+            events: Subscribers::default(),
+            #[cfg(siginfo)]
+            status: Status::default(),
+        }
+    }
+
+    /// Asks for a reopen on the next operation.
+    pub(crate) fn request(&self) {
+        self.signal.store(true, Ordering::Release);
+        self.events.emit(ReopenEvent::ReopenRequested);
+    }
+
+    /// Puts back a request that was taken but couldn't be satisfied.
+    ///
+    /// Unlike [`request`][Shared::request], this doesn't produce an event, as it is not a new
+    /// request.
+    pub(crate) fn restore_request(&self) {
+        self.signal.store(true, Ordering::Release);
+    }
+
+    /// Checks if there's a pending request, without consuming it.
+    pub(crate) fn requested(&self) -> bool {
+        self.signal.load(Ordering::Acquire)
+    }
+
+    /// A cheap check if there might be a pending request that can be taken.
+    ///
+    /// This doesn't synchronize with the requester, so it is only a hint whether
+    /// [`take_request`][Shared::take_request] is worth calling. A request is noticed eventually,
+    /// not necessarily right away. Requests held back by a pause don't count.
+    pub(crate) fn maybe_requested(&self) -> bool {
+        self.signal.load(Ordering::Relaxed) && self.paused.load(Ordering::Relaxed) == 0
+    }
+
+    /// Consumes the pending request, if any.
+    ///
+    /// Multiple requests that happen before this is called are merged into one. Nothing is taken
+    /// while paused, the request stays pending for after the resume.
+    pub(crate) fn take_request(&self) -> bool {
+        // The read-modify-write is needed only if there's something to take.
+        self.maybe_requested()
+            && self.paused.load(Ordering::Acquire) == 0
+            && self.signal.swap(false, Ordering::AcqRel)
+    }
+
+    /// Holds back the requests until the matching [`resume`][Shared::resume].
+    pub(crate) fn pause(&self) {
+        self.paused.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Ends one pause.
+    ///
+    /// # Panics
+    ///
+    /// If there's no pause in effect.
+    pub(crate) fn resume(&self) {
+        let previous = self.paused.fetch_sub(1, Ordering::Release);
+        if previous == 0 {
+            // Put it back, so the counter isn't left wrapped around.
+            self.paused.fetch_add(1, Ordering::Relaxed);
+            panic!("Resume without a pause");
+        }
+    }
+
+    /// The raw flag, for places that can't use the methods (signal handlers).
+    #[cfg(all(
+        any(
+            all(feature = "signals", any(unix, windows)),
+            all(feature = "windows-console", windows)
+        ),
+        not(loom)
+    ))]
+    pub(crate) fn flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.signal)
+    }
+}
+
+#[cfg(all(test, loom))]
+mod tests {
+    use std::io::Error;
+
+    use loom::sync::atomic::{AtomicUsize, Ordering};
+    use loom::sync::Arc;
+    use loom::thread;
+
+    use crate::{Handle, Reopen};
+
+    /// A reopen with a counter of how many times the constructor was called.
+    fn counted() -> (Reopen<usize>, Arc<AtomicUsize>) {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let reopen = Reopen::new(Box::new({
+            let opened = Arc::clone(&opened);
+            move || Ok::<_, Error>(opened.fetch_add(1, Ordering::Relaxed))
+        }))
+        .unwrap();
+        (reopen, opened)
+    }
+
+    /// A request concurrent with a lock is either satisfied by it or by the next one, but never
+    /// lost and never satisfied twice.
+    #[test]
+    fn request_during_lock() {
+        loom::model(|| {
+            let (mut reopen, opened) = counted();
+            let handle = reopen.handle();
+            let requester = thread::spawn(move || handle.reopen());
+            reopen.lock().unwrap();
+            requester.join().unwrap();
+            reopen.lock().unwrap();
+            assert_eq!(2, opened.load(Ordering::Relaxed));
+        });
+    }
+
+    /// Two handles requesting at the same time. Each request is satisfied, but they may be merged
+    /// into one reopen.
+    #[test]
+    fn concurrent_requests() {
+        loom::model(|| {
+            let (mut reopen, opened) = counted();
+            let requesters = (0..2)
+                .map(|_| {
+                    let handle = reopen.handle();
+                    thread::spawn(move || handle.reopen())
+                })
+                .collect::<Vec<_>>();
+            reopen.lock().unwrap();
+            for requester in requesters {
+                requester.join().unwrap();
+            }
+            reopen.lock().unwrap();
+            let opened = opened.load(Ordering::Relaxed);
+            assert!(opened == 2 || opened == 3, "Opened {} times", opened);
+            // Nothing left pending.
+            reopen.lock().unwrap();
+            assert_eq!(opened, opened_now(&reopen));
+        });
+    }
+
+    fn opened_now(reopen: &Reopen<usize>) -> usize {
+        // The FD is the sequence number of the open, so the count is one more.
+        reopen.fd.unwrap() + 1
+    }
+
+    /// A request made while paused is held back, but not lost, no matter how the pause, the request
+    /// and the resume interleave with the operations.
+    #[test]
+    fn pause_request_resume() {
+        loom::model(|| {
+            let (mut reopen, opened) = counted();
+            let handle = reopen.handle();
+            let requester = thread::spawn(move || {
+                handle.pause();
+                handle.reopen();
+                handle.resume();
+            });
+            reopen.lock().unwrap();
+            requester.join().unwrap();
+            reopen.lock().unwrap();
+            assert_eq!(2, opened.load(Ordering::Relaxed));
+        });
+    }
+
+    /// Nothing is reopened while paused, even with the request made before the pause.
+    #[test]
+    fn paused_holds_back() {
+        loom::model(|| {
+            let (mut reopen, opened) = counted();
+            let handle = reopen.handle();
+            handle.reopen();
+            handle.pause();
+            let locker = thread::spawn(move || {
+                reopen.lock().unwrap();
+                reopen
+            });
+            let mut reopen = locker.join().unwrap();
+            assert_eq!(1, opened.load(Ordering::Relaxed));
+            handle.resume();
+            reopen.lock().unwrap();
+            assert_eq!(2, opened.load(Ordering::Relaxed));
+        });
+    }
+
+    /// Whatever the requester did before asking for the reopen is visible to the constructor.
+    #[test]
+    fn request_publishes() {
+        loom::model(|| {
+            let param = Arc::new(AtomicUsize::new(0));
+            let handle = Handle::stub();
+            let mut reopen = Reopen::with_handle(
+                handle.clone(),
+                Box::new({
+                    let param = Arc::clone(&param);
+                    move || Ok::<_, Error>(param.load(Ordering::Relaxed))
+                }),
+            )
+            .unwrap();
+            let requester = thread::spawn(move || {
+                param.store(42, Ordering::Relaxed);
+                handle.reopen();
+            });
+            let value = *reopen.lock().unwrap();
+            // Either it didn't get reopened yet, or it sees the new parameter.
+            assert!(value == 0 || value == 42);
+            requester.join().unwrap();
+            assert_eq!(42, *reopen.lock().unwrap());
+        });
+    }
+}
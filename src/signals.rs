@@ -1,9 +1,129 @@
+//! Support for reopening on signals.
+//!
+//! The signal numbers can be passed either as raw numbers (eg. from the [`consts`] module) or as
+//! the [`Signal`] enum, so users don't need to depend on `libc` or `signal-hook` directly.
+//!
+//! # Features
+//!
+//! This is available only with the `signals` feature enabled.
+//!
+//! # Platform support
+//!
+//! Signals are supported on the platforms `signal-hook` supports (unix and, in a very limited
+//! way, windows). Elsewhere (eg. WASI), this still compiles, but
+//! [`register_signal`][Handle::register_signal] always fails.
+
 use std::io::Error;
-use std::sync::Arc;
+#[cfg(not(any(unix, windows)))]
+use std::io::ErrorKind;
+use std::os::raw::c_int;
+
+use super::{Handle, Reopen};
+
+/// Removes a signal handler registered by [`register_signal`][Handle::register_signal].
+#[cfg(any(unix, windows))]
+pub(crate) fn unregister(id: SigId) {
+    signal_hook::low_level::unregister(id);
+}
+
+/// Nothing can be registered here.
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn unregister(_: SigId) {}
+
+/// The identifier of a registered signal handler, returned from
+/// [`register_signal`][Handle::register_signal].
+#[cfg(any(unix, windows))]
+pub use signal_hook::SigId;
+
+/// The identifier of a registered signal handler.
+///
+/// On this platform, registering signals always fails, so this is never actually created.
+#[cfg(not(any(unix, windows)))]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct SigId(());
+
+/// The common signal numbers.
+///
+/// These are the same ones `signal-hook` (as used by this crate) uses. Windows has only some of
+/// them. On platforms without signal support, these are only placeholders.
+pub mod consts {
+    #[cfg(unix)]
+    pub use signal_hook::consts::{SIGHUP, SIGUSR1, SIGUSR2};
+    #[cfg(any(unix, windows))]
+    pub use signal_hook::consts::{SIGINT, SIGTERM};
 
-use signal_hook::SigId;
+    #[cfg(not(any(unix, windows)))]
+    mod placeholders {
+        use std::os::raw::c_int;
+
+        /// A placeholder for `SIGHUP`.
+        pub const SIGHUP: c_int = 1;
+        /// A placeholder for `SIGINT`.
+        pub const SIGINT: c_int = 2;
+        /// A placeholder for `SIGUSR1`.
+        pub const SIGUSR1: c_int = 10;
+        /// A placeholder for `SIGUSR2`.
+        pub const SIGUSR2: c_int = 12;
+        /// A placeholder for `SIGTERM`.
+        pub const SIGTERM: c_int = 15;
+    }
+    #[cfg(not(any(unix, windows)))]
+    pub use self::placeholders::*;
+}
+
+/// The signals commonly used to ask for a reopen.
+///
+/// Accepted by [`Handle::register_signal`] in place of the raw number.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::Error;
+/// # use reopen::Reopen;
+/// # use reopen::signals::Signal;
+/// # fn main() -> Result<(), Error> {
+/// let file = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?;
+/// file.handle().register_signal(Signal::Term)?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Signal {
+    /// The `SIGHUP`, the traditional one for reopening log files.
+    #[cfg(not(windows))]
+    Hup,
+    /// The `SIGUSR1`.
+    #[cfg(not(windows))]
+    Usr1,
+    /// The `SIGUSR2`.
+    #[cfg(not(windows))]
+    Usr2,
+    /// The `SIGTERM`.
+    Term,
+    /// The `SIGINT`.
+    Int,
+}
+
+impl Signal {
+    /// The raw signal number.
+    pub fn as_raw(self) -> c_int {
+        match self {
+            #[cfg(not(windows))]
+            Signal::Hup => consts::SIGHUP,
+            #[cfg(not(windows))]
+            Signal::Usr1 => consts::SIGUSR1,
+            #[cfg(not(windows))]
+            Signal::Usr2 => consts::SIGUSR2,
+            Signal::Term => consts::SIGTERM,
+            Signal::Int => consts::SIGINT,
+        }
+    }
+}
 
-use super::Handle;
+impl From<Signal> for c_int {
+    fn from(signal: Signal) -> c_int {
+        signal.as_raw()
+    }
+}
 
 impl Handle {
     /// Installs a signal handler to invoke the reopening when a certain signal comes.
@@ -12,6 +132,11 @@ impl Handle {
     ///
     /// This is available only with the `signals` feature enabled.
     ///
+    /// # Errors
+    ///
+    /// Besides the errors from `signal-hook`, this always fails on platforms without signal
+    /// support (see the [module documentation][crate::signals]).
+    ///
     /// # Notes
     ///
     /// * Under the hood, this uses the [`signal-hook`](https://crates.io/signal-hook) crate, so
@@ -28,21 +153,80 @@ impl Handle {
     /// * A single handle can be used for multiple signals.
     /// * To unregister a handle from a signal handle, use the returned `SigId` and the
     ///   [`signal_hook::unregister`](https://docs.rs/signal-hook/*/signal_hook/fn.unregister.html).
-    pub fn register_signal(&self, signal: libc::c_int) -> Result<SigId, Error> {
-        signal_hook::flag::register(signal, Arc::clone(&self.0))
+    /// * The signal can be either a raw number or a [`Signal`].
+    #[cfg(any(unix, windows))]
+    pub fn register_signal<S: Into<c_int>>(&self, signal: S) -> Result<SigId, Error> {
+        signal_hook::flag::register(signal.into(), self.0.flag())
+    }
+
+    /// Installs a signal handler to invoke the reopening when a certain signal comes.
+    ///
+    /// There's no signal support on this platform, so this always fails.
+    #[cfg(not(any(unix, windows)))]
+    // Error::other is too new for our MSRV.
+    #[allow(clippy::io_other_error)]
+    pub fn register_signal<S: Into<c_int>>(&self, _signal: S) -> Result<SigId, Error> {
+        Err(Error::new(
+            ErrorKind::Other,
+            "Signals are not supported on this platform",
+        ))
     }
 }
 
-#[cfg(all(test, not(windows)))] // Not testing on windows, very limited signal support
+impl<FD> Reopen<FD> {
+    /// Creates a new instance reopening on the given signal.
+    ///
+    /// This is the same as [`new`][Reopen::new] followed by
+    /// [`register_signal`][Handle::register_signal] on its handle, the common "open a file and
+    /// reopen on `SIGHUP`" in a single call. The returned `SigId` can be used to unregister the
+    /// signal later.
+    ///
+    /// With the other constructors (eg. [`lazy`][Reopen::lazy]), register the signal on the
+    /// [`handle`][Reopen::handle] of the created instance the same way.
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `signals` feature enabled.
+    ///
+    /// # Errors
+    ///
+    /// If the constructor fails or the signal can't be registered (see
+    /// [`register_signal`][Handle::register_signal]).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # use reopen::signals::consts::SIGTERM;
+    /// # fn main() -> Result<(), Error> {
+    /// # #[cfg(any(unix, windows))] {
+    /// let (file, _sig_id) = Reopen::with_signal(SIGTERM, Box::new(|| Ok(Vec::<u8>::new())))?;
+    /// # let _ = file;
+    /// # }
+    /// # Ok(()) }
+    /// ```
+    pub fn with_signal<S: Into<c_int>>(
+        signal: S,
+        constructor: Box<dyn Fn() -> Result<FD, Error> + Send>,
+    ) -> Result<(Self, SigId), Error> {
+        let reopen = Self::new(constructor)?;
+        let id = reopen.handle().register_signal(signal)?;
+        Ok((reopen, id))
+    }
+}
+
+#[cfg(all(test, unix))] // Not testing on windows, very limited signal support
 mod tests {
     use std::io::Read;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use std::thread;
     use std::time::Duration;
 
     use super::*;
 
-    struct Fake(Arc<AtomicUsize>);
+    struct Fake;
 
     impl Read for Fake {
         fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Error> {
@@ -50,13 +234,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn signal_numbers() {
+        assert_eq!(libc::SIGHUP, Signal::Hup.as_raw());
+        assert_eq!(libc::SIGUSR1, Signal::Usr1.as_raw());
+        assert_eq!(libc::SIGUSR2, c_int::from(Signal::Usr2));
+        assert_eq!(libc::SIGTERM, Signal::Term.as_raw());
+        assert_eq!(libc::SIGINT, consts::SIGINT);
+    }
+
     #[test]
     fn signal_sent() {
         let opened_times = Arc::new(AtomicUsize::new(0));
         let opened_times_cp = Arc::clone(&opened_times);
         let mut reopen = crate::Reopen::new(Box::new(move || {
             opened_times_cp.fetch_add(1, Ordering::Relaxed);
-            Ok(Fake(Arc::clone(&opened_times_cp)))
+            Ok(Fake)
         }))
         .unwrap();
         assert_eq!(1, opened_times.load(Ordering::Relaxed));
@@ -66,6 +259,7 @@ mod tests {
         // Don't register sooner, in case some other test uses the signal.
         reopen.handle().register_signal(libc::SIGHUP).unwrap();
         // Now send us a signal
+        // SAFETY: plain FFI call, the signal has a handler registered.
         unsafe { libc::kill(libc::getpid(), libc::SIGHUP) };
         // Wait a little for the signal to propagate, as it might arrive into another thread. The
         // second here is not guaranteed to work, this is only a hack for tests.
@@ -74,4 +268,54 @@ mod tests {
         // It got reopened
         assert_eq!(2, opened_times.load(Ordering::Relaxed));
     }
+
+    #[test]
+    fn with_signal_sent() {
+        let opened_times = Arc::new(AtomicUsize::new(0));
+        let opened_times_cp = Arc::clone(&opened_times);
+        let (mut reopen, id) = crate::Reopen::with_signal(
+            Signal::Usr1,
+            Box::new(move || {
+                opened_times_cp.fetch_add(1, Ordering::Relaxed);
+                Ok(Fake)
+            }),
+        )
+        .unwrap();
+        assert_eq!(1, opened_times.load(Ordering::Relaxed));
+        // SAFETY: plain FFI call, the signal has a handler registered.
+        unsafe { libc::kill(libc::getpid(), libc::SIGUSR1) };
+        // The same hack as above.
+        thread::sleep(Duration::from_secs(1));
+        let mut buf = [0];
+        assert_eq!(0, reopen.read(&mut buf).unwrap());
+        assert_eq!(2, opened_times.load(Ordering::Relaxed));
+        assert!(signal_hook::low_level::unregister(id));
+    }
+
+    /// A handle registered before it's given to the `Reopen` works too, even though the signal
+    /// handler doesn't hold the handle itself.
+    #[test]
+    fn registered_before_with_handle() {
+        let opened_times = Arc::new(AtomicUsize::new(0));
+        let opened_times_cp = Arc::clone(&opened_times);
+        let handle = Handle::stub();
+        let id = handle.register_signal(Signal::Usr2).unwrap();
+        let mut reopen = crate::Reopen::with_handle(
+            handle,
+            Box::new(move || {
+                opened_times_cp.fetch_add(1, Ordering::Relaxed);
+                Ok(Fake)
+            }),
+        )
+        .unwrap();
+        let mut buf = [0];
+        assert_eq!(0, reopen.read(&mut buf).unwrap());
+        // SAFETY: plain FFI call, the signal has a handler registered.
+        unsafe { libc::kill(libc::getpid(), libc::SIGUSR2) };
+        // The same hack as above.
+        thread::sleep(Duration::from_secs(1));
+        assert_eq!(0, reopen.read(&mut buf).unwrap());
+        assert_eq!(2, opened_times.load(Ordering::Relaxed));
+        assert!(signal_hook::low_level::unregister(id));
+    }
 }
@@ -0,0 +1,134 @@
+//! Exporting the statistics through the [`metrics`](https://docs.rs/metrics) crate.
+//!
+//! Without the `metrics` feature, this is a no-op.
+
+#[cfg(feature = "metrics")]
+use std::cell::Cell;
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use metrics::{counter, gauge, histogram, Counter, Gauge, Histogram};
+
+#[cfg(feature = "metrics")]
+#[derive(Debug)]
+pub(crate) struct Stats {
+    reopens: Counter,
+    open_failures: Counter,
+    bytes_written: Counter,
+    open_duration: Histogram,
+    requests: Counter,
+    pending: Gauge,
+    /// A request was taken and the reopen didn't succeed yet.
+    is_pending: Cell<bool>,
+}
+
+#[cfg(feature = "metrics")]
+impl Stats {
+    /// Registers the counters with the current recorder.
+    pub(crate) fn new(label: Option<&str>) -> Self {
+        macro_rules! register {
+            ($kind: ident, $name: expr) => {
+                match label {
+                    Some(label) => $kind!($name, "label" => label.to_owned()),
+                    None => $kind!($name),
+                }
+            };
+            ($name: expr) => {
+                register!(counter, $name)
+            };
+        }
+        let pending = register!(gauge, "reopen.pending");
+        pending.set(0.0);
+        Stats {
+            reopens: register!("reopen.reopens_total"),
+            open_failures: register!("reopen.open_failures_total"),
+            bytes_written: register!("reopen.bytes_written_total"),
+            open_duration: register!(histogram, "reopen.open_duration_seconds"),
+            requests: register!("reopen.requests_total"),
+            pending,
+            is_pending: Cell::new(false),
+        }
+    }
+
+    /// A reopen request was taken.
+    ///
+    /// A request that is retried after a failed open is counted only once.
+    pub(crate) fn requested(&self) {
+        if !self.is_pending.replace(true) {
+            self.requests.increment(1);
+            self.pending.set(1.0);
+        }
+    }
+
+    pub(crate) fn reopened(&self) {
+        self.reopens.increment(1);
+        if self.is_pending.replace(false) {
+            self.pending.set(0.0);
+        }
+    }
+
+    pub(crate) fn open_failed(&self) {
+        self.open_failures.increment(1);
+    }
+
+    pub(crate) fn written(&self, bytes: usize) {
+        self.bytes_written.increment(bytes as u64);
+    }
+
+    pub(crate) fn opened_in(&self, duration: Duration) {
+        self.open_duration.record(duration);
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+#[derive(Debug)]
+pub(crate) struct Stats;
+
+#[cfg(not(feature = "metrics"))]
+impl Stats {
+    pub(crate) fn new(_label: Option<&str>) -> Self {
+        Stats
+    }
+
+    pub(crate) fn requested(&self) {}
+
+    pub(crate) fn reopened(&self) {}
+
+    pub(crate) fn open_failed(&self) {}
+
+    pub(crate) fn written(&self, _bytes: usize) {}
+
+    pub(crate) fn opened_in(&self, _duration: Duration) {}
+}
+
+/// Counts the bytes written through it.
+///
+/// Used to count the bytes of formatted output, where we don't know the size up front.
+pub(crate) struct Counting<'a, W> {
+    inner: &'a mut W,
+    pub(crate) written: usize,
+}
+
+impl<'a, W> Counting<'a, W> {
+    pub(crate) fn new(inner: &'a mut W) -> Self {
+        Counting { inner, written: 0 }
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for Counting<'_, W> {
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written;
+        Ok(written)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.inner.write_all(buf)?;
+        self.written += buf.len();
+        Ok(())
+    }
+}
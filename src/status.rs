@@ -0,0 +1,126 @@
+//! Reporting the status on `SIGINFO`.
+//!
+//! Only on the platforms that have `SIGINFO` (the BSDs and macOS), with the `signals` feature.
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{Error, Read};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use signal_hook::low_level::pipe;
+use signal_hook::SigId;
+
+use super::Handle;
+
+/// The counters behind a [`StatusSnapshot`], kept up to date by the [`Reopen`][crate::Reopen].
+///
+/// These are `usize`, as `AtomicU64` is too new for our MSRV.
+#[derive(Debug, Default)]
+pub(crate) struct Status {
+    generation: AtomicUsize,
+    written: AtomicUsize,
+}
+
+impl Status {
+    pub(crate) fn opened(&self, generation: u64) {
+        self.generation
+            .store(generation as usize, Ordering::Relaxed);
+        self.written.store(0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn written(&self, bytes: usize) {
+        self.written.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+/// The status of a [`Reopen`][crate::Reopen] at some point in time.
+///
+/// Handed to the callback of [`Handle::register_siginfo_status`]. The [`Display`] implementation
+/// formats it as a single line (without the line end).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct StatusSnapshot {
+    /// How many times the FD was reopened.
+    pub generation: u64,
+    /// Bytes written into the current FD since it was opened.
+    pub written: u64,
+    /// If a reopen is requested but didn't happen yet.
+    pub pending: bool,
+}
+
+impl Display for StatusSnapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "generation {}, {} bytes written since open, reopen {}",
+            self.generation,
+            self.written,
+            if self.pending { "pending" } else { "idle" }
+        )
+    }
+}
+
+impl Handle {
+    /// Reports the status on `SIGINFO` (usually sent by Ctrl+T in the terminal).
+    ///
+    /// Every time the signal comes, the `sink` is called with a [`StatusSnapshot`] of the
+    /// companion [`Reopen`][crate::Reopen]. The sink doesn't run inside the signal handler, but
+    /// in a helper thread woken up by it, so it may do anything (eg. write the snapshot to
+    /// stderr).
+    ///
+    /// The snapshot is read without locking, therefore it may be slightly out of date if the
+    /// [`Reopen`][crate::Reopen] is being used from another thread at the time.
+    ///
+    /// To stop the reporting, unregister the returned `SigId` (see
+    /// [`signal_hook::low_level::unregister`]). The helper thread then terminates. It also
+    /// terminates at the next signal after the [`Reopen`][crate::Reopen] and all its handles
+    /// are gone.
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `signals` feature enabled, on the platforms that have
+    /// `SIGINFO` (FreeBSD, NetBSD, OpenBSD, DragonFly and macOS).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let log = Reopen::append_to("/log/file")?;
+    /// log.handle().register_siginfo_status(|status| eprintln!("log: {}", status))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn register_siginfo_status<F>(&self, sink: F) -> Result<SigId, Error>
+    where
+        F: Fn(StatusSnapshot) + Send + Sync + 'static,
+    {
+        let (mut read, write) = UnixStream::pair()?;
+        let shared = Arc::downgrade(&self.0);
+        let id = pipe::register(libc::SIGINFO, write)?;
+        let spawned = thread::Builder::new()
+            .name("reopen-siginfo".to_owned())
+            .spawn(move || {
+                let mut buf = [0; 16];
+                // Ends on error or EOF, when the signal got unregistered.
+                while let Ok(1..=16) = read.read(&mut buf) {
+                    let shared = match shared.upgrade() {
+                        Some(shared) => shared,
+                        None => break,
+                    };
+                    sink(StatusSnapshot {
+                        generation: shared.status.generation.load(Ordering::Relaxed) as u64,
+                        written: shared.status.written.load(Ordering::Relaxed) as u64,
+                        pending: shared.requested(),
+                    });
+                }
+            });
+        if let Err(e) = spawned {
+            signal_hook::low_level::unregister(id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+}
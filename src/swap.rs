@@ -0,0 +1,119 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::io::Error;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use arc_swap::ArcSwap;
+
+use super::{Handle, Reopenable, Shared};
+
+/// A read-mostly variant of [`Reopen`][crate::Reopen], based on [`ArcSwap`].
+///
+/// Instead of proxying the IO operations, this hands out the current instance in an [`Arc`]
+/// (see [`load`][SwapReopen::load]). Loading it is lock-free and shared between threads, only the
+/// reopening itself is serialized. This is useful when many threads need access to something
+/// reopenable (eg. a configuration parsed from a file) and reopens are rare.
+///
+/// As the instances are shared, they can be used only through shared references, therefore this
+/// is of use mostly with things that need just `&self` (or have interior mutability).
+///
+/// # Staleness
+///
+/// The reopen happens on the first [`load`][SwapReopen::load] after it has been requested. Other
+/// threads may still be holding the old instance at that time (or even load it a little while
+/// longer, until they notice the change), therefore the old one may be used for a short while
+/// after the reopen. It is dropped once the last [`Arc`] holding it goes away.
+///
+/// # Features
+///
+/// This is available only with the `arc-swap` feature enabled.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::Error;
+/// # use reopen::SwapReopen;
+/// # fn main() -> Result<(), Error> {
+/// let config = SwapReopen::new(Box::new(|| Ok(String::from("Some config"))))?;
+/// assert_eq!("Some config", config.load()?.as_str());
+/// # Ok(()) }
+/// ```
+pub struct SwapReopen<FD> {
+    shared: Arc<Shared>,
+    constructor: Mutex<Box<dyn Fn() -> Result<FD, Error> + Send>>,
+    current: ArcSwap<FD>,
+}
+
+impl<FD> SwapReopen<FD> {
+    /// Creates a new instance.
+    pub fn new(constructor: Box<dyn Fn() -> Result<FD, Error> + Send>) -> Result<Self, Error> {
+        Self::with_handle(Handle::stub(), constructor)
+    }
+
+    /// Creates a new instance from the given handle.
+    ///
+    /// See [`Reopen::with_handle`][crate::Reopen::with_handle].
+    pub fn with_handle(
+        handle: Handle,
+        constructor: Box<dyn Fn() -> Result<FD, Error> + Send>,
+    ) -> Result<Self, Error> {
+        let fd = constructor()?;
+        Ok(Self {
+            shared: handle.0,
+            constructor: Mutex::new(constructor),
+            current: ArcSwap::from_pointee(fd),
+        })
+    }
+
+    /// Returns a handle to signal this `SwapReopen` to perform the reopening.
+    pub fn handle(&self) -> Handle {
+        Handle(Arc::clone(&self.shared))
+    }
+
+    /// Provides the current instance, reopening it first if requested.
+    ///
+    /// # Errors
+    ///
+    /// If the reopening fails, the error is returned. The old instance stays in place and another
+    /// attempt to reopen is made on the next call.
+    pub fn load(&self) -> Result<Arc<FD>, Error> {
+        if self.shared.requested() {
+            // Only one thread reopens at a time, the others wait for it to finish.
+            let constructor = self
+                .constructor
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            // Someone else might have done the reopen while we were waiting for the lock.
+            if self.shared.take_request() {
+                match constructor() {
+                    Ok(fd) => self.current.store(Arc::new(fd)),
+                    Err(e) => {
+                        self.shared.restore_request();
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        Ok(self.current.load_full())
+    }
+}
+
+impl<FD> Reopenable for SwapReopen<FD> {
+    fn handle(&self) -> Handle {
+        SwapReopen::handle(self)
+    }
+
+    fn reopen_now(&mut self) -> Result<(), Error> {
+        self.shared.request();
+        self.load().map(|_| ())
+    }
+}
+
+impl<FD: Debug> Debug for SwapReopen<FD> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("SwapReopen")
+            .field("pending", &self.shared.requested())
+            .field("current", &self.current)
+            .field("constructor", &"...")
+            .finish()
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt::{self, Debug, Formatter, Result as FmtResult};
+use std::io::{Error, Read, Write};
+#[cfg(vectored)]
+use std::io::{IoSlice, IoSliceMut};
+#[cfg(not(feature = "parking_lot"))]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+#[cfg(feature = "parking_lot")]
+use parking_lot::{Mutex, MutexGuard};
+
+use super::{Handle, Reopen, Reopenable};
+
+/// A [`Reopen`] usable through a shared reference.
+///
+/// Some APIs provide only `&self` but still need to write somewhere (for example the
+/// `log::Log::log` method). This wraps the [`Reopen`] into a mutex and implements [`Read`] and
+/// [`Write`] for `&SyncReopen`. Each operation locks the mutex for its whole duration, so
+/// operations from different threads don't interleave (eg. one `write_all` is not mixed with
+/// another).
+///
+/// Unlike the [`Reopen`] itself, this is [`Sync`] (provided the `FD` is [`Send`]), so it can live
+/// in a `static` or be shared between threads by a reference.
+///
+/// With the `parking_lot` feature, the mutex from the
+/// [`parking_lot`](https://docs.rs/parking_lot) crate is used internally instead of the one from
+/// the standard library. The API is the same in both cases.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::{Error, Write};
+/// # use reopen::{Reopen, SyncReopen};
+/// # fn main() -> Result<(), Error> {
+/// let writer = SyncReopen::new(Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?);
+/// // Note: no `mut` needed
+/// write!(&writer, "Hello")?;
+/// # Ok(()) }
+/// ```
+pub struct SyncReopen<FD> {
+    inner: Mutex<Reopen<FD>>,
+}
+
+impl<FD> SyncReopen<FD> {
+    /// Wraps the [`Reopen`].
+    pub fn new(reopen: Reopen<FD>) -> Self {
+        Self {
+            inner: Mutex::new(reopen),
+        }
+    }
+
+    /// Returns a handle to signal the inner [`Reopen`] to perform the reopening.
+    pub fn handle(&self) -> Handle {
+        self.lock().handle()
+    }
+
+    /// Unwraps the inner [`Reopen`].
+    #[cfg(not(feature = "parking_lot"))]
+    pub fn into_inner(self) -> Reopen<FD> {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Unwraps the inner [`Reopen`].
+    #[cfg(feature = "parking_lot")]
+    pub fn into_inner(self) -> Reopen<FD> {
+        self.inner.into_inner()
+    }
+
+    // A panic in the middle of an operation doesn't leave the Reopen in an inconsistent state (at
+    // worst the FD has half of some data written), so it's fine to ignore poisoning.
+    #[cfg(not(feature = "parking_lot"))]
+    fn lock(&self) -> MutexGuard<'_, Reopen<FD>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[cfg(feature = "parking_lot")]
+    fn lock(&self) -> MutexGuard<'_, Reopen<FD>> {
+        self.inner.lock()
+    }
+}
+
+impl<FD> Reopenable for SyncReopen<FD> {
+    fn handle(&self) -> Handle {
+        SyncReopen::handle(self)
+    }
+
+    fn reopen_now(&mut self) -> Result<(), Error> {
+        self.lock().reopen_now()
+    }
+}
+
+impl<FD: Debug> Debug for SyncReopen<FD> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("SyncReopen")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<FD: Read> Read for &SyncReopen<FD> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.lock().read(buf)
+    }
+
+    #[cfg(vectored)]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize, Error> {
+        self.lock().read_vectored(bufs)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Error> {
+        self.lock().read_exact(buf)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize, Error> {
+        self.lock().read_to_end(buf)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> Result<usize, Error> {
+        self.lock().read_to_string(buf)
+    }
+}
+
+impl<FD: Write> Write for &SyncReopen<FD> {
+    fn flush(&mut self) -> Result<(), Error> {
+        self.lock().flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.lock().write(buf)
+    }
+
+    #[cfg(vectored)]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize, Error> {
+        self.lock().write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Error> {
+        self.lock().write_all(buf)
+    }
+
+    fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> Result<(), Error> {
+        self.lock().write_fmt(fmt)
+    }
+}
@@ -0,0 +1,127 @@
+//! The systemd reload protocol.
+
+use std::env;
+use std::io::{Error, ErrorKind, Read};
+use std::os::unix::net::{UnixDatagram, UnixStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+use signal_hook::low_level::pipe;
+use signal_hook::SigId;
+
+use super::{Handle, ReopenEvent};
+
+/// The current `CLOCK_MONOTONIC` time, in microseconds, as systemd wants it.
+// std offers no way to get the absolute value of the clock.
+#[allow(unsafe_code)]
+fn monotonic_usec() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: the timespec is valid for writing. This clock can't fail.
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    ts.tv_sec as u64 * 1_000_000 + ts.tv_nsec as u64 / 1_000
+}
+
+/// Sends a message to the service manager, if there's one.
+fn notify(socket: &Option<PathBuf>, msg: &str) {
+    if let Some(socket) = socket {
+        // Nobody to report the error to, and systemd not hearing from us is not fatal for the
+        // application.
+        let _ = UnixDatagram::unbound().and_then(|sock| sock.send_to(msg.as_bytes(), socket));
+    }
+}
+
+impl Handle {
+    /// Reopens on `SIGHUP`, following the systemd reload protocol.
+    ///
+    /// When the signal comes, this tells the service manager the service is reloading
+    /// (`RELOADING=1`) and requests a reopen. Once the [`Reopen`][crate::Reopen] successfully
+    /// opens the new FD, it reports the service is ready again (`READY=1`). This makes
+    /// `systemctl reload` (with `ExecReload=kill -HUP $MAINPID` or `Type=notify-reload`) wait
+    /// for the logs to be actually reopened.
+    ///
+    /// The messages are sent to the socket in the `NOTIFY_SOCKET` environment variable, read
+    /// when this is called. If it is not set (the service doesn't run under systemd), only the
+    /// reopen is requested.
+    ///
+    /// The notifications are sent from a helper thread woken up by the signal. Note that the
+    /// reopen happens on the next operation of the [`Reopen`][crate::Reopen], therefore the
+    /// reload is not finished until it is used. Further signals that come before that are
+    /// handled after the reload finishes.
+    ///
+    /// To stop the reloading, unregister the returned `SigId` (see
+    /// [`signal_hook::low_level::unregister`]). The helper thread then terminates. It also
+    /// terminates after the [`Reopen`][crate::Reopen] and all its handles are gone.
+    ///
+    /// # Errors
+    ///
+    /// If the signal can't be registered or the helper thread started. The abstract socket
+    /// namespace (`NOTIFY_SOCKET` starting with `@`) is not supported.
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `sd-notify` feature enabled, on unix.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let log = Reopen::append_to("/log/file")?;
+    /// log.handle().register_systemd_reload()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn register_systemd_reload(&self) -> Result<SigId, Error> {
+        let socket = env::var_os("NOTIFY_SOCKET").map(PathBuf::from);
+        if let Some(socket) = socket.as_ref() {
+            if socket.to_string_lossy().starts_with('@') {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Abstract NOTIFY_SOCKET is not supported",
+                ));
+            }
+        }
+        let (mut read, write) = UnixStream::pair()?;
+        let shared = Arc::downgrade(&self.0);
+        let id = pipe::register(libc::SIGHUP, write)?;
+        let spawned = thread::Builder::new()
+            .name("reopen-systemd".to_owned())
+            .spawn(move || {
+                let mut buf = [0; 16];
+                // Ends on error or EOF, when the signal got unregistered.
+                while let Ok(1..=16) = read.read(&mut buf) {
+                    let events = match shared.upgrade() {
+                        Some(shared) => {
+                            // Subscribe before requesting, not to miss the reopen. The READY
+                            // is sent only after the RELOADING anyway, so request first and the
+                            // reload is already pending once systemd hears about it.
+                            let events = shared.events.subscribe();
+                            shared.request();
+                            let msg = format!("RELOADING=1\nMONOTONIC_USEC={}", monotonic_usec());
+                            notify(&socket, &msg);
+                            events
+                        }
+                        None => break,
+                    };
+                    // Not holding the shared while waiting, the channel closes once it's gone.
+                    loop {
+                        match events.recv() {
+                            Ok(ReopenEvent::OpenSucceeded { .. }) => break,
+                            Ok(_) => (),
+                            Err(_) => return,
+                        }
+                    }
+                    notify(&socket, "READY=1");
+                }
+            });
+        if let Err(e) = spawned {
+            signal_hook::low_level::unregister(id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+}
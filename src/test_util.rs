@@ -0,0 +1,298 @@
+//! Helpers for testing code that uses [`Reopen`][crate::Reopen].
+//!
+//! These are mock FDs and constructors, so tests can check what got written where and how the
+//! code behaves when reopening fails, without touching the filesystem.
+//!
+//! The constructors are handed out by `constructor` methods and can be wrapped into each other.
+//! All the copies of the same helper share their state.
+//!
+//! # Features
+//!
+//! This is available only with the `test-util` feature enabled.
+//!
+//! # Examples
+//!
+//! ```rust
+//! # use std::io::{Error, Write};
+//! # use reopen::Reopen;
+//! # use reopen::test_util::{CountingConstructor, FailN, HistorySink};
+//! # fn main() -> Result<(), Error> {
+//! let sink = HistorySink::new();
+//! // The first reopen fails, and we count all the attempts.
+//! let counting = CountingConstructor::new(FailN::new(1, sink.constructor()).constructor());
+//! let mut writer = Reopen::lazy(Box::new(counting.constructor()));
+//! assert!(writer.write_all(b"hello").is_err());
+//! writer.write_all(b"hello")?;
+//! assert_eq!(2, counting.opens());
+//! assert_eq!(vec![b"hello".to_vec()], sink.history());
+//! # Ok(()) }
+//! ```
+
+use std::io::{Error, ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::Handle;
+
+type History = Arc<Mutex<Vec<Vec<u8>>>>;
+
+/// Records the bytes written into each incarnation of the FD.
+///
+/// Each [`open`][HistorySink::open] starts a new incarnation, the returned [`HistoryWriter`]
+/// writes into it.
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::{Error, Write};
+/// # use reopen::Reopen;
+/// # use reopen::test_util::HistorySink;
+/// # fn main() -> Result<(), Error> {
+/// let sink = HistorySink::new();
+/// let mut writer = Reopen::new(Box::new(sink.constructor()))?;
+/// writer.write_all(b"hello")?;
+/// writer.handle().reopen();
+/// writer.write_all(b"world")?;
+/// assert_eq!(vec![b"hello".to_vec(), b"world".to_vec()], sink.history());
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HistorySink {
+    history: History,
+}
+
+impl HistorySink {
+    /// Creates an empty sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new incarnation.
+    ///
+    /// This never fails, the [`Result`] is there to fit a constructor.
+    pub fn open(&self) -> Result<HistoryWriter, Error> {
+        let mut history = self.history.lock().unwrap_or_else(PoisonError::into_inner);
+        history.push(Vec::new());
+        Ok(HistoryWriter {
+            history: Arc::clone(&self.history),
+            index: history.len() - 1,
+        })
+    }
+
+    /// A constructor calling [`open`][HistorySink::open].
+    pub fn constructor(&self) -> impl Fn() -> Result<HistoryWriter, Error> + Send + Sync + 'static {
+        let sink = self.clone();
+        move || sink.open()
+    }
+
+    /// A copy of all the data, each incarnation separately, the oldest first.
+    pub fn history(&self) -> Vec<Vec<u8>> {
+        self.history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// How many times it was opened.
+    pub fn incarnations(&self) -> usize {
+        self.history
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+}
+
+/// A single incarnation of the [`HistorySink`].
+///
+/// The writes always succeed. Old incarnations still write into their own part of the history.
+#[derive(Debug)]
+pub struct HistoryWriter {
+    history: History,
+    index: usize,
+}
+
+impl Write for HistoryWriter {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.history.lock().unwrap_or_else(PoisonError::into_inner)[self.index].write(buf)
+    }
+}
+
+/// A constructor wrapper failing the first few opens.
+///
+/// The failures have the [`ErrorKind::NotFound`] kind, as if the file wasn't there yet. After
+/// that, the wrapped constructor is called.
+#[derive(Debug)]
+pub struct FailN<F> {
+    remaining: Arc<AtomicUsize>,
+    constructor: Arc<F>,
+}
+
+impl<F> FailN<F> {
+    /// Wraps the constructor so the first `failures` opens fail.
+    pub fn new(failures: usize, constructor: F) -> Self {
+        FailN {
+            remaining: Arc::new(AtomicUsize::new(failures)),
+            constructor: Arc::new(constructor),
+        }
+    }
+
+    /// How many failures are still to come.
+    pub fn remaining(&self) -> usize {
+        self.remaining.load(Ordering::Relaxed)
+    }
+
+    /// Fails or calls the wrapped constructor.
+    pub fn open<FD>(&self) -> Result<FD, Error>
+    where
+        F: Fn() -> Result<FD, Error>,
+    {
+        let fail = self
+            .remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |r| r.checked_sub(1))
+            .is_ok();
+        if fail {
+            Err(Error::new(ErrorKind::NotFound, "Failing on purpose"))
+        } else {
+            (self.constructor)()
+        }
+    }
+
+    /// A constructor calling [`open`][FailN::open].
+    pub fn constructor<FD>(&self) -> impl Fn() -> Result<FD, Error> + Send + Sync + 'static
+    where
+        F: Fn() -> Result<FD, Error> + Send + Sync + 'static,
+    {
+        let fail = self.clone();
+        move || fail.open()
+    }
+}
+
+impl<F> Clone for FailN<F> {
+    fn clone(&self) -> Self {
+        FailN {
+            remaining: Arc::clone(&self.remaining),
+            constructor: Arc::clone(&self.constructor),
+        }
+    }
+}
+
+/// A constructor wrapper counting how many times it was called.
+///
+/// Both the successful and failed opens are counted.
+#[derive(Debug)]
+pub struct CountingConstructor<F> {
+    opens: Arc<AtomicUsize>,
+    constructor: Arc<F>,
+}
+
+impl<F> CountingConstructor<F> {
+    /// Wraps the constructor.
+    pub fn new(constructor: F) -> Self {
+        CountingConstructor {
+            opens: Arc::new(AtomicUsize::new(0)),
+            constructor: Arc::new(constructor),
+        }
+    }
+
+    /// How many times it was called.
+    pub fn opens(&self) -> usize {
+        self.opens.load(Ordering::Relaxed)
+    }
+
+    /// Counts and calls the wrapped constructor.
+    pub fn open<FD>(&self) -> Result<FD, Error>
+    where
+        F: Fn() -> Result<FD, Error>,
+    {
+        self.opens.fetch_add(1, Ordering::Relaxed);
+        (self.constructor)()
+    }
+
+    /// A constructor calling [`open`][CountingConstructor::open].
+    pub fn constructor<FD>(&self) -> impl Fn() -> Result<FD, Error> + Send + Sync + 'static
+    where
+        F: Fn() -> Result<FD, Error> + Send + Sync + 'static,
+    {
+        let counting = self.clone();
+        move || counting.open()
+    }
+}
+
+impl<F> Clone for CountingConstructor<F> {
+    fn clone(&self) -> Self {
+        CountingConstructor {
+            opens: Arc::clone(&self.opens),
+            constructor: Arc::clone(&self.constructor),
+        }
+    }
+}
+
+/// An FD requesting a reopen after each read or write.
+///
+/// This allows checking that a reopen doesn't happen in the middle of something, like a
+/// `write_all` or a [`lock`][crate::Reopen::lock].
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::io::{Error, Write};
+/// # use reopen::{Handle, Reopen};
+/// # use reopen::test_util::{HistorySink, ReopenAfterEachOp};
+/// # fn main() -> Result<(), Error> {
+/// let sink = HistorySink::new();
+/// let handle = Handle::stub();
+/// let mut writer = Reopen::with_handle(handle.clone(), Box::new({
+///     let sink = sink.clone();
+///     move || Ok(ReopenAfterEachOp::new(handle.clone(), sink.open()?))
+/// }))?;
+/// writer.write_all(b"hello")?;
+/// writer.write_all(b"world")?;
+/// assert_eq!(2, sink.incarnations());
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct ReopenAfterEachOp<FD> {
+    handle: Handle,
+    fd: FD,
+}
+
+impl<FD> ReopenAfterEachOp<FD> {
+    /// Wraps the FD, requesting reopens through the handle.
+    pub fn new(handle: Handle, fd: FD) -> Self {
+        ReopenAfterEachOp { handle, fd }
+    }
+
+    /// The wrapped FD.
+    pub fn get_ref(&self) -> &FD {
+        &self.fd
+    }
+
+    /// The wrapped FD.
+    pub fn get_mut(&mut self) -> &mut FD {
+        &mut self.fd
+    }
+}
+
+impl<FD: Read> Read for ReopenAfterEachOp<FD> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let result = self.fd.read(buf);
+        self.handle.reopen();
+        result
+    }
+}
+
+impl<FD: Write> Write for ReopenAfterEachOp<FD> {
+    fn flush(&mut self) -> Result<(), Error> {
+        self.fd.flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let result = self.fd.write(buf);
+        self.handle.reopen();
+        result
+    }
+}
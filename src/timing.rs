@@ -0,0 +1,40 @@
+//! When the pending reopens are applied, relative to the operations.
+
+/// When a requested reopen takes effect, see [`check_timing`][crate::Reopen::check_timing].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CheckTiming {
+    /// The request is checked before each operation.
+    ///
+    /// The first operation after the request already goes to the new FD. This is the default.
+    Before,
+
+    /// The request is checked after the operations, once the current FD is done.
+    ///
+    /// The current FD is used for as long as it has something to offer, the new one only after
+    /// that. What "done" means differs for reads and writes:
+    ///
+    /// * A write (or flush) goes to the current FD and the reopen happens right after it. So the
+    ///   first write after the request still goes to the old FD and the second one to the new
+    ///   FD.
+    /// * A read switches only once a read from the current FD produces no data. That is, it
+    ///   reaches the end, there's nothing ready on a non-blocking FD
+    ///   ([`WouldBlock`][std::io::ErrorKind::WouldBlock]) or it fails. In the first two cases,
+    ///   the read is repeated on the new FD, so the caller doesn't see the end of the old one.
+    ///   Errors are returned as they are. Reads that return some data never switch.
+    ///
+    /// If the reopen fails, the result of the operation is still returned and the reopen stays
+    /// pending, the next operation tries again and returns the error if that fails too.
+    ///
+    /// Only reading and writing through the [`Reopen`][crate::Reopen] itself (and the wrappers
+    /// built on top of it) switches, the other ways to access the FD (like
+    /// [`lock`][crate::Reopen::lock]) leave the request pending.
+    After,
+}
+
+// #[default] on enum variants is too new for our MSRV.
+#[allow(clippy::derivable_impls)]
+impl Default for CheckTiming {
+    fn default() -> Self {
+        CheckTiming::Before
+    }
+}
@@ -0,0 +1,118 @@
+//! Writing into anonymous files that get their name only once complete.
+//!
+//! # Features
+//!
+//! This is available only with the `tmpfile` feature enabled, on Linux.
+
+use std::ffi::CString;
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use super::Reopen;
+
+/// Opens an anonymous file (`O_TMPFILE`) in the given directory.
+///
+/// The file has no name, nobody else can see it and it disappears when closed, unless it is
+/// given a name by [`link`].
+///
+/// # Errors
+///
+/// Besides the usual errors, not all filesystems support anonymous files (it fails with
+/// `EOPNOTSUPP` then).
+///
+/// # Features
+///
+/// This is available only with the `tmpfile` feature enabled, on Linux.
+pub fn open(dir: &Path) -> Result<File, Error> {
+    OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_TMPFILE)
+        .mode(0o644)
+        .open(dir)
+}
+
+/// Gives a name to an anonymous file opened by [`open`].
+///
+/// The file appears at the path at once, with all the data written into it so far. The path
+/// needs to be on the same filesystem as the file and must not exist yet.
+///
+/// This goes through `/proc/self/fd`, so it needs the `/proc` filesystem mounted, but no special
+/// privileges.
+///
+/// # Features
+///
+/// This is available only with the `tmpfile` feature enabled, on Linux.
+#[allow(unsafe_code)]
+pub fn link(file: &File, path: &Path) -> Result<(), Error> {
+    let invalid = |e| Error::new(ErrorKind::InvalidInput, e);
+    let source = CString::new(format!("/proc/self/fd/{}", file.as_raw_fd())).map_err(invalid)?;
+    let target = CString::new(path.as_os_str().as_bytes()).map_err(invalid)?;
+    // SAFETY: both paths are zero-terminated strings.
+    let result = unsafe {
+        libc::linkat(
+            libc::AT_FDCWD,
+            source.as_ptr(),
+            libc::AT_FDCWD,
+            target.as_ptr(),
+            libc::AT_SYMLINK_FOLLOW,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+/// Creates a [`Reopen`] writing into anonymous files, named only when complete.
+///
+/// Each incarnation is an anonymous file in the given directory (see [`open`]). When it is
+/// closed, on reopen or when the [`Reopen`] is dropped, its data are synced to the disk and it
+/// is [linked][link] into the directory under the name returned by `name` (eg. with a counter or
+/// a timestamp). Therefore, the files show up only complete, nobody ever sees a partial one.
+///
+/// The file is opened on the first use (see [`lazy`][Reopen::lazy]).
+///
+/// Errors from the closing are not reported anywhere (there's no operation to return them
+/// from). If the link fails (eg. because the name is already taken), the data of that file are
+/// lost. If the process crashes, so are the data of the current file.
+///
+/// # Features
+///
+/// This is available only with the `tmpfile` feature enabled, on Linux.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use std::io::Write;
+/// # use std::sync::atomic::{AtomicUsize, Ordering};
+/// let counter = AtomicUsize::new(0);
+/// let mut audit = reopen::tmpfile::linked_on_close("/log/audit", move || {
+///     format!("audit.{}", counter.fetch_add(1, Ordering::Relaxed))
+/// });
+/// writeln!(audit, "Something happened").unwrap();
+/// audit.handle().reopen();
+/// // The audit.0 file appears now, complete, and this goes into the next one.
+/// writeln!(audit, "More").unwrap();
+/// ```
+pub fn linked_on_close<D, F, P>(dir: D, name: F) -> Reopen<File>
+where
+    D: Into<PathBuf>,
+    F: Fn() -> P + Send + 'static,
+    P: AsRef<Path>,
+{
+    let dir = dir.into();
+    let mut reopen = Reopen::lazy(Box::new({
+        let dir = dir.clone();
+        move || open(&dir)
+    }));
+    reopen.close_hook = Some(Box::new(move |file: &mut File| {
+        file.sync_data()?;
+        link(file, &dir.join(name()))
+    }));
+    reopen
+}
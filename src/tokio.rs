@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use super::Handle;
+
+impl Handle {
+    /// Spawns a task reopening each time the [`Notify`] is notified.
+    ///
+    /// This allows an async control plane already using a [`Notify`] to drive the reopens
+    /// directly. The task holds only a weak reference to the [`Reopen`][crate::Reopen], it
+    /// terminates once the [`Reopen`][crate::Reopen] and all its handles are gone (this is
+    /// noticed at the next notification). It can also be stopped by aborting the returned
+    /// [`JoinHandle`].
+    ///
+    /// # Panics
+    ///
+    /// If called outside of a tokio runtime.
+    ///
+    /// # Features
+    ///
+    /// This is available only with the `tokio` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use std::io::Error;
+    /// # use std::sync::Arc;
+    /// # use reopen::Reopen;
+    /// # use tokio::sync::Notify;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Error> {
+    /// let writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))?;
+    /// let notify = Arc::new(Notify::new());
+    /// writer.handle().spawn_notified(Arc::clone(&notify));
+    /// // Somewhere in the control plane
+    /// notify.notify_one();
+    /// # Ok(()) }
+    /// ```
+    pub fn spawn_notified(&self, notify: Arc<Notify>) -> JoinHandle<()> {
+        let shared = Arc::downgrade(&self.0);
+        tokio::spawn(async move {
+            loop {
+                notify.notified().await;
+                match shared.upgrade() {
+                    Some(shared) => shared.request(),
+                    None => break,
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,199 @@
+//! Instrumentation of the reopen lifecycle through the [`tracing`](https://docs.rs/tracing) and
+//! [`log`](https://docs.rs/log) crates.
+//!
+//! Without the `tracing` and `log` features, this is a no-op.
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+use std::cell::Cell;
+use std::io::Error;
+#[cfg(any(feature = "fern", feature = "env_logger"))]
+use std::io::Write;
+use std::time::Duration;
+
+#[cfg(feature = "tracing")]
+use tracing::{debug, debug_span, Span};
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+thread_local! {
+    // The const initializer is too new for our MSRV.
+    #[allow(clippy::missing_const_for_thread_local)]
+    /// This thread is inside the subscriber or logger.
+    static EMITTING: Cell<bool> = Cell::new(false);
+}
+
+/// Restores the flag even if the subscriber panics.
+#[cfg(any(feature = "tracing", feature = "log"))]
+struct Emitting<'a> {
+    flag: &'a Cell<bool>,
+    previous: bool,
+}
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+impl Drop for Emitting<'_> {
+    fn drop(&mut self) {
+        self.flag.set(self.previous);
+    }
+}
+
+/// Runs the closure with our own events suppressed on this thread.
+///
+/// Used by the loggers writing through a [`Reopen`][crate::Reopen]. An event emitted while such
+/// logger holds its [`Reopen`][crate::Reopen] would get back to it and deadlock on the lock.
+#[cfg(all(
+    feature = "log",
+    any(feature = "logger", feature = "fern", feature = "env_logger")
+))]
+pub(crate) fn muted<R, F: FnOnce() -> R>(f: F) -> R {
+    EMITTING.with(|flag| {
+        let _restore = Emitting {
+            flag,
+            previous: flag.replace(true),
+        };
+        f()
+    })
+}
+
+/// A writer for the loggers of other crates, muting our events around each operation.
+#[cfg(all(feature = "log", any(feature = "fern", feature = "env_logger")))]
+struct Muted<W>(W);
+
+#[cfg(all(feature = "log", any(feature = "fern", feature = "env_logger")))]
+impl<W: Write> Write for Muted<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let inner = &mut self.0;
+        muted(|| inner.write(buf))
+    }
+    fn flush(&mut self) -> Result<(), Error> {
+        let inner = &mut self.0;
+        muted(|| inner.flush())
+    }
+}
+
+/// Boxes the writer for a logger of another crate (see [`muted`]).
+#[cfg(all(feature = "log", any(feature = "fern", feature = "env_logger")))]
+pub(crate) fn logger_output<W: Write + Send + 'static>(writer: W) -> Box<dyn Write + Send> {
+    Box::new(Muted(writer))
+}
+
+/// Boxes the writer for a logger of another crate.
+#[cfg(all(not(feature = "log"), any(feature = "fern", feature = "env_logger")))]
+pub(crate) fn logger_output<W: Write + Send + 'static>(writer: W) -> Box<dyn Write + Send> {
+    Box::new(writer)
+}
+
+/// Calls into the subscriber, unless this thread is already inside it because of us.
+///
+/// If the subscriber writes through a [`Reopen`][crate::Reopen] and that one emits something,
+/// we would get called again from within the subscriber, possibly forever.
+#[cfg(any(feature = "tracing", feature = "log"))]
+fn guarded<R, F: FnOnce() -> R>(f: F) -> Option<R> {
+    EMITTING
+        .try_with(|flag| {
+            if flag.get() {
+                return None;
+            }
+            let _restore = Emitting {
+                flag,
+                previous: flag.replace(true),
+            };
+            Some(f())
+        })
+        .ok()
+        .and_then(|result| result)
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn requested(label: Option<&str>, source: &'static str) {
+    #[cfg(feature = "tracing")]
+    guarded(|| debug!(target: "reopen", label, source, "reopen requested"));
+}
+
+/// The span around a call of the constructor.
+#[cfg(feature = "tracing")]
+pub(crate) fn open_span(label: Option<&str>) -> Span {
+    guarded(|| debug_span!(target: "reopen", "open", label)).unwrap_or_else(Span::none)
+}
+
+#[cfg_attr(
+    not(all(feature = "tracing", feature = "log")),
+    allow(unused_variables)
+)]
+pub(crate) fn open_finished<FD>(
+    label: Option<&str>,
+    result: &Result<FD, Error>,
+    duration: Duration,
+) {
+    #[cfg(feature = "tracing")]
+    guarded(|| match result {
+        Ok(_) => debug!(target: "reopen", ?duration, "open succeeded"),
+        Err(e) => debug!(target: "reopen", ?duration, error = %e, "open failed"),
+    });
+    #[cfg(feature = "log")]
+    {
+        if let Err(e) = result {
+            guarded(|| log::warn!(target: "reopen", "{}: Open failed: {}", name(label), e));
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "log"), allow(unused_variables))]
+pub(crate) fn reopened(label: Option<&str>, generation: u64) {
+    #[cfg(feature = "log")]
+    guarded(|| {
+        log::debug!(
+            target: "reopen",
+            "{}: Reopened, generation {}",
+            name(label),
+            generation
+        )
+    });
+}
+
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn closed(label: Option<&str>, generation: u64) {
+    #[cfg(feature = "tracing")]
+    guarded(|| debug!(target: "reopen", label, generation, "old FD closed"));
+}
+
+#[cfg_attr(
+    not(all(feature = "tracing", feature = "log")),
+    allow(unused_variables)
+)]
+pub(crate) fn flush_timed_out(label: Option<&str>, timeout: Duration) {
+    #[cfg(feature = "tracing")]
+    guarded(|| tracing::warn!(target: "reopen", label, ?timeout, "flush of old FD timed out"));
+    #[cfg(feature = "log")]
+    guarded(|| {
+        log::warn!(
+            target: "reopen",
+            "{}: Flush of the old FD didn't finish in {:?}, proceeding with the reopen",
+            name(label),
+            timeout
+        )
+    });
+}
+
+#[cfg(feature = "log")]
+fn name(label: Option<&str>) -> &str {
+    label.unwrap_or("Reopen")
+}
+
+/// Stands in for the span when there's no tracing.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+/// Stands in for the entered span.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Entered;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub(crate) fn enter(&self) -> Entered {
+        Entered
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn open_span(_label: Option<&str>) -> Span {
+    Span
+}
@@ -0,0 +1,83 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::Reopen;
+
+/// State of watching for a trigger file.
+pub(crate) struct Trigger {
+    path: PathBuf,
+    interval: Duration,
+    /// When to look for the file next time.
+    next: Option<Instant>,
+}
+
+impl<FD> Reopen<FD> {
+    /// Reopens when a trigger file appears.
+    ///
+    /// Once in the given interval, an operation looks if a file exists at the given path. If it
+    /// does, the file is deleted and a reopen is requested, which then happens right away in the
+    /// same operation. Log rotation tools can therefore ask for a reopen by creating the file (eg.
+    /// `touch /log/file.reopen`).
+    ///
+    /// This needs neither signals nor helper threads, only the filesystem, therefore it works
+    /// on platforms without these (eg. WASI). The price is that every operation reads the
+    /// [clock][Reopen::with_clock] to find out if the interval is up.
+    ///
+    /// If the file exists but can't be deleted, no reopen is requested (otherwise it would
+    /// reopen in each interval forever).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::{Error, Write};
+    /// # use std::time::Duration;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// // On WASI, the directory needs to be preopened (eg. `wasmtime --dir /log`).
+    /// let mut log = Reopen::append_to("/log/file")?
+    ///     .with_trigger_file("/log/file.reopen", Duration::from_secs(1));
+    /// writeln!(log, "Hello")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn with_trigger_file<P: Into<PathBuf>>(mut self, path: P, interval: Duration) -> Self {
+        self.slow = true;
+        self.trigger = Some(Trigger {
+            path: path.into(),
+            interval,
+            next: None,
+        });
+        self
+    }
+
+    /// It's time to look for the trigger file.
+    #[inline]
+    pub(crate) fn trigger_due(&self, now: Instant) -> bool {
+        match self.trigger {
+            Some(Trigger {
+                next: Some(next), ..
+            }) => now >= next,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Looks for the trigger file if it's time to.
+    pub(crate) fn poll_trigger(&mut self) {
+        let now = self.clock.now();
+        let found = match self.trigger.as_mut() {
+            Some(trigger) => {
+                match trigger.next {
+                    Some(next) if now < next => return,
+                    _ => (),
+                }
+                trigger.next = Some(now + trigger.interval);
+                fs::remove_file(&trigger.path).is_ok()
+            }
+            None => return,
+        };
+        if found {
+            self.request_inside();
+        }
+    }
+}
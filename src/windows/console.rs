@@ -0,0 +1,160 @@
+use std::io::Error;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use windows_sys::Win32::Foundation::{BOOL, FALSE, TRUE};
+use windows_sys::Win32::System::Console::{
+    SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT,
+    CTRL_SHUTDOWN_EVENT,
+};
+
+use crate::Handle;
+
+/// A console control event.
+///
+/// See the documentation of `SetConsoleCtrlHandler` for when each of them comes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CtrlEvent {
+    /// `Ctrl+C`.
+    CtrlC,
+    /// `Ctrl+Break`.
+    CtrlBreak,
+    /// The console is being closed.
+    Close,
+    /// The user is logging off (received only by services).
+    Logoff,
+    /// The system is shutting down (received only by services).
+    Shutdown,
+}
+
+impl CtrlEvent {
+    fn raw(self) -> u32 {
+        match self {
+            CtrlEvent::CtrlC => CTRL_C_EVENT,
+            CtrlEvent::CtrlBreak => CTRL_BREAK_EVENT,
+            CtrlEvent::Close => CTRL_CLOSE_EVENT,
+            CtrlEvent::Logoff => CTRL_LOGOFF_EVENT,
+            CtrlEvent::Shutdown => CTRL_SHUTDOWN_EVENT,
+        }
+    }
+}
+
+struct Registration {
+    id: usize,
+    event: u32,
+    flag: Arc<AtomicBool>,
+}
+
+/// All the handles registered to some event.
+///
+/// Windows runs the handler in a new thread (not in an interrupted one, like unix signal
+/// handlers), so it is fine to lock in there.
+static REGISTRY: Mutex<Vec<Registration>> = Mutex::new(Vec::new());
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+extern "system" fn handler(event: u32) -> BOOL {
+    let registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut handled = FALSE;
+    for registration in registry.iter().filter(|r| r.event == event) {
+        registration.flag.store(true, Ordering::Release);
+        handled = TRUE;
+    }
+    handled
+}
+
+fn set_handler(add: bool) -> Result<(), Error> {
+    let add = if add { TRUE } else { FALSE };
+    // SAFETY: the handler is a valid function for the whole life of the program.
+    #[allow(unsafe_code)]
+    let result = unsafe { SetConsoleCtrlHandler(Some(handler), add) };
+    if result == FALSE {
+        Err(Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+impl Handle {
+    /// Installs a console control handler to invoke the reopening when the event comes.
+    ///
+    /// This is the windows counterpart of [`register_signal`][Handle::register_signal]. The
+    /// handler only sets the flag, the reopen happens on the next operation as usual.
+    ///
+    /// The event is considered handled by the process, therefore the next handler (eg. the
+    /// default one, terminating the process) is not called for it while any handle is registered
+    /// to it.
+    ///
+    /// The handler stays installed until the returned guard is dropped.
+    ///
+    /// # Features
+    ///
+    /// This is available only on windows with the `windows-console` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # use reopen::windows::CtrlEvent;
+    /// # fn main() -> Result<(), Error> {
+    /// let log = Reopen::append_to("log.txt")?;
+    /// let _guard = log.handle().register_console_ctrl(CtrlEvent::CtrlBreak)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn register_console_ctrl(&self, event: CtrlEvent) -> Result<CtrlGuard, Error> {
+        let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+        if registry.is_empty() {
+            set_handler(true)?;
+        }
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        registry.push(Registration {
+            id,
+            event: event.raw(),
+            flag: self.0.flag(),
+        });
+        Ok(CtrlGuard { id })
+    }
+}
+
+/// Keeps a console control handler registered.
+///
+/// See [`Handle::register_console_ctrl`]. Dropping it unregisters the handle.
+#[derive(Debug)]
+pub struct CtrlGuard {
+    id: usize,
+}
+
+impl Drop for CtrlGuard {
+    fn drop(&mut self) {
+        let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+        registry.retain(|r| r.id != self.id);
+        if registry.is_empty() {
+            // Nothing to do about the error in a destructor, the handler does nothing now anyway.
+            let _ = set_handler(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Both in one test, as they share the global registry.
+    #[test]
+    fn register_and_remove() {
+        let handle = Handle::stub();
+        let first = handle.register_console_ctrl(CtrlEvent::CtrlBreak).unwrap();
+        let second = handle.register_console_ctrl(CtrlEvent::Close).unwrap();
+        assert_eq!(2, REGISTRY.lock().unwrap().len());
+
+        assert_eq!(FALSE, handler(CTRL_C_EVENT));
+        assert!(!handle.0.requested());
+        assert_eq!(TRUE, handler(CTRL_BREAK_EVENT));
+        assert!(handle.0.requested());
+
+        drop(first);
+        drop(second);
+        assert!(REGISTRY.lock().unwrap().is_empty());
+    }
+}
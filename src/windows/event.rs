@@ -0,0 +1,107 @@
+use std::io::Error;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use windows_sys::Win32::Foundation::{FALSE, WAIT_OBJECT_0};
+use windows_sys::Win32::System::Threading::{
+    OpenEventW, WaitForMultipleObjects, EVENT_MODIFY_STATE, INFINITE,
+};
+
+use super::sys::{check, wide, Event};
+use crate::Handle;
+
+impl Event {
+    /// Opens an existing named event, for signalling it.
+    #[allow(unsafe_code)]
+    fn open(name: &str) -> Result<Self, Error> {
+        let name = wide(name);
+        // SAFETY: the name is zero-terminated and lives until the call returns.
+        check(unsafe { OpenEventW(EVENT_MODIFY_STATE, FALSE, name.as_ptr()) })
+    }
+}
+
+/// Signals a named event, to trigger the reopen in whatever process registered to it.
+///
+/// This is meant for the other side of [`Handle::register_named_event`], eg. a small tool
+/// called by the rotation script. The event must exist (some process must have registered to
+/// it).
+///
+/// # Features
+///
+/// This is available only on windows with the `windows-event` feature enabled.
+pub fn signal_event(name: &str) -> Result<(), Error> {
+    Event::open(name)?.set()
+}
+
+impl Handle {
+    /// Reopens each time a named event object is signalled.
+    ///
+    /// This creates (or opens, if it exists already) an auto-reset event object of the given
+    /// name. Another process can then trigger the reopen by signalling the event, for example
+    /// with [`signal_event`][crate::windows::signal_event] or `SetEvent`. This is the usual
+    /// windows replacement of sending a `SIGHUP`.
+    ///
+    /// A helper thread waits for the event, it is stopped when the returned guard is dropped.
+    ///
+    /// # Features
+    ///
+    /// This is available only on windows with the `windows-event` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let log = Reopen::append_to("log.txt")?;
+    /// let _guard = log.handle().register_named_event("Global\\my-service-reopen")?;
+    /// # Ok(()) }
+    /// ```
+    #[allow(unsafe_code)]
+    pub fn register_named_event(&self, name: &str) -> Result<EventGuard, Error> {
+        let event = Event::create(Some(name), false)?;
+        let stop = Arc::new(Event::create(None, true)?);
+        let handle = self.clone();
+        let thread = thread::Builder::new()
+            .name("reopen-event".to_owned())
+            .spawn({
+                let stop = Arc::clone(&stop);
+                move || loop {
+                    let handles = [stop.0, event.0];
+                    // SAFETY: both handles stay valid for the whole life of the thread.
+                    let result =
+                        unsafe { WaitForMultipleObjects(2, handles.as_ptr(), FALSE, INFINITE) };
+                    if result == WAIT_OBJECT_0 + 1 {
+                        handle.reopen();
+                    } else {
+                        // Either asked to stop or something went wrong.
+                        break;
+                    }
+                }
+            })?;
+        Ok(EventGuard {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Keeps a named event registered.
+///
+/// See [`Handle::register_named_event`]. Dropping it stops waiting for the event.
+#[derive(Debug)]
+pub struct EventGuard {
+    stop: Arc<Event>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for EventGuard {
+    fn drop(&mut self) {
+        if self.stop.set().is_ok() {
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+        // If we can't stop the thread, leave it be rather than hang.
+    }
+}
@@ -0,0 +1,34 @@
+//! Windows specific ways to trigger a reopen.
+//!
+//! Windows has no `SIGHUP` (and the `signals` feature is of little use there), so these offer
+//! the native alternatives.
+//!
+//! # Platform support
+//!
+//! This is available only on windows and each of the ways needs its feature: the console control
+//! handler the `windows-console` one, the named events `windows-event`, the named pipe
+//! `windows-pipe`, the service control handler `windows-service` and watching the path `watch`.
+
+#[cfg(feature = "windows-console")]
+mod console;
+#[cfg(feature = "windows-event")]
+mod event;
+#[cfg(feature = "windows-pipe")]
+mod pipe;
+#[cfg(feature = "windows-service")]
+mod service;
+#[cfg(any(feature = "windows-event", feature = "watch"))]
+mod sys;
+#[cfg(feature = "watch")]
+mod watch;
+
+#[cfg(feature = "windows-console")]
+pub use self::console::{CtrlEvent, CtrlGuard};
+#[cfg(feature = "windows-event")]
+pub use self::event::{signal_event, EventGuard};
+#[cfg(feature = "windows-pipe")]
+pub use self::pipe::PipeGuard;
+#[cfg(feature = "windows-service")]
+pub use self::service::service_control_handler;
+#[cfg(feature = "watch")]
+pub use self::watch::WatchGuard;
@@ -0,0 +1,167 @@
+use std::ffi::OsStr;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, Error};
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::{AsRawHandle, FromRawHandle};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use windows_sys::Win32::Foundation::{ERROR_PIPE_CONNECTED, FALSE, HANDLE, INVALID_HANDLE_VALUE};
+use windows_sys::Win32::Storage::FileSystem::PIPE_ACCESS_DUPLEX;
+use windows_sys::Win32::System::Pipes::{
+    ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_TYPE_BYTE,
+    PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+};
+
+use crate::control;
+use crate::{Handle, RealClock};
+
+/// How long to wait for the reopen before answering `timeout`.
+const ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Creates a new instance of the named pipe.
+#[allow(unsafe_code)]
+fn create(name: &[u16]) -> Result<File, Error> {
+    // SAFETY: the name is zero-terminated and lives until the call returns.
+    let pipe = unsafe {
+        CreateNamedPipeW(
+            name.as_ptr(),
+            PIPE_ACCESS_DUPLEX,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+            PIPE_UNLIMITED_INSTANCES,
+            512,
+            512,
+            0,
+            ptr::null(),
+        )
+    };
+    if pipe == INVALID_HANDLE_VALUE {
+        Err(Error::last_os_error())
+    } else {
+        // SAFETY: we own the freshly created handle.
+        Ok(unsafe { File::from_raw_handle(pipe as _) })
+    }
+}
+
+/// Waits for a client to connect to the pipe instance.
+#[allow(unsafe_code)]
+fn connect(pipe: &File) -> Result<(), Error> {
+    // SAFETY: the handle is valid for as long as the file lives.
+    if unsafe { ConnectNamedPipe(pipe.as_raw_handle() as HANDLE, ptr::null_mut()) } == FALSE {
+        let err = Error::last_os_error();
+        // The client came between creating the instance and waiting for it, that's fine.
+        if err.raw_os_error() != Some(ERROR_PIPE_CONNECTED as i32) {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+impl Handle {
+    /// Serves reopen commands on a named pipe.
+    ///
+    /// This creates a named pipe of the given name (eg. `\\.\pipe\myapp-logctl`) and serves
+    /// clients connecting to it in a helper thread, one at a time. The clients send textual
+    /// commands, one per line, and get one line answers:
+    ///
+    /// * `reopen`: Requests a reopen and waits for it to happen. Answers `ok` once the new FD is
+    ///   open, `failed <error kind>` if opening it failed or `timeout` if it didn't happen within
+    ///   10 seconds (the reopen happens on the next operation of the [`Reopen`][crate::Reopen],
+    ///   so an idle one doesn't reopen). This lets the rotation script wait for the reopen
+    ///   before eg. compressing the old file.
+    /// * `status`: Answers `pending` if a reopen was requested and didn't happen yet, `idle`
+    ///   otherwise.
+    ///
+    /// The helper thread stops when the returned guard is dropped.
+    ///
+    /// # Features
+    ///
+    /// This is available only on windows with the `windows-pipe` feature enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let log = Reopen::append_to("log.txt")?;
+    /// let _guard = log.handle().serve_named_pipe(r"\\.\pipe\myapp-logctl")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn serve_named_pipe(&self, name: &str) -> Result<PipeGuard, Error> {
+        let wide = OsStr::new(name)
+            .encode_wide()
+            .chain(Some(0))
+            .collect::<Vec<_>>();
+        // Create the first instance right away, so the errors are reported and the pipe exists
+        // once we return.
+        let first = create(&wide)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = self.clone();
+        let thread = thread::Builder::new()
+            .name("reopen-pipe".to_owned())
+            .spawn({
+                let stop = Arc::clone(&stop);
+                move || {
+                    let mut next = Some(first);
+                    loop {
+                        let pipe = match next.take() {
+                            Some(pipe) => pipe,
+                            None => match create(&wide) {
+                                Ok(pipe) => pipe,
+                                Err(_) => break,
+                            },
+                        };
+                        if connect(&pipe).is_err() || stop.load(Ordering::Acquire) {
+                            break;
+                        }
+                        // A misbehaving client is no reason to stop serving the others.
+                        let _ = control::serve(
+                            &handle,
+                            &RealClock,
+                            BufReader::new(&pipe),
+                            &pipe,
+                            ACK_TIMEOUT,
+                        );
+                    }
+                }
+            })?;
+        Ok(PipeGuard {
+            name: name.to_owned(),
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Keeps serving a named pipe.
+///
+/// See [`Handle::serve_named_pipe`]. Dropping it stops the serving. If a client is connected at
+/// that time, it waits for it to disconnect.
+#[derive(Debug)]
+pub struct PipeGuard {
+    name: String,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for PipeGuard {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        // Wake up the thread waiting for a client by connecting as one.
+        let woken = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&self.name)
+            .is_ok();
+        if woken {
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+        // If we can't wake it up, leave it be rather than hang.
+    }
+}
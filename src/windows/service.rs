@@ -0,0 +1,47 @@
+use windows_service::service::{ServiceControl, UserEventCode};
+use windows_service::service_control_handler::ServiceControlHandlerResult;
+
+use crate::Handle;
+
+/// Creates a service control handler that reopens on a user-defined control code.
+///
+/// A windows service can be sent custom control codes (128 to 255, eg. by `sc control`). This
+/// returns a handler for the [`windows-service`](https://docs.rs/windows-service) crate which
+/// requests the reopen when the given code comes. It answers `Interrogate` as required and
+/// reports any other control as not implemented.
+///
+/// Services handling more controls can call this handler from their own one (or simply call
+/// [`Handle::reopen`] in there).
+///
+/// # Features
+///
+/// This is available only on windows with the `windows-service` feature enabled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use reopen::Reopen;
+/// # use reopen::windows::service_control_handler;
+/// # use windows_service::service::UserEventCode;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let log = Reopen::append_to("log.txt")?;
+/// let code = UserEventCode::from_raw(130)?;
+/// let _status = windows_service::service_control_handler::register(
+///     "my-service",
+///     service_control_handler(log.handle(), code),
+/// )?;
+/// # Ok(()) }
+/// ```
+pub fn service_control_handler(
+    handle: Handle,
+    code: UserEventCode,
+) -> impl Fn(ServiceControl) -> ServiceControlHandlerResult + Send + 'static {
+    move |control| match control {
+        ServiceControl::UserEvent(received) if received == code => {
+            handle.reopen();
+            ServiceControlHandlerResult::NoError
+        }
+        ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+        _ => ServiceControlHandlerResult::NotImplemented,
+    }
+}
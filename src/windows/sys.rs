@@ -0,0 +1,63 @@
+//! Small wrappers around the windows API shared by the triggers.
+
+use std::ffi::OsStr;
+use std::io::Error;
+use std::os::windows::ffi::OsStrExt;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{CloseHandle, FALSE, HANDLE, TRUE};
+use windows_sys::Win32::System::Threading::{CreateEventW, SetEvent};
+
+/// An owned event object.
+#[derive(Debug)]
+pub(crate) struct Event(pub(crate) HANDLE);
+
+#[allow(unsafe_code)]
+// SAFETY: the event handles can be used from any thread.
+unsafe impl Send for Event {}
+#[allow(unsafe_code)]
+// SAFETY: the event handles can be used from any thread, the API synchronizes internally.
+unsafe impl Sync for Event {}
+
+/// Converts the name to the zero-terminated UTF-16 the API wants.
+pub(crate) fn wide(name: &str) -> Vec<u16> {
+    OsStr::new(name).encode_wide().chain(Some(0)).collect()
+}
+
+pub(crate) fn check(handle: HANDLE) -> Result<Event, Error> {
+    if handle.is_null() {
+        Err(Error::last_os_error())
+    } else {
+        Ok(Event(handle))
+    }
+}
+
+impl Event {
+    /// Creates a new event, or opens an existing one of the same name.
+    #[allow(unsafe_code)]
+    pub(crate) fn create(name: Option<&str>, manual_reset: bool) -> Result<Self, Error> {
+        let name = name.map(wide);
+        let name_ptr = name.as_ref().map_or(ptr::null(), |n| n.as_ptr());
+        let manual_reset = if manual_reset { TRUE } else { FALSE };
+        // SAFETY: the name is zero-terminated and lives until the call returns.
+        check(unsafe { CreateEventW(ptr::null(), manual_reset, FALSE, name_ptr) })
+    }
+
+    #[allow(unsafe_code)]
+    pub(crate) fn set(&self) -> Result<(), Error> {
+        // SAFETY: the handle is valid as long as we own it.
+        if unsafe { SetEvent(self.0) } == FALSE {
+            Err(Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Drop for Event {
+    #[allow(unsafe_code)]
+    fn drop(&mut self) {
+        // SAFETY: we own the handle and nobody uses it after this.
+        unsafe { CloseHandle(self.0) };
+    }
+}
@@ -0,0 +1,202 @@
+use std::ffi::{OsStr, OsString};
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind};
+use std::mem;
+use std::os::windows::ffi::OsStringExt;
+use std::os::windows::fs::OpenOptionsExt;
+use std::os::windows::io::AsRawHandle;
+use std::path::Path;
+use std::ptr;
+use std::slice;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use windows_sys::Win32::Foundation::{FALSE, HANDLE, TRUE, WAIT_OBJECT_0};
+use windows_sys::Win32::Storage::FileSystem::{
+    ReadDirectoryChangesW, FILE_ACTION_REMOVED, FILE_ACTION_RENAMED_OLD_NAME,
+    FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OVERLAPPED, FILE_LIST_DIRECTORY,
+    FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_INFORMATION, FILE_SHARE_DELETE, FILE_SHARE_READ,
+    FILE_SHARE_WRITE,
+};
+use windows_sys::Win32::System::Threading::{WaitForMultipleObjects, INFINITE};
+use windows_sys::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
+
+use super::sys::Event;
+use crate::Handle;
+
+/// Compares file names the way windows does (ignoring case).
+fn same_name(a: &OsStr, b: &OsStr) -> bool {
+    a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase()
+}
+
+/// Checks if any of the notifications in the buffer is about our file going away.
+///
+/// # Safety
+///
+/// The buffer must contain `len` bytes of valid notifications, as filled in by
+/// `ReadDirectoryChangesW`.
+#[allow(unsafe_code)]
+unsafe fn gone(buffer: &[u32], len: usize, name: &OsStr) -> bool {
+    let start = buffer.as_ptr() as *const u8;
+    let mut offset = 0;
+    while offset < len {
+        let info = start.add(offset) as *const FILE_NOTIFY_INFORMATION;
+        let action = ptr::addr_of!((*info).Action).read_unaligned();
+        let name_len = ptr::addr_of!((*info).FileNameLength).read_unaligned() as usize;
+        let name_ptr = ptr::addr_of!((*info).FileName) as *const u16;
+        let changed = OsString::from_wide(slice::from_raw_parts(name_ptr, name_len / 2));
+        let removed = action == FILE_ACTION_REMOVED || action == FILE_ACTION_RENAMED_OLD_NAME;
+        if removed && same_name(&changed, name) {
+            return true;
+        }
+        let next = ptr::addr_of!((*info).NextEntryOffset).read_unaligned() as usize;
+        if next == 0 {
+            break;
+        }
+        offset += next;
+    }
+    false
+}
+
+/// Watches the directory until asked to stop.
+#[allow(unsafe_code)]
+fn watch(dir: File, name: OsString, handle: Handle, stop: &Event) -> Result<(), Error> {
+    let dir_handle = dir.as_raw_handle() as HANDLE;
+    let io = Event::create(None, true)?;
+    // The notifications need to be aligned to 4 bytes.
+    let mut buffer = [0u32; 1024];
+    loop {
+        // SAFETY: zeroed OVERLAPPED is the documented initial state.
+        let mut overlapped: OVERLAPPED = unsafe { mem::zeroed() };
+        overlapped.hEvent = io.0;
+        // SAFETY: the buffer and the overlapped outlive the operation, we always wait for it to
+        // finish (or cancel it and wait) before touching them again.
+        let started = unsafe {
+            ReadDirectoryChangesW(
+                dir_handle,
+                buffer.as_mut_ptr() as *mut _,
+                mem::size_of_val(&buffer) as u32,
+                FALSE,
+                FILE_NOTIFY_CHANGE_FILE_NAME,
+                ptr::null_mut(),
+                &mut overlapped,
+                None,
+            )
+        };
+        if started == FALSE {
+            return Err(Error::last_os_error());
+        }
+        let handles = [stop.0, io.0];
+        // SAFETY: both handles stay valid for the whole call.
+        let woken = unsafe { WaitForMultipleObjects(2, handles.as_ptr(), FALSE, INFINITE) };
+        let mut len = 0;
+        if woken != WAIT_OBJECT_0 + 1 {
+            // Either asked to stop or something went wrong. The buffer must not go away while
+            // the operation is still running.
+            // SAFETY: the handle and the overlapped are valid.
+            unsafe {
+                CancelIoEx(dir_handle, &overlapped);
+                GetOverlappedResult(dir_handle, &overlapped, &mut len, TRUE);
+            }
+            return Ok(());
+        }
+        // SAFETY: the operation is done, as its event got signalled.
+        if unsafe { GetOverlappedResult(dir_handle, &overlapped, &mut len, FALSE) } == FALSE {
+            return Err(Error::last_os_error());
+        }
+        // Zero length means there were too many changes to fit, so we don't know what
+        // happened. Better reopen needlessly than miss it. Otherwise, a batch of notifications
+        // (eg. a rename reported several times) requests only one reopen.
+        // SAFETY: the buffer was filled with that many bytes of notifications.
+        if len == 0 || unsafe { gone(&buffer, len as usize, &name) } {
+            handle.reopen();
+        }
+    }
+}
+
+impl Handle {
+    /// Reopens when the file at the given path is renamed or deleted.
+    ///
+    /// This watches the directory of the file for changes (by `ReadDirectoryChangesW`) in a
+    /// helper thread. Whenever the file is renamed away or deleted (usually by a log rotation
+    /// tool), a reopen is requested, so the next operation of the [`Reopen`][crate::Reopen]
+    /// opens a fresh file at the original path. This needs no cooperation from the rotation
+    /// tool. The file needs to be opened in a way that allows renaming it (as
+    /// [`Reopen::append_to`][crate::Reopen::append_to] does).
+    ///
+    /// Several notifications about the file in a quick succession request only one reopen. The
+    /// names are compared ignoring case, as windows does.
+    ///
+    /// The helper thread is stopped when the returned guard is dropped.
+    ///
+    /// # Errors
+    ///
+    /// If the path has no file name or its directory can't be opened for watching.
+    ///
+    /// # Platform support
+    ///
+    /// This is available with the `watch` feature on windows, macOS and the BSDs (there it
+    /// watches the file by `kqueue` instead).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use std::io::Error;
+    /// # use reopen::Reopen;
+    /// # fn main() -> Result<(), Error> {
+    /// let log = Reopen::append_to("C:\\logs\\app.log")?;
+    /// let _guard = log.handle().watch_path("C:\\logs\\app.log")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn watch_path<P: AsRef<Path>>(&self, path: P) -> Result<WatchGuard, Error> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "The path has no file name"))?
+            .to_owned();
+        let dir = match path.parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        let dir = OpenOptions::new()
+            .access_mode(FILE_LIST_DIRECTORY)
+            .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OVERLAPPED)
+            .open(dir)?;
+        let stop = Arc::new(Event::create(None, true)?);
+        let handle = self.clone();
+        let thread = thread::Builder::new()
+            .name("reopen-watch".to_owned())
+            .spawn({
+                let stop = Arc::clone(&stop);
+                move || {
+                    // Nobody to report the error to, the watching just ends.
+                    let _ = watch(dir, name, handle, &stop);
+                }
+            })?;
+        Ok(WatchGuard {
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Keeps watching a path.
+///
+/// See [`Handle::watch_path`]. Dropping it stops the watching.
+#[derive(Debug)]
+pub struct WatchGuard {
+    stop: Arc<Event>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        if self.stop.set().is_ok() {
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+        // If we can't stop the thread, leave it be rather than hang.
+    }
+}
@@ -0,0 +1,163 @@
+//! Tests of running all the opens in the opener thread.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use reopen::{Reopen, Reopenable};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// Writes until the condition holds.
+fn write_until<F: Fn() -> bool>(writer: &mut Reopen<Vec<u8>>, cond: F) {
+    let start = Instant::now();
+    while !cond() {
+        assert!(start.elapsed() < Duration::from_secs(5), "Never happened");
+        writer.write_all(b"Hello").unwrap();
+        thread::sleep(ms(10));
+    }
+}
+
+/// A constructor stuck forever doesn't hang the operations nor the drop.
+#[test]
+fn stuck_constructor() {
+    let (_keep, stuck) = mpsc::channel::<()>();
+    let stuck = Mutex::new(stuck);
+    let opens = AtomicUsize::new(0);
+    let mut writer = Reopen::new(Box::new(move || {
+        if opens.fetch_add(1, Ordering::Relaxed) > 0 {
+            // Never receives anything, the sender lives until the end of the test.
+            let _ = stuck.lock().unwrap().recv();
+        }
+        Ok(Vec::new())
+    }))
+    .unwrap()
+    .background_open(true, ms(50));
+
+    let start = Instant::now();
+    writer.handle().reopen();
+    writer.write_all(b"Hello").unwrap();
+    writer.write_all(b"Hello").unwrap();
+    drop(writer);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+/// All the opens happen in the same helper thread.
+#[test]
+fn single_thread() {
+    let threads: Arc<Mutex<Vec<ThreadId>>> = Arc::new(Mutex::new(Vec::new()));
+    let mut writer = Reopen::new(Box::new({
+        let threads = Arc::clone(&threads);
+        move || {
+            threads.lock().unwrap().push(thread::current().id());
+            Ok(Vec::new())
+        }
+    }))
+    .unwrap()
+    .background_open(true, Duration::from_secs(10));
+
+    for opens in 2..4 {
+        writer.handle().reopen();
+        write_until(&mut writer, || threads.lock().unwrap().len() == opens);
+    }
+
+    let threads = threads.lock().unwrap();
+    // The first one is done by new, before turning this on.
+    assert_eq!(thread::current().id(), threads[0]);
+    assert_ne!(thread::current().id(), threads[1]);
+    assert_eq!(threads[1], threads[2]);
+}
+
+/// A panicking constructor is reported as a failed open and the next one works.
+#[test]
+fn panic_recovery() {
+    let opens = Arc::new(AtomicUsize::new(0));
+    let mut writer = Reopen::new(Box::new({
+        let opens = Arc::clone(&opens);
+        move || {
+            if opens.fetch_add(1, Ordering::Relaxed) == 1 {
+                panic!("Constructor panic");
+            }
+            Ok(Vec::new())
+        }
+    }))
+    .unwrap()
+    .background_open(true, Duration::from_secs(10));
+
+    writer.handle().reopen();
+    // The request is retried after the panic.
+    write_until(&mut writer, || opens.load(Ordering::Relaxed) == 3);
+}
+
+/// Further requests while an open is in progress don't start more opens.
+#[test]
+fn merged_requests() {
+    let opens = Arc::new(AtomicUsize::new(0));
+    let mut writer = Reopen::new(Box::new({
+        let opens = Arc::clone(&opens);
+        move || {
+            if opens.fetch_add(1, Ordering::Relaxed) > 0 {
+                thread::sleep(ms(300));
+            }
+            Ok(Vec::new())
+        }
+    }))
+    .unwrap()
+    .background_open(true, Duration::from_secs(10));
+
+    let handle = writer.handle();
+    for _ in 0..5 {
+        handle.reopen();
+        // Uses the old FD without waiting.
+        let start = Instant::now();
+        writer.write_all(b"Hello").unwrap();
+        assert!(start.elapsed() < ms(200));
+    }
+    write_until(&mut writer, || opens.load(Ordering::Relaxed) >= 2);
+    thread::sleep(ms(50));
+    writer.write_all(b"Hello").unwrap();
+    assert_eq!(2, opens.load(Ordering::Relaxed));
+}
+
+/// Without an old FD, a stuck constructor makes the operations time out, but not hang.
+#[test]
+fn stuck_without_fd() {
+    let (_keep, stuck) = mpsc::channel::<()>();
+    let stuck = Mutex::new(stuck);
+    let mut writer = Reopen::<Vec<u8>>::lazy(Box::new(move || {
+        let _ = stuck.lock().unwrap().recv();
+        Ok(Vec::new())
+    }))
+    .background_open(true, ms(50));
+
+    let start = Instant::now();
+    let err = writer.write_all(b"Hello").unwrap_err();
+    assert_eq!(ErrorKind::TimedOut, err.kind());
+    // These would call the constructor directly otherwise, waiting for the stuck one.
+    let err = writer.block_until_openable(ms(200), ms(10)).unwrap_err();
+    assert_eq!(ErrorKind::TimedOut, err.kind());
+    let err = writer.reopen_now().unwrap_err();
+    assert_eq!(ErrorKind::TimedOut, err.kind());
+    drop(writer);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+/// Errors of the constructor get through to the operation.
+#[test]
+fn error_reported() {
+    let mut writer = Reopen::<Vec<u8>>::lazy(Box::new(|| {
+        Err(Error::new(ErrorKind::NotFound, "Not there"))
+    }))
+    .background_open(true, Duration::from_secs(10));
+
+    let err = writer.write_all(b"Hello").unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+    let err = writer.block_until_openable(ms(50), ms(10)).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+    assert_eq!(Some("Not there".to_owned()), writer.health().last_error);
+}
@@ -0,0 +1,76 @@
+//! Tests of formatting into a buffer.
+
+use std::io::{Error, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use reopen::test_util::{HistorySink, HistoryWriter, ReopenAfterEachOp};
+use reopen::{Handle, Reopen};
+
+/// Counts the write calls.
+struct Counting {
+    inner: HistoryWriter,
+    writes: Arc<AtomicUsize>,
+}
+
+impl Write for Counting {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.writes.fetch_add(1, Ordering::Relaxed);
+        self.inner.write(buf)
+    }
+}
+
+/// Writes a formatted line and returns the output and the number of writes.
+fn format(buffered: bool) -> (Vec<Vec<u8>>, usize) {
+    let sink = HistorySink::new();
+    let writes = Arc::new(AtomicUsize::new(0));
+    let mut writer = Reopen::new(Box::new({
+        let sink = sink.clone();
+        let writes = Arc::clone(&writes);
+        move || {
+            Ok(Counting {
+                inner: sink.open()?,
+                writes: Arc::clone(&writes),
+            })
+        }
+    }))
+    .unwrap()
+    .buffer_fmt(buffered);
+    let name = "world";
+    writeln!(writer, "Hello {} and {:?}", name, 42).unwrap();
+    writeln!(writer, "Again").unwrap();
+    (sink.history(), writes.load(Ordering::Relaxed))
+}
+
+#[test]
+fn same_output() {
+    let (plain, plain_writes) = format(false);
+    let (buffered, buffered_writes) = format(true);
+    assert_eq!(plain, buffered);
+    assert_eq!(vec![b"Hello world and 42\nAgain\n".to_vec()], buffered);
+    assert_eq!(2, buffered_writes);
+    assert!(plain_writes > buffered_writes);
+}
+
+/// A reopen requested in the middle of formatting waits for the next operation.
+#[test]
+fn reopen_checked_once() {
+    let sink = HistorySink::new();
+    let handle = Handle::stub();
+    let mut writer = Reopen::with_handle(
+        handle.clone(),
+        Box::new({
+            let sink = sink.clone();
+            move || Ok(ReopenAfterEachOp::new(handle.clone(), sink.open()?))
+        }),
+    )
+    .unwrap()
+    .buffer_fmt(true);
+    write!(writer, "{}-{}", 1, 2).unwrap();
+    write!(writer, "{}-{}", 3, 4).unwrap();
+    assert_eq!(vec![b"1-2".to_vec(), b"3-4".to_vec()], sink.history());
+}
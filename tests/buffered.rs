@@ -0,0 +1,130 @@
+//! Tests of the `BufferedReopen`.
+
+use std::io::{BufWriter, Error, Write};
+use std::sync::{Arc, Mutex};
+
+use reopen::test_util::HistorySink;
+use reopen::{BufferedReopen, Reopen};
+
+/// The trap: an outer `BufWriter` writes the old data into the new FD.
+#[test]
+fn naive_bufwriter_misplaces() {
+    let sink = HistorySink::new();
+    let reopen = Reopen::new(Box::new(sink.constructor())).unwrap();
+    let handle = reopen.handle();
+    let mut writer = BufWriter::new(reopen);
+    writer.write_all(b"old").unwrap();
+    handle.reopen();
+    writer.write_all(b"new").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(vec![Vec::new(), b"oldnew".to_vec()], sink.history());
+}
+
+#[test]
+fn flushed_before_reopen() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .wrap_buffered(1024);
+    writer.write_all(b"old").unwrap();
+    assert_eq!(b"old", writer.buffered());
+    assert_eq!(vec![Vec::<u8>::new()], sink.history());
+    writer.handle().reopen();
+    writer.write_all(b"new").unwrap();
+    // Reopened, but the new data are still in the buffer.
+    assert_eq!(vec![b"old".to_vec(), Vec::new()], sink.history());
+    writer.flush().unwrap();
+    assert_eq!(vec![b"old".to_vec(), b"new".to_vec()], sink.history());
+}
+
+/// The pending data are written on drop, to the old FD if a reopen is pending.
+#[test]
+fn drop_before_reopen() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .wrap_buffered(1024);
+    writer.write_all(b"data").unwrap();
+    writer.handle().reopen();
+    drop(writer);
+    assert_eq!(vec![b"data".to_vec()], sink.history());
+}
+
+#[test]
+fn overflow() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .wrap_buffered(4);
+    writer.write_all(b"abc").unwrap();
+    writer.write_all(b"de").unwrap();
+    assert_eq!(b"de", writer.buffered());
+    assert_eq!(vec![b"abc".to_vec()], sink.history());
+    // Too large to buffer at all
+    writer.write_all(b"fghij").unwrap();
+    assert!(writer.buffered().is_empty());
+    assert_eq!(vec![b"abcdefghij".to_vec()], sink.history());
+}
+
+/// A disk with limited space.
+#[derive(Debug)]
+struct Disk {
+    data: Arc<Mutex<Vec<u8>>>,
+    space: usize,
+}
+
+impl Write for Disk {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let mut data = self.data.lock().unwrap();
+        let len = buf.len().min(self.space - data.len());
+        if len == 0 {
+            return Err(Error::from_raw_os_error(libc::ENOSPC));
+        }
+        data.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+fn provide_disk(space: usize, capacity: usize) -> (BufferedReopen<Disk>, Arc<Mutex<Vec<u8>>>) {
+    let data = Arc::new(Mutex::new(Vec::new()));
+    let writer = Reopen::new(Box::new({
+        let data = Arc::clone(&data);
+        move || {
+            Ok(Disk {
+                data: Arc::clone(&data),
+                space,
+            })
+        }
+    }))
+    .unwrap()
+    .wrap_buffered(capacity);
+    (writer, data)
+}
+
+#[test]
+fn drain_after_failed_flush() {
+    let (mut writer, data) = provide_disk(0, 1024);
+    writer.write_all(b"data").unwrap();
+    assert!(writer.flush().is_err());
+    assert_eq!(b"data", writer.buffered());
+    assert_eq!(b"data", &writer.drain_buffered()[..]);
+    assert!(writer.buffered().is_empty());
+    assert!(data.lock().unwrap().is_empty());
+    // Nothing left to write.
+    writer.flush().unwrap();
+}
+
+/// Only the part that didn't fit is drained.
+#[test]
+fn drain_after_partial_flush() {
+    let (mut writer, data) = provide_disk(5, 8);
+    writer.write_all(b"abcdef").unwrap();
+    // Doesn't fit into the buffer, so it's flushed first. That fails half-way.
+    assert!(writer.write_all(b"ghi").is_err());
+    assert_eq!(b"abcde", &data.lock().unwrap()[..]);
+    assert_eq!(b"f", &writer.drain_buffered()[..]);
+}
@@ -0,0 +1,71 @@
+//! Tests of checking for reopen requests only on some operations.
+
+use std::io::{Error, ErrorKind, Write};
+use std::num::NonZeroUsize;
+
+use reopen::test_util::{HistorySink, HistoryWriter};
+use reopen::{Handle, Reopen};
+
+fn provide_writer(n: usize) -> (Reopen<HistoryWriter>, Handle, HistorySink) {
+    let sink = HistorySink::new();
+    let writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .check_every(NonZeroUsize::new(n).unwrap());
+    let handle = writer.handle();
+    (writer, handle, sink)
+}
+
+/// However the request aligns with the checks, it takes effect within 8 writes.
+#[test]
+fn reopen_within_n() {
+    for offset in 0..8 {
+        let (mut writer, handle, sink) = provide_writer(8);
+        for _ in 0..offset {
+            writer.write_all(b"a").unwrap();
+        }
+        handle.reopen();
+        let mut writes = 0;
+        while sink.incarnations() == 1 {
+            writes += 1;
+            assert!(writes <= 8, "Not reopened within 8 writes");
+            writer.write_all(b"b").unwrap();
+        }
+    }
+}
+
+/// The first operation checks.
+#[test]
+fn first_checks() {
+    let (mut writer, handle, sink) = provide_writer(8);
+    handle.reopen();
+    writer.write_all(b"a").unwrap();
+    assert_eq!(vec![Vec::new(), b"a".to_vec()], sink.history());
+}
+
+struct Failing;
+
+impl Write for Failing {
+    fn write(&mut self, _: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(ErrorKind::BrokenPipe, "Broken"))
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The operation after an error checks.
+#[test]
+fn error_checks() {
+    let mut writer = Reopen::new(Box::new(|| Ok(Failing)))
+        .unwrap()
+        .check_every(NonZeroUsize::new(100).unwrap())
+        .with_max_reopens(0);
+    let handle = writer.handle();
+    writer.flush().unwrap();
+    assert!(writer.write_all(b"a").is_err());
+    handle.reopen();
+    // Would not check for the next 98 operations otherwise.
+    let err = writer.flush().unwrap_err();
+    assert_eq!("reopen limit exceeded", err.to_string());
+}
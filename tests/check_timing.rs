@@ -0,0 +1,164 @@
+//! Tests of doing the requested reopens before or after the operations.
+
+use std::io::{Cursor, Error, ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use reopen::test_util::{HistorySink, HistoryWriter};
+use reopen::{CheckTiming, Reopen};
+
+/// The opens fail while the returned flag is set.
+fn provide_writer(timing: CheckTiming) -> (Reopen<HistoryWriter>, HistorySink, Arc<AtomicBool>) {
+    let sink = HistorySink::new();
+    let fail = Arc::new(AtomicBool::new(false));
+    let writer = Reopen::new(Box::new({
+        let sink = sink.clone();
+        let fail = Arc::clone(&fail);
+        move || {
+            if fail.load(Ordering::Relaxed) {
+                return Err(Error::new(ErrorKind::NotFound, "Not there"));
+            }
+            sink.open()
+        }
+    }))
+    .unwrap()
+    .check_timing(timing);
+    (writer, sink, fail)
+}
+
+/// Each incarnation contains its sequence number.
+fn provide_reader(timing: CheckTiming) -> Reopen<Cursor<Vec<u8>>> {
+    let opened = AtomicUsize::new(0);
+    Reopen::new(Box::new(move || {
+        let n = opened.fetch_add(1, Ordering::Relaxed);
+        Ok(Cursor::new(format!("file {}", n).into_bytes()))
+    }))
+    .unwrap()
+    .check_timing(timing)
+}
+
+#[test]
+fn write_before() {
+    let (mut writer, sink, _) = provide_writer(CheckTiming::Before);
+    writer.write_all(b"a").unwrap();
+    writer.handle().reopen();
+    writer.write_all(b"b").unwrap();
+    writer.write_all(b"c").unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"bc".to_vec()], sink.history());
+}
+
+/// The write right after the request still goes to the old FD.
+#[test]
+fn write_after() {
+    let (mut writer, sink, _) = provide_writer(CheckTiming::After);
+    writer.write_all(b"a").unwrap();
+    writer.handle().reopen();
+    writer.write_all(b"b").unwrap();
+    assert!(!writer.is_pending());
+    writer.write_all(b"c").unwrap();
+    assert_eq!(vec![b"ab".to_vec(), b"c".to_vec()], sink.history());
+}
+
+/// The write succeeded, so it's not reported as failed just because the reopen after it did.
+#[test]
+fn write_after_failed_reopen() {
+    let (mut writer, sink, fail) = provide_writer(CheckTiming::After);
+    writer.handle().reopen();
+    fail.store(true, Ordering::Relaxed);
+    writer.write_all(b"a").unwrap();
+    assert!(writer.is_pending());
+    assert!(writer.write_all(b"b").is_err());
+    fail.store(false, Ordering::Relaxed);
+    writer.write_all(b"c").unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"c".to_vec()], sink.history());
+}
+
+#[test]
+fn read_before() {
+    let mut reader = provide_reader(CheckTiming::Before);
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf).unwrap();
+    reader.handle().reopen();
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!("file 1", rest);
+}
+
+/// The old FD is read to the end first, then the reading continues in the new one.
+#[test]
+fn read_after() {
+    let mut reader = provide_reader(CheckTiming::After);
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf).unwrap();
+    reader.handle().reopen();
+    let read = reader.read(&mut buf).unwrap();
+    assert_eq!(b" 0", &buf[..read]);
+    assert!(reader.is_pending());
+    // The end of the old one is not seen, the read goes to the new one right away.
+    let read = reader.read(&mut buf).unwrap();
+    assert_eq!(b"file", &buf[..read]);
+    assert!(!reader.is_pending());
+}
+
+#[test]
+fn read_to_string_after() {
+    let mut reader = provide_reader(CheckTiming::After);
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf).unwrap();
+    reader.handle().reopen();
+    let mut rest = String::new();
+    assert_eq!(8, reader.read_to_string(&mut rest).unwrap());
+    assert_eq!(" 0file 1", rest);
+}
+
+#[test]
+fn read_exact_across_after() {
+    let mut reader = provide_reader(CheckTiming::After);
+    let mut buf = [0; 4];
+    reader.read_exact(&mut buf).unwrap();
+    reader.handle().reopen();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(b" 0fi", &buf);
+}
+
+/// Without a request, the end is reported as usual.
+#[test]
+fn end_without_request_after() {
+    let mut reader = provide_reader(CheckTiming::After);
+    let mut all = String::new();
+    reader.read_to_string(&mut all).unwrap();
+    assert_eq!("file 0", all);
+    let mut buf = [0; 4];
+    assert_eq!(0, reader.read(&mut buf).unwrap());
+}
+
+/// A non-blocking reader with some data ready.
+#[derive(Debug)]
+struct Ready(Cursor<&'static [u8]>);
+
+impl Read for Ready {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        match self.0.read(buf)? {
+            0 => Err(Error::new(ErrorKind::WouldBlock, "Nothing ready")),
+            read => Ok(read),
+        }
+    }
+}
+
+/// Nothing being ready on the old FD counts as drained.
+#[test]
+fn would_block_after() {
+    let mut reader = Reopen::with_fd(
+        Ready(Cursor::new(b"old")),
+        Box::new(|| Ok(Ready(Cursor::new(b"new")))),
+    )
+    .check_timing(CheckTiming::After);
+    reader.handle().reopen();
+    let mut buf = [0; 8];
+    let read = reader.read(&mut buf).unwrap();
+    assert_eq!(b"old", &buf[..read]);
+    let read = reader.read(&mut buf).unwrap();
+    assert_eq!(b"new", &buf[..read]);
+    let err = reader.read(&mut buf).unwrap_err();
+    assert_eq!(ErrorKind::WouldBlock, err.kind());
+}
@@ -0,0 +1,67 @@
+//! Tests of the checksum footer.
+
+use std::io::{Error, ErrorKind, Write};
+
+use reopen::test_util::HistorySink;
+use reopen::{ChecksumWriter, Reopen};
+
+#[test]
+fn footer_on_each_file() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::with_checksum_footer(Box::new(sink.constructor())).unwrap();
+    writer.write_all(b"12345").unwrap();
+    writer.write_all(b"6789").unwrap();
+    writer.handle().reopen();
+    writeln!(writer, "Hello").unwrap();
+    writeln!(writer, "world").unwrap();
+    drop(writer);
+
+    let history = sink.history();
+    assert_eq!(2, history.len());
+    // The well-known check value of CRC-32.
+    assert_eq!(
+        "123456789# lines=0 bytes=9 crc32=cbf43926\n",
+        String::from_utf8_lossy(&history[0])
+    );
+    assert_eq!(
+        "Hello\nworld\n# lines=2 bytes=12 crc32=dcf48607\n",
+        String::from_utf8_lossy(&history[1])
+    );
+}
+
+#[test]
+fn footer_once() {
+    let mut writer = ChecksumWriter::new(Vec::new());
+    writer.write_all(b"").unwrap();
+    assert_eq!(0, writer.crc32());
+    writer.finish().unwrap();
+    writer.finish().unwrap();
+    assert_eq!(b"# lines=0 bytes=0 crc32=00000000\n", &writer.get_ref()[..]);
+}
+
+/// Accepts only a few bytes at a time.
+struct Short(Vec<u8>);
+
+impl Write for Short {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if self.0.len() >= 6 {
+            return Err(Error::new(ErrorKind::WriteZero, "Full"));
+        }
+        let len = buf.len().min(4);
+        self.0.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Only the bytes really written are covered.
+#[test]
+fn partial_writes() {
+    let mut writer = ChecksumWriter::new(Short(Vec::new()));
+    writer.write_all(b"123456789").unwrap_err();
+    assert_eq!(8, writer.bytes());
+    assert_eq!(b"12345678", &writer.get_ref().0[..]);
+}
@@ -0,0 +1,167 @@
+//! Tests of writing into a child process.
+#![cfg(unix)]
+
+use std::fs;
+use std::io::{Error, ErrorKind, Write};
+use std::process::{Command, Stdio};
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reopen::process::{piped, piped_with_grace};
+use reopen::{ChildWriter, Clock, ManualClock, Reopen};
+use tempfile::TempDir;
+
+#[test]
+fn respawn_on_broken_pipe() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("out");
+    let mut writer = Reopen::spawn_child({
+        let path = path.clone();
+        move || {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg("exec cat >> \"$0\"").arg(&path);
+            command
+        }
+    })
+    .unwrap();
+    writer.write_all(b"first\n").unwrap();
+    // Don't kill it before it got the data.
+    let start = Instant::now();
+    while fs::read_to_string(&path).unwrap_or_default() != "first\n" {
+        assert!(start.elapsed() < Duration::from_secs(5), "Nothing written");
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let first = writer.lock().unwrap().child().id();
+    {
+        let child = writer.lock().unwrap().child_mut();
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+    let err = writer.write_all(b"lost\n").unwrap_err();
+    assert_eq!(ErrorKind::BrokenPipe, err.kind());
+    assert!(writer.is_pending());
+
+    writer.write_all(b"second\n").unwrap();
+    assert_ne!(first, writer.lock().unwrap().child().id());
+    // Reaps the child, after it wrote everything.
+    drop(writer);
+    assert_eq!("first\nsecond\n", fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn reopen_replaces_child() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("out");
+    let mut writer = Reopen::spawn_child({
+        let path = path.clone();
+        move || {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg("exec cat >> \"$0\"").arg(&path);
+            command
+        }
+    })
+    .unwrap();
+    writer.write_all(b"first\n").unwrap();
+    writer.handle().reopen();
+    writer.write_all(b"second\n").unwrap();
+    drop(writer);
+    assert_eq!("first\nsecond\n", fs::read_to_string(&path).unwrap());
+}
+
+/// The replaced children are waited for, not left as zombies.
+#[test]
+fn no_zombies() {
+    let mut writer = Reopen::from_fn(piped(|| {
+        let mut command = Command::new("cat");
+        command.stdout(Stdio::null());
+        command
+    }))
+    .unwrap()
+    .reopen_on_broken_pipe(true);
+    let mut pids = Vec::new();
+    for _ in 0..5 {
+        writer.write_all(b"data\n").unwrap();
+        pids.push(writer.lock().unwrap().child().id());
+        writer.handle().reopen();
+    }
+    drop(writer);
+    for pid in pids {
+        // Someone already waited for it, so there's nothing to wait for.
+        let result = unsafe { libc::waitpid(pid as libc::pid_t, ptr::null_mut(), libc::WNOHANG) };
+        assert_eq!(-1, result);
+        assert_eq!(Some(libc::ECHILD), Error::last_os_error().raw_os_error());
+    }
+}
+
+fn reaped(pid: u32) -> bool {
+    let result = unsafe { libc::waitpid(pid as libc::pid_t, ptr::null_mut(), libc::WNOHANG) };
+    result == -1 && Error::last_os_error().raw_os_error() == Some(libc::ECHILD)
+}
+
+/// A child that doesn't exit on its own is killed before the new one starts.
+///
+/// The grace period is measured by the manual clock, so it's over without actually waiting.
+#[test]
+fn kill_stubborn() {
+    let grace = Duration::from_secs(3600);
+    let clock = Arc::new(ManualClock::new());
+    let previous = Arc::new(Mutex::new(None::<u32>));
+    let mut writer = Reopen::from_fn({
+        let clock = Arc::clone(&clock);
+        let previous = Arc::clone(&previous);
+        move || {
+            if let Some(pid) = *previous.lock().unwrap() {
+                assert!(reaped(pid), "The old child still runs");
+            }
+            let mut command = Command::new("sh");
+            // Ignores both the end of input and SIGTERM.
+            command.arg("-c").arg("trap '' TERM; exec sleep 30");
+            ChildWriter::spawn(&mut command)
+                .map(|writer| writer.kill_after(grace).with_clock(clock.clone()))
+        }
+    })
+    .unwrap()
+    .with_clock(clock.clone());
+    let first = writer.lock().unwrap().child().id();
+    *previous.lock().unwrap() = Some(first);
+
+    let start = Instant::now();
+    let clock_start = clock.now();
+    writer.handle().reopen();
+    let second = writer.lock().unwrap().child().id();
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert!(clock.now() - clock_start >= grace);
+    assert_ne!(first, second);
+    assert!(reaped(first));
+    *previous.lock().unwrap() = Some(second);
+    drop(writer);
+    assert!(reaped(second));
+}
+
+/// A child exiting within the grace period is left to finish.
+#[test]
+fn grace_to_finish() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("out");
+    let mut writer = Reopen::from_fn(piped_with_grace(
+        {
+            let path = path.clone();
+            move || {
+                let mut command = Command::new("sh");
+                command
+                    .arg("-c")
+                    .arg("cat > /dev/null; sleep 0.1; echo done >> \"$0\"")
+                    .arg(&path);
+                command
+            }
+        },
+        Duration::from_secs(10),
+    ))
+    .unwrap();
+    writer.write_all(b"data\n").unwrap();
+    drop(writer);
+    assert_eq!("done\n", fs::read_to_string(&path).unwrap());
+}
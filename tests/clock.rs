@@ -0,0 +1,87 @@
+//! Tests of the clock abstraction.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use reopen::test_util::{CountingConstructor, FailN, HistorySink};
+use reopen::{Clock, ManualClock, Reopen};
+
+#[test]
+fn manual_clock() {
+    let clock = ManualClock::new();
+    let start = clock.now();
+    let system_start = clock.system_now();
+    assert_eq!(start, clock.now());
+    clock.advance(Duration::from_secs(5));
+    clock.advance(Duration::from_millis(500));
+    assert_eq!(Duration::from_millis(5500), clock.now() - start);
+    assert_eq!(
+        Duration::from_millis(5500),
+        clock.system_now().duration_since(system_start).unwrap()
+    );
+}
+
+#[test]
+fn reopen_uses_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let reopen = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))
+        .unwrap()
+        .with_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+    let start = reopen.clock().now();
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(Duration::from_secs(1), reopen.clock().now() - start);
+}
+
+#[test]
+fn block_until_openable() {
+    let clock = Arc::new(ManualClock::new());
+    let sink = HistorySink::new();
+    let attempts = CountingConstructor::new(FailN::new(3, sink.constructor()).constructor());
+    let mut reopen = Reopen::lazy(Box::new(attempts.constructor()))
+        .with_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+    let start = clock.now();
+    reopen
+        .block_until_openable(Duration::from_secs(10), Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(4, attempts.opens());
+    assert_eq!(Duration::from_secs(3), clock.now() - start);
+    // Already opened, no more attempts.
+    reopen.write_all(b"hello").unwrap();
+    assert_eq!(4, attempts.opens());
+    assert_eq!(vec![b"hello".to_vec()], sink.history());
+}
+
+#[test]
+fn block_until_openable_timeout() {
+    let clock = Arc::new(ManualClock::new());
+    let mut reopen = Reopen::<Vec<u8>>::lazy(Box::new(|| {
+        Err(Error::new(ErrorKind::NotFound, "Never there"))
+    }))
+    .with_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+    let start = clock.now();
+    let err = reopen
+        .block_until_openable(Duration::from_millis(2500), Duration::from_secs(1))
+        .unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+    assert_eq!(Duration::from_millis(2500), clock.now() - start);
+    assert!(reopen.write_all(b"hello").is_err());
+}
+
+/// Waiting for the opener thread doesn't time out just because the manual clock is asked to
+/// sleep, the open really runs in the meantime.
+#[test]
+fn background_wait_manual() {
+    let clock = Arc::new(ManualClock::new());
+    let mut reopen = Reopen::lazy(Box::new(|| {
+        thread::sleep(Duration::from_millis(200));
+        Ok(Vec::<u8>::new())
+    }))
+    .with_clock(Arc::clone(&clock) as Arc<dyn Clock>)
+    .background_open(true, Duration::from_secs(1));
+    let start = clock.now();
+    reopen.write_all(b"hello").unwrap();
+    assert_eq!(start, clock.now());
+    assert_eq!(b"hello", &reopen.lock().unwrap()[..]);
+}
@@ -0,0 +1,36 @@
+//! Tests of writing into gzip-compressed files.
+#![cfg(feature = "flate2")]
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use reopen::compress::gzip_file;
+use tempfile::TempDir;
+
+fn decode(path: &Path) -> String {
+    let mut decoder = GzDecoder::new(File::open(path).unwrap());
+    let mut content = String::new();
+    decoder.read_to_string(&mut content).unwrap();
+    content
+}
+
+#[test]
+fn rotate_twice() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log.gz");
+    let mut writer = gzip_file(&path).unwrap();
+    writeln!(writer, "first").unwrap();
+    fs::rename(&path, dir.path().join("log.gz.1")).unwrap();
+    writer.handle().reopen();
+    writeln!(writer, "second").unwrap();
+    fs::rename(&path, dir.path().join("log.gz.2")).unwrap();
+    writer.handle().reopen();
+    writeln!(writer, "third").unwrap();
+    drop(writer);
+
+    assert_eq!("first\n", decode(&dir.path().join("log.gz.1")));
+    assert_eq!("second\n", decode(&dir.path().join("log.gz.2")));
+    assert_eq!("third\n", decode(&path));
+}
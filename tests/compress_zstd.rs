@@ -0,0 +1,54 @@
+//! Tests of writing into zstd-compressed files.
+#![cfg(feature = "zstd")]
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use reopen::compress::zstd_file;
+use tempfile::TempDir;
+
+fn decode(path: &Path) -> Vec<u8> {
+    zstd::stream::decode_all(File::open(path).unwrap()).unwrap()
+}
+
+#[test]
+fn rotate_twice() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log.zst");
+    let mut writer = zstd_file(&path, 0).unwrap();
+    writeln!(writer, "first").unwrap();
+    fs::rename(&path, dir.path().join("log.zst.1")).unwrap();
+    writer.handle().reopen();
+    writeln!(writer, "second").unwrap();
+    fs::rename(&path, dir.path().join("log.zst.2")).unwrap();
+    writer.handle().reopen();
+    writeln!(writer, "third").unwrap();
+    drop(writer);
+
+    assert_eq!(b"first\n", &decode(&dir.path().join("log.zst.1"))[..]);
+    assert_eq!(b"second\n", &decode(&dir.path().join("log.zst.2"))[..]);
+    assert_eq!(b"third\n", &decode(&path)[..]);
+}
+
+/// Writing into an existing file appends another frame, the whole file still decodes.
+#[test]
+fn append_frames() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log.zst");
+    for line in &["first", "second"] {
+        let mut writer = zstd_file(&path, 3).unwrap();
+        writeln!(writer, "{}", line).unwrap();
+    }
+    assert_eq!(b"first\nsecond\n", &decode(&path)[..]);
+}
+
+#[test]
+fn flush_reaches_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log.zst");
+    let mut writer = zstd_file(&path, 0).unwrap();
+    writeln!(writer, "Hello").unwrap();
+    writer.flush().unwrap();
+    assert!(fs::metadata(&path).unwrap().len() > 0);
+}
@@ -0,0 +1,139 @@
+//! Tests of building a `Reopen` from a configuration.
+#![cfg(feature = "serde")]
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use reopen::{Compression, ReopenConfig};
+use tempfile::TempDir;
+
+#[test]
+fn deserialize_and_build() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let json = serde_json::json!({
+        "path": path,
+        "header": "header\n",
+        "footer": "footer\n",
+    });
+    let config: ReopenConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(path, config.path);
+    assert!(!config.reopen_on_write_zero);
+    assert_eq!(1, config.record_retries);
+    assert!(config.signals.is_empty());
+
+    let mut log = config.build().unwrap();
+    writeln!(log, "first").unwrap();
+    let rotated = dir.path().join("log.1");
+    fs::rename(&path, &rotated).unwrap();
+    log.handle().reopen();
+    writeln!(log, "second").unwrap();
+    drop(log);
+
+    assert_eq!(
+        "header\nfirst\nfooter\n",
+        fs::read_to_string(&rotated).unwrap()
+    );
+    assert_eq!(
+        "header\nsecond\nfooter\n",
+        fs::read_to_string(&path).unwrap()
+    );
+}
+
+#[test]
+fn roundtrip() {
+    let mut config = ReopenConfig::new("/log/file");
+    config.record_retries = 3;
+    config.signals = vec![1];
+    let json = serde_json::to_string(&config).unwrap();
+    let back: ReopenConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(config, back);
+}
+
+#[test]
+#[cfg(any(not(feature = "signals"), target_os = "wasi"))]
+fn signals_unsupported() {
+    let dir = TempDir::new().unwrap();
+    let mut config = ReopenConfig::new(dir.path().join("log"));
+    config.signals = vec![1];
+    assert!(config.build().is_err());
+}
+
+/// The names of the files in the directory, sorted.
+fn files(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn rotation_by_config() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    let json = serde_json::json!({
+        "path": path,
+        "size_limit": 10,
+        "keep": 2,
+    });
+    let config: ReopenConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(Some(10), config.size_limit);
+    assert_eq!(2, config.keep);
+
+    let mut log = config.build().unwrap();
+    // Each record is 5 bytes, so two fill a file.
+    for i in 0..7 {
+        writeln!(log, "rec{}", i).unwrap();
+    }
+    drop(log);
+
+    assert_eq!(vec!["app.log", "app.log.1", "app.log.2"], files(dir.path()));
+    assert_eq!("rec6\n", fs::read_to_string(&path).unwrap());
+    let rotated = fs::read_to_string(dir.path().join("app.log.1")).unwrap();
+    assert_eq!("rec4\nrec5\n", rotated);
+}
+
+#[test]
+#[cfg(feature = "flate2")]
+fn compressed_rotation() {
+    use flate2::read::GzDecoder;
+    use std::fs::File;
+    use std::io::Read;
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    let json = serde_json::json!({
+        "path": path,
+        "size_limit": 10,
+        "keep": 1,
+        "compression": "gzip",
+    });
+    let config: ReopenConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(Some(Compression::Gzip), config.compression);
+
+    let mut log = config.build().unwrap();
+    for i in 0..5 {
+        writeln!(log, "rec{}", i).unwrap();
+    }
+    drop(log);
+
+    assert_eq!(vec!["app.log", "app.log.1.gz"], files(dir.path()));
+    assert_eq!("rec4\n", fs::read_to_string(&path).unwrap());
+    let mut rotated = String::new();
+    GzDecoder::new(File::open(dir.path().join("app.log.1.gz")).unwrap())
+        .read_to_string(&mut rotated)
+        .unwrap();
+    assert_eq!("rec2\nrec3\n", rotated);
+}
+
+/// Compression makes sense only with the rotation done by us.
+#[test]
+fn compression_without_limit() {
+    let dir = TempDir::new().unwrap();
+    let mut config = ReopenConfig::new(dir.path().join("log"));
+    config.compression = Some(Compression::Gzip);
+    assert_eq!(ErrorKind::InvalidInput, config.build().unwrap_err().kind());
+}
@@ -0,0 +1,44 @@
+//! Tests of the debug output.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use reopen::Reopen;
+
+#[test]
+fn states() {
+    let fail = Arc::new(AtomicBool::new(false));
+    let mut writer = Reopen::new(Box::new({
+        let fail = Arc::clone(&fail);
+        move || {
+            if fail.load(Ordering::Relaxed) {
+                Err(Error::new(ErrorKind::NotFound, "Not there"))
+            } else {
+                Ok(Vec::<u8>::new())
+            }
+        }
+    }))
+    .unwrap();
+    let debug = format!("{:?}", writer);
+    assert!(debug.contains("pending: false"), "{}", debug);
+    assert!(debug.contains("open: true"), "{}", debug);
+
+    fail.store(true, Ordering::Relaxed);
+    writer.handle().reopen();
+    let debug = format!("{:?}", writer);
+    assert!(debug.contains("pending: true"), "{}", debug);
+    assert!(debug.contains("open: true"), "{}", debug);
+
+    assert!(writer.write_all(b"a").is_err());
+    let debug = format!("{:?}", writer);
+    assert!(debug.contains("pending: true"), "{}", debug);
+    assert!(debug.contains("open: false"), "{}", debug);
+
+    fail.store(false, Ordering::Relaxed);
+    writer.write_all(b"a").unwrap();
+    let debug = format!("{:?}", writer);
+    assert!(debug.contains("pending: false"), "{}", debug);
+    assert!(debug.contains("open: true"), "{}", debug);
+    assert!(debug.contains("reopens: 1"), "{}", debug);
+}
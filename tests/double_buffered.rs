@@ -0,0 +1,147 @@
+//! Tests of opening the new FD in the background.
+
+use std::io::{Error, ErrorKind, Write};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reopen::test_util::{HistorySink, HistoryWriter};
+use reopen::Reopen;
+
+/// All but the first open take half a second.
+fn provide_writer() -> (Reopen<HistoryWriter>, HistorySink) {
+    let sink = HistorySink::new();
+    let opened = AtomicUsize::new(0);
+    let reopen = Reopen::new(Box::new({
+        let sink = sink.clone();
+        move || {
+            if opened.fetch_add(1, Ordering::Relaxed) > 0 {
+                thread::sleep(Duration::from_millis(500));
+            }
+            sink.open()
+        }
+    }))
+    .unwrap()
+    .double_buffered(true);
+    (reopen, sink)
+}
+
+/// No write waits for the slow constructor, yet the writes eventually land in the new FD.
+#[test]
+fn writes_dont_wait() {
+    let (mut writer, history) = provide_writer();
+    writer.write_all(b"a").unwrap();
+    writer.handle().reopen();
+    let start = Instant::now();
+    while history.incarnations() < 2 {
+        assert!(start.elapsed() < Duration::from_secs(5), "Never reopened");
+        let write_start = Instant::now();
+        writer.write_all(b"b").unwrap();
+        assert!(write_start.elapsed() < Duration::from_millis(100));
+        thread::sleep(Duration::from_millis(10));
+    }
+    writer.write_all(b"c").unwrap();
+
+    let history = history.history();
+    assert_eq!(2, history.len());
+    assert!(history[0].len() > 2, "Writes didn't go to the old FD");
+    assert!(history[0].starts_with(b"ab"));
+    assert!(history[0][1..].iter().all(|b| *b == b'b'));
+    // The new FD might have got one of the b's, depending on timing.
+    assert!(history[1].ends_with(b"c"));
+}
+
+/// Multiple requests while an open is in progress result in a single reopen.
+#[test]
+fn requests_merged() {
+    let (mut writer, history) = provide_writer();
+    let handle = writer.handle();
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_millis(800) {
+        handle.reopen();
+        writer.write_all(b"a").unwrap();
+        thread::sleep(Duration::from_millis(10));
+    }
+    // One reopen started at the beginning and finished; the requests during it started another.
+    // But not more than that.
+    assert!(history.incarnations() <= 3);
+}
+
+/// The double buffering keeps the fast path, a request waits for its turn to be checked.
+#[test]
+fn check_every_respected() {
+    let sink = HistorySink::new();
+    let opened = Arc::new(AtomicUsize::new(0));
+    let mut writer = Reopen::new(Box::new({
+        let opened = Arc::clone(&opened);
+        move || {
+            opened.fetch_add(1, Ordering::Relaxed);
+            sink.open()
+        }
+    }))
+    .unwrap()
+    .double_buffered(true)
+    .check_every(NonZeroUsize::new(100).unwrap());
+    let handle = writer.handle();
+    writer.write_all(b"a").unwrap();
+    handle.reopen();
+    writer.write_all(b"b").unwrap();
+    // Give a (wrongly) started background open the time to run.
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(1, opened.load(Ordering::Relaxed));
+}
+
+/// Fails the writes once its number is marked as dead.
+struct Dying {
+    number: usize,
+    dead: Arc<AtomicUsize>,
+}
+
+impl Write for Dying {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if self.dead.load(Ordering::Relaxed) == self.number {
+            Err(Error::new(ErrorKind::BrokenPipe, "Dead"))
+        } else {
+            Ok(buf.len())
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// When the old FD dies during the background open, the FD is taken over from that open instead
+/// of opening another one.
+#[test]
+fn old_dies_during_open() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let dead = Arc::new(AtomicUsize::new(0));
+    let mut writer = Reopen::new(Box::new({
+        let opened = Arc::clone(&opened);
+        let dead = Arc::clone(&dead);
+        move || {
+            let number = opened.fetch_add(1, Ordering::Relaxed) + 1;
+            if number > 1 {
+                thread::sleep(Duration::from_millis(300));
+            }
+            Ok(Dying {
+                number,
+                dead: Arc::clone(&dead),
+            })
+        }
+    }))
+    .unwrap()
+    .double_buffered(true);
+    writer.handle().reopen();
+    // Starts the open in the background, goes to the old FD.
+    writer.write_all(b"a").unwrap();
+    dead.store(1, Ordering::Relaxed);
+    writer.write_record(b"b").unwrap();
+    assert_eq!(2, writer.lock().unwrap().number);
+    // Give a (wrongly) started third open the time to finish.
+    thread::sleep(Duration::from_millis(400));
+    assert_eq!(2, opened.load(Ordering::Relaxed));
+}
@@ -0,0 +1,41 @@
+//! Tests of the env_logger target helper.
+#![cfg(feature = "env_logger")]
+
+use std::fs;
+use std::io::Write;
+
+use log::{Level, LevelFilter, Log, Record};
+use reopen::integrations::env_logger_target;
+use tempfile::TempDir;
+
+fn log(logger: &dyn Log, level: Level, msg: &str) {
+    logger.log(
+        &Record::builder()
+            .level(level)
+            .target("test")
+            .args(format_args!("{}", msg))
+            .build(),
+    );
+}
+
+#[test]
+fn log_and_rotate() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let (target, handle) = env_logger_target(&path).unwrap();
+    let logger = env_logger::Builder::new()
+        .target(target)
+        .filter_level(LevelFilter::Info)
+        .format(|out, record| writeln!(out, "{} {}", record.level(), record.args()))
+        .build();
+
+    log(&logger, Level::Info, "first");
+    log(&logger, Level::Debug, "filtered out");
+    let rotated = path.with_extension("1");
+    fs::rename(&path, &rotated).unwrap();
+    handle.reopen();
+    log(&logger, Level::Warn, "second");
+
+    assert_eq!("INFO first\n", fs::read_to_string(&rotated).unwrap());
+    assert_eq!("WARN second\n", fs::read_to_string(&path).unwrap());
+}
@@ -0,0 +1,80 @@
+//! Tests of the event stream.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use reopen::{Reopen, ReopenEvent};
+
+/// Check the exact sequence of events across a failed and then a successful reopen.
+#[test]
+fn failure_then_success() {
+    let fail = Arc::new(AtomicBool::new(false));
+    let mut writer = Reopen::new(Box::new({
+        let fail = Arc::clone(&fail);
+        move || {
+            if fail.load(Ordering::Relaxed) {
+                Err(Error::new(ErrorKind::NotFound, "Not there"))
+            } else {
+                Ok(Vec::<u8>::new())
+            }
+        }
+    }))
+    .unwrap();
+    let events = writer.subscribe();
+
+    // No changes, no events.
+    writer.write_all(b"hello").unwrap();
+    assert!(events.try_recv().is_err());
+
+    fail.store(true, Ordering::Relaxed);
+    writer.handle().reopen();
+    assert_eq!(
+        ErrorKind::NotFound,
+        writer.write_all(b"hello").unwrap_err().kind()
+    );
+    fail.store(false, Ordering::Relaxed);
+    writer.write_all(b"hello").unwrap();
+
+    let received = events.try_iter().collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            ReopenEvent::ReopenRequested,
+            ReopenEvent::Closed,
+            ReopenEvent::OpenFailed {
+                error_kind: ErrorKind::NotFound
+            },
+            ReopenEvent::OpenSucceeded { generation: 1 },
+        ],
+        received
+    );
+    assert_eq!(0, writer.dropped_events());
+}
+
+/// A subscriber that doesn't read doesn't block the writer, the events are counted instead.
+#[test]
+fn slow_consumer() {
+    let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+    let events = writer.subscribe();
+    let handle = writer.handle();
+    for _ in 0..100 {
+        handle.reopen();
+        writer.write_all(b"hello").unwrap();
+    }
+    // 3 events per round
+    assert_eq!(300 - 128, writer.dropped_events());
+    assert_eq!(128, events.try_iter().count());
+}
+
+/// Dropping the receiver unsubscribes.
+#[test]
+fn unsubscribe() {
+    let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+    drop(writer.subscribe());
+    let handle = writer.handle();
+    for _ in 0..100 {
+        handle.reopen();
+        writer.write_all(b"hello").unwrap();
+    }
+    assert_eq!(0, writer.dropped_events());
+}
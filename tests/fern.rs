@@ -0,0 +1,42 @@
+//! Tests of the fern adapter.
+#![cfg(feature = "fern")]
+
+use std::fs;
+
+use log::{Level, LevelFilter, Log, Record};
+use reopen::integrations::fern_output;
+use reopen::Reopen;
+use tempfile::TempDir;
+
+fn log(logger: &dyn Log, level: Level, msg: &str) {
+    logger.log(
+        &Record::builder()
+            .level(level)
+            .target("test")
+            .args(format_args!("{}", msg))
+            .build(),
+    );
+}
+
+#[test]
+fn log_and_rotate() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let reopen = Reopen::append_to(&path).unwrap();
+    let handle = reopen.handle();
+    let (_, logger) = fern::Dispatch::new()
+        .format(|out, message, record| out.finish(format_args!("{} {}", record.level(), message)))
+        .level(LevelFilter::Info)
+        .chain(fern_output(reopen))
+        .into_log();
+
+    log(&*logger, Level::Info, "first");
+    log(&*logger, Level::Debug, "filtered out");
+    let rotated = path.with_extension("1");
+    fs::rename(&path, &rotated).unwrap();
+    handle.reopen();
+    log(&*logger, Level::Warn, "second");
+
+    assert_eq!("INFO first\n", fs::read_to_string(&rotated).unwrap());
+    assert_eq!("WARN second\n", fs::read_to_string(&path).unwrap());
+}
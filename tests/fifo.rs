@@ -0,0 +1,81 @@
+//! Tests of writing into a FIFO with a reader that comes and goes.
+#![cfg(all(feature = "fifo", unix))]
+
+use std::ffi::CString;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+fn mkfifo(dir: &TempDir) -> PathBuf {
+    let path = dir.path().join("fifo");
+    let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    assert_eq!(0, unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) });
+    path
+}
+
+/// Opens the reading end without waiting for a writer.
+fn reader(path: &Path) -> File {
+    OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+        .unwrap()
+}
+
+fn read(reader: &mut File, len: usize) -> String {
+    let mut buf = vec![0; len];
+    reader.read_exact(&mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn reader_comes_and_goes() {
+    let dir = TempDir::new().unwrap();
+    let path = mkfifo(&dir);
+    let mut writer = reopen::fifo::writer(&path);
+    assert_eq!(Some(path.as_path()), writer.current_path());
+
+    // Doesn't block without a reader, just fails.
+    let err = writer.write_all(b"lost").unwrap_err();
+    assert_eq!(ErrorKind::NotConnected, err.kind());
+
+    let mut first = reader(&path);
+    writer.write_all(b"hello").unwrap();
+    assert_eq!("hello", read(&mut first, 5));
+
+    drop(first);
+    let err = writer.write_all(b"lost").unwrap_err();
+    assert_eq!(ErrorKind::BrokenPipe, err.kind());
+    assert!(writer.is_pending());
+    let err = writer.write_all(b"lost").unwrap_err();
+    assert_eq!(ErrorKind::NotConnected, err.kind());
+
+    let mut second = reader(&path);
+    writer.write_all(b"world").unwrap();
+    assert_eq!("world", read(&mut second, 5));
+}
+
+/// The FIFO is switched back to blocking, so writes wait for a slow reader instead of failing.
+#[test]
+fn blocking_after_open() {
+    let dir = TempDir::new().unwrap();
+    let path = mkfifo(&dir);
+    let mut first = reader(&path);
+    let mut writer = reopen::fifo::open(&path).unwrap();
+    let data = vec![b'x'; 1024 * 1024];
+    let sender = std::thread::spawn(move || writer.write_all(&data));
+    let mut received = Vec::new();
+    while received.len() < 1024 * 1024 {
+        let mut buf = [0; 4096];
+        match first.read(&mut buf) {
+            Ok(read) => received.extend_from_slice(&buf[..read]),
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => std::thread::yield_now(),
+            Err(e) => panic!("{}", e),
+        }
+    }
+    sender.join().unwrap().unwrap();
+}
@@ -0,0 +1,148 @@
+//! Tests of the file helpers.
+
+use std::convert::TryInto;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+/// Writes, moves the file away, reopens and writes again.
+fn check_rotation(mut log: Reopen<File>, path: &Path) {
+    writeln!(log, "first").unwrap();
+    let rotated = path.with_extension("1");
+    fs::rename(path, &rotated).unwrap();
+    log.handle().reopen();
+    writeln!(log, "second").unwrap();
+
+    assert_eq!("first\n", fs::read_to_string(&rotated).unwrap());
+    assert_eq!("second\n", fs::read_to_string(path).unwrap());
+}
+
+#[test]
+fn from_path_buf() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let log: Reopen<File> = path.clone().try_into().unwrap();
+    check_rotation(log, &path);
+}
+
+#[test]
+fn from_path() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let log: Reopen<File> = path.as_path().try_into().unwrap();
+    check_rotation(log, &path);
+}
+
+#[test]
+fn from_str() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let log: Reopen<File> = path.to_str().unwrap().parse().unwrap();
+    check_rotation(log, &path);
+}
+
+/// The file is appended to, not truncated.
+#[test]
+fn appends() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    fs::write(&path, "existing\n").unwrap();
+    let mut log = Reopen::append_to(&path).unwrap();
+    writeln!(log, "new").unwrap();
+    assert_eq!("existing\nnew\n", fs::read_to_string(&path).unwrap());
+}
+
+/// Conversion fails if the file can't be opened.
+#[test]
+fn open_error() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("missing").join("log");
+    let log: Result<Reopen<File>, _> = path.try_into();
+    assert!(log.is_err());
+}
+
+#[test]
+fn current_path() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let log = Reopen::append_to(&path).unwrap();
+    assert_eq!(Some(path.as_path()), log.current_path());
+}
+
+#[test]
+fn current_path_unknown() {
+    let log = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+    assert_eq!(None, log.current_path());
+}
+
+#[test]
+fn append_with_options() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    fs::write(&path, "existing\n").unwrap();
+    let mut options = OpenOptions::new();
+    options.write(true).truncate(true);
+    let mut log = Reopen::append_with(&path, options).unwrap();
+    writeln!(log, "new").unwrap();
+    assert_eq!("new\n", fs::read_to_string(&path).unwrap());
+    assert_eq!(Some(path.as_path()), log.current_path());
+}
+
+/// The file can be renamed while open, which needs `FILE_SHARE_DELETE` on windows.
+#[cfg(windows)]
+#[test]
+fn rename_while_open() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let log = Reopen::append_to(&path).unwrap();
+    check_rotation(log, &path);
+}
+
+#[test]
+fn create_parents() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("2024").join("01").join("log");
+    let mut log = Reopen::lazy_append_to(&path).with_create_parents(true);
+    writeln!(log, "first").unwrap();
+    assert_eq!("first\n", fs::read_to_string(&path).unwrap());
+
+    // Rotated into a directory that doesn't exist yet.
+    fs::rename(dir.path().join("2024"), dir.path().join("old")).unwrap();
+    log.handle().reopen();
+    writeln!(log, "second").unwrap();
+    assert_eq!("second\n", fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn create_parents_background() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("a").join("log");
+    let mut log = Reopen::lazy_append_to(&path)
+        .with_create_parents(true)
+        .double_buffered(true);
+    writeln!(log, "first").unwrap();
+    fs::rename(dir.path().join("a"), dir.path().join("b")).unwrap();
+    log.handle().reopen();
+    // The new file is opened in a helper thread, wait for it.
+    for _ in 0..1000 {
+        writeln!(log, "more").unwrap();
+        if path.exists() {
+            return;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("File not created");
+}
+
+#[test]
+fn without_create_parents() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("missing").join("log");
+    let mut log = Reopen::lazy_append_to(&path);
+    assert!(writeln!(log, "first").is_err());
+}
@@ -0,0 +1,121 @@
+//! Tests of the time limit on flushing the old FD.
+
+use std::io::{BufWriter, Error, Write};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reopen::Reopen;
+
+/// A writer whose flush blocks until released (or the sender is dropped).
+struct Stuck {
+    release: Receiver<()>,
+    flushed: Arc<Mutex<usize>>,
+}
+
+impl Write for Stuck {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> Result<(), Error> {
+        let _ = self.release.recv();
+        *self.flushed.lock().unwrap() += 1;
+        Ok(())
+    }
+}
+
+/// The senders releasing the flushes of the FDs, in the order of opening.
+type Releases = Arc<Mutex<Vec<Sender<()>>>>;
+
+fn stuck() -> (Reopen<Stuck>, Releases, Arc<Mutex<usize>>) {
+    let senders = Arc::new(Mutex::new(Vec::new()));
+    let flushed = Arc::new(Mutex::new(0));
+    let reopen = Reopen::new(Box::new({
+        let senders = Arc::clone(&senders);
+        let flushed = Arc::clone(&flushed);
+        move || {
+            let (sender, release) = mpsc::channel();
+            senders.lock().unwrap().push(sender);
+            Ok(Stuck {
+                release,
+                flushed: Arc::clone(&flushed),
+            })
+        }
+    }))
+    .unwrap();
+    (reopen, senders, flushed)
+}
+
+#[test]
+fn hung_flush_doesnt_block_reopen() {
+    let (writer, senders, flushed) = stuck();
+    let mut writer = writer.flush_timeout(Duration::from_millis(50));
+    writer.write_all(b"Hello").unwrap();
+    let start = Instant::now();
+    writer.handle().reopen();
+    writer.write_all(b"Hello").unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    // The reopen happened, with a new FD
+    assert_eq!(2, senders.lock().unwrap().len());
+    assert_eq!(1, writer.flush_timeouts());
+    assert_eq!(0, *flushed.lock().unwrap());
+
+    // Releasing the helper thread lets it finish the flush in the background.
+    senders.lock().unwrap()[0].send(()).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while *flushed.lock().unwrap() == 0 {
+        assert!(Instant::now() < deadline);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn timely_flush() {
+    let data = Arc::new(Mutex::new(Vec::new()));
+    /// Appends into the shared buffer.
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+    let mut writer = Reopen::new(Box::new({
+        let data = Arc::clone(&data);
+        move || Ok(BufWriter::new(Shared(Arc::clone(&data))))
+    }))
+    .unwrap()
+    .flush_timeout(Duration::from_secs(10));
+    writer.write_all(b"Hello").unwrap();
+    writer.handle().reopen();
+    writer.write_all(b" world").unwrap();
+    // The old one got flushed and waited for before the reopen.
+    assert_eq!(b"Hello", &data.lock().unwrap()[..]);
+    assert_eq!(0, writer.flush_timeouts());
+    drop(writer);
+    assert_eq!(b"Hello world", &data.lock().unwrap()[..]);
+}
+
+#[test]
+fn drop_bounded() {
+    let (writer, senders, _flushed) = stuck();
+    let writer = writer.flush_timeout(Duration::from_millis(50));
+    let start = Instant::now();
+    drop(writer);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    drop(senders);
+}
+
+#[test]
+fn off_by_default() {
+    let (mut writer, senders, flushed) = stuck();
+    assert_eq!(0, writer.flush_timeouts());
+    // Without a limit, the old FD is dropped without a flush of our own.
+    writer.handle().reopen();
+    writer.write_all(b"Hello").unwrap();
+    assert_eq!(2, senders.lock().unwrap().len());
+    assert_eq!(0, *flushed.lock().unwrap());
+}
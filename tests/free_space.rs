@@ -0,0 +1,109 @@
+//! Tests of reacting to the free space running low.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reopen::test_util::{HistorySink, HistoryWriter};
+use reopen::{LowSpaceAction, ManualClock, Reopen};
+
+const MIN: usize = 1000;
+const SECOND: Duration = Duration::from_secs(1);
+
+fn provide_writer(
+    action: LowSpaceAction,
+) -> (
+    Reopen<HistoryWriter>,
+    HistorySink,
+    Arc<AtomicUsize>,
+    Arc<ManualClock>,
+) {
+    let sink = HistorySink::new();
+    let free = Arc::new(AtomicUsize::new(MIN * 10));
+    let clock = Arc::new(ManualClock::new());
+    let writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .with_clock(Arc::clone(&clock) as Arc<_>)
+        .with_min_free_space_by(MIN as u64, SECOND, action, {
+            let free = Arc::clone(&free);
+            move || Ok(free.load(Ordering::Relaxed) as u64)
+        });
+    (writer, sink, free, clock)
+}
+
+#[test]
+fn reopen_when_low() {
+    let (mut writer, sink, free, clock) = provide_writer(LowSpaceAction::Reopen);
+    writer.write_all(b"a").unwrap();
+    free.store(MIN - 1, Ordering::Relaxed);
+    // Not checked again yet.
+    writer.write_all(b"b").unwrap();
+    clock.advance(SECOND);
+    writer.write_all(b"c").unwrap();
+    assert_eq!(vec![b"ab".to_vec(), b"c".to_vec()], sink.history());
+    // Still low, but that's not a new drop.
+    clock.advance(SECOND);
+    writer.write_all(b"d").unwrap();
+    assert_eq!(2, sink.incarnations());
+    // Recovers and drops again.
+    free.store(MIN, Ordering::Relaxed);
+    clock.advance(SECOND);
+    writer.write_all(b"e").unwrap();
+    free.store(0, Ordering::Relaxed);
+    clock.advance(SECOND);
+    writer.write_all(b"f").unwrap();
+    assert_eq!(
+        vec![b"ab".to_vec(), b"cde".to_vec(), b"f".to_vec()],
+        sink.history()
+    );
+}
+
+#[test]
+fn refuse_when_low() {
+    let (mut writer, sink, free, clock) = provide_writer(LowSpaceAction::Refuse);
+    writer.write_all(b"a").unwrap();
+    free.store(MIN - 1, Ordering::Relaxed);
+    clock.advance(SECOND);
+    let err = writer.write_all(b"b").unwrap_err();
+    assert!(err.to_string().contains("disk full guard"));
+    assert_eq!(Some(err.to_string().as_str()), writer.poison_reason());
+    // Stays refused between the checks.
+    free.store(MIN, Ordering::Relaxed);
+    assert!(writer.write_all(b"c").is_err());
+    clock.advance(SECOND);
+    writer.write_all(b"d").unwrap();
+    assert!(!writer.is_poisoned());
+    // No reopen, all in the same FD.
+    assert_eq!(vec![b"ad".to_vec()], sink.history());
+}
+
+/// The reset checks right away, without waiting for the interval.
+#[test]
+fn reset_rechecks() {
+    let (mut writer, _, free, _clock) = provide_writer(LowSpaceAction::Refuse);
+    free.store(0, Ordering::Relaxed);
+    assert!(writer.write_all(b"a").is_err());
+    free.store(MIN, Ordering::Relaxed);
+    writer.reset();
+    writer.write_all(b"b").unwrap();
+}
+
+#[cfg(all(any(unix, windows), feature = "free-space"))]
+#[test]
+fn real_disk() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let mut enough =
+        Reopen::append_to(&path)
+            .unwrap()
+            .with_min_free_space(0, SECOND, LowSpaceAction::Refuse);
+    enough.write_all(b"Hello").unwrap();
+    // More than any disk has.
+    let mut too_little = Reopen::append_to(&path).unwrap().with_min_free_space(
+        1 << 62,
+        SECOND,
+        LowSpaceAction::Refuse,
+    );
+    assert!(too_little.write_all(b"Hello").is_err());
+}
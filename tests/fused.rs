@@ -0,0 +1,46 @@
+//! Tests of the `FusedReopen`.
+
+use std::io::{Cursor, Read};
+
+use reopen::test_util::CountingConstructor;
+use reopen::Reopen;
+
+#[test]
+fn eof_is_final() {
+    let constructor = CountingConstructor::new(|| Ok(Cursor::new(b"hello".to_vec())));
+    let mut reader = Reopen::new(Box::new(constructor.constructor()))
+        .unwrap()
+        .fuse();
+    let handle = reader.get_ref().handle();
+
+    let mut buf = [0; 3];
+    assert_eq!(3, reader.read(&mut buf).unwrap());
+    assert_eq!(2, reader.read(&mut buf).unwrap());
+    assert!(!reader.is_done());
+    assert_eq!(0, reader.read(&mut buf).unwrap());
+    assert!(reader.is_done());
+
+    handle.reopen();
+    assert_eq!(0, reader.read(&mut buf).unwrap());
+    // It didn't even get to reopening.
+    assert_eq!(1, constructor.opens());
+    assert!(reader.get_ref().is_pending());
+
+    // The inner one is not fused.
+    let mut inner = reader.into_inner();
+    assert_eq!(3, inner.read(&mut buf).unwrap());
+    assert_eq!(2, constructor.opens());
+}
+
+/// Reading into an empty buffer is not an EOF.
+#[test]
+fn empty_buffer() {
+    let mut reader = Reopen::new(Box::new(|| Ok(Cursor::new(b"hello".to_vec()))))
+        .unwrap()
+        .fuse();
+    assert_eq!(0, reader.read(&mut []).unwrap());
+    assert!(!reader.is_done());
+    let mut content = String::new();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!("hello", content);
+}
@@ -0,0 +1,141 @@
+//! Tests of the `genio` traits.
+#![cfg(feature = "genio")]
+
+use std::io::{Error, ErrorKind};
+use std::sync::{Arc, Mutex};
+
+use genio::error::ReadExactError;
+use genio::{Read, Write};
+use reopen::{GenioError, Reopen};
+
+/// Reads the given data, one byte at a time, and requests a reopen after each.
+struct Bytes {
+    data: &'static [u8],
+    handle: reopen::Handle,
+}
+
+impl Read for Bytes {
+    type ReadError = ErrorKind;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, ErrorKind> {
+        self.handle.reopen();
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        match self.data.split_first() {
+            Some((first, rest)) => {
+                buf[0] = *first;
+                self.data = rest;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+/// Records all the incarnations, refuses to write more than `limit` bytes into each.
+struct Sink {
+    history: Arc<Mutex<Vec<Vec<u8>>>>,
+    limit: usize,
+}
+
+impl Write for Sink {
+    type WriteError = ErrorKind;
+    type FlushError = ErrorKind;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, ErrorKind> {
+        let mut history = self.history.lock().unwrap();
+        let current = history.last_mut().unwrap();
+        if current.len() + buf.len() > self.limit {
+            return Err(ErrorKind::WriteZero);
+        }
+        current.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), ErrorKind> {
+        Ok(())
+    }
+
+    fn size_hint(&mut self, _: usize) {}
+}
+
+fn provide_reader() -> Reopen<Bytes> {
+    let handle = reopen::Handle::stub();
+    Reopen::with_handle(
+        handle.clone(),
+        Box::new(move || {
+            Ok(Bytes {
+                data: b"hello",
+                handle: handle.clone(),
+            })
+        }),
+    )
+    .unwrap()
+}
+
+#[test]
+fn read_reopens() {
+    let mut reader = provide_reader();
+    let mut buf = [0; 1];
+    assert_eq!(1, reader.read(&mut buf).unwrap());
+    assert_eq!(b'h', buf[0]);
+    // Reopened, starts over.
+    assert_eq!(1, reader.read(&mut buf).unwrap());
+    assert_eq!(b'h', buf[0]);
+}
+
+#[test]
+fn read_exact_not_interrupted() {
+    let mut reader = provide_reader();
+    let mut buf = [0; 3];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(b"hel", &buf);
+    let mut buf = [0; 10];
+    match reader.read_exact(&mut buf) {
+        Err(ReadExactError::UnexpectedEnd) => (),
+        _ => panic!("Should have hit the end"),
+    }
+}
+
+#[test]
+fn write_and_errors() {
+    let history = Arc::new(Mutex::new(Vec::new()));
+    let fail = Arc::new(Mutex::new(false));
+    let mut writer = Reopen::new(Box::new({
+        let history = Arc::clone(&history);
+        let fail = Arc::clone(&fail);
+        move || {
+            if *fail.lock().unwrap() {
+                return Err(Error::new(ErrorKind::NotFound, "Not there"));
+            }
+            history.lock().unwrap().push(Vec::new());
+            Ok(Sink {
+                history: Arc::clone(&history),
+                limit: 5,
+            })
+        }
+    }))
+    .unwrap();
+
+    writer.write_all(b"hello").unwrap();
+    match writer.write_all(b"!") {
+        Err(GenioError::Fd(ErrorKind::WriteZero)) => (),
+        other => panic!("Unexpected {:?}", other),
+    }
+
+    writer.handle().reopen();
+    *fail.lock().unwrap() = true;
+    match writer.write(b"world") {
+        Err(GenioError::Open(e)) => assert_eq!(ErrorKind::NotFound, e.kind()),
+        other => panic!("Unexpected {:?}", other),
+    }
+
+    *fail.lock().unwrap() = false;
+    assert_eq!(5, writer.write(b"world").unwrap());
+    writer.flush().unwrap();
+    assert_eq!(
+        vec![b"hello".to_vec(), b"world".to_vec()],
+        *history.lock().unwrap()
+    );
+}
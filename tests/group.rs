@@ -0,0 +1,75 @@
+//! Tests of the `ReopenGroup`.
+#![cfg(unix)]
+
+use std::fs;
+use std::io::Write;
+use std::sync::Barrier;
+use std::thread;
+
+use reopen::{Reopen, ReopenGroup};
+use tempfile::TempDir;
+
+const THREADS: usize = 4;
+const ROUNDS: usize = 100;
+
+fn check_lines(content: &str, phase: &str) {
+    let lines = content.lines().collect::<Vec<_>>();
+    assert_eq!(THREADS * ROUNDS, lines.len());
+    for t in 0..THREADS {
+        let expected = format!("{}-{}", phase, t);
+        assert_eq!(ROUNDS, lines.iter().filter(|l| **l == expected).count());
+    }
+}
+
+/// Members write from several threads, the master gets rotated in between.
+#[test]
+fn rotate_under_members() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let rotated = dir.path().join("log.1");
+    let group = ReopenGroup::new(Reopen::append_to(&path).unwrap());
+    let barrier = Barrier::new(THREADS + 1);
+
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let mut member = group.member().unwrap();
+            let barrier = &barrier;
+            s.spawn(move || {
+                // Note: writeln! would split it into multiple writes.
+                let line = format!("before-{}\n", t);
+                for _ in 0..ROUNDS {
+                    member.write_all(line.as_bytes()).unwrap();
+                }
+                barrier.wait();
+                // Rotating here
+                barrier.wait();
+                let line = format!("after-{}\n", t);
+                for _ in 0..ROUNDS {
+                    member.write_all(line.as_bytes()).unwrap();
+                }
+            });
+        }
+        barrier.wait();
+        fs::rename(&path, &rotated).unwrap();
+        group.handle().reopen();
+        barrier.wait();
+    });
+
+    check_lines(&fs::read_to_string(&rotated).unwrap(), "before");
+    check_lines(&fs::read_to_string(&path).unwrap(), "after");
+}
+
+/// A member created after a reopen request gets the new file right away.
+#[test]
+fn late_member() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let rotated = dir.path().join("log.1");
+    let group = ReopenGroup::new(Reopen::append_to(&path).unwrap());
+    fs::rename(&path, &rotated).unwrap();
+    group.handle().reopen();
+    let mut member = group.member().unwrap();
+    writeln!(member, "hello").unwrap();
+    assert_eq!("", fs::read_to_string(&rotated).unwrap());
+    assert_eq!("hello\n", fs::read_to_string(&path).unwrap());
+}
@@ -0,0 +1,71 @@
+//! Tests of reopening multiple things at once through a `HandleGroup`.
+
+use std::io::{Cursor, Read, Write};
+
+use reopen::test_util::{CountingConstructor, HistorySink};
+use reopen::{HandleGroup, Reopen};
+
+#[test]
+fn reopen_all() {
+    let sink = HistorySink::new();
+    let writer_constructor = CountingConstructor::new(sink.constructor());
+    let mut writer = Reopen::new(Box::new(writer_constructor.constructor())).unwrap();
+    let reader_constructor = CountingConstructor::new(|| Ok(Cursor::new(b"hello")));
+    let mut reader = Reopen::new(Box::new(reader_constructor.constructor())).unwrap();
+    let vec_constructor = CountingConstructor::new(|| Ok(Vec::<u8>::new()));
+    let mut vec = Reopen::new(Box::new(vec_constructor.constructor())).unwrap();
+
+    let group = vec![writer.handle(), reader.handle(), vec.handle()]
+        .into_iter()
+        .collect::<HandleGroup>();
+    assert_eq!(3, group.len());
+
+    let mut buf = [0; 3];
+    writer.write_all(b"a").unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    vec.write_all(b"a").unwrap();
+
+    group.reopen_all();
+    // Nothing happens until the next operation.
+    assert_eq!(1, writer_constructor.opens());
+    assert_eq!(1, reader_constructor.opens());
+    assert_eq!(1, vec_constructor.opens());
+
+    writer.write_all(b"b").unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    vec.write_all(b"b").unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], sink.history());
+    assert_eq!(b"hel", &buf);
+    assert_eq!(b"b", &vec.lock().unwrap()[..]);
+    assert_eq!(2, writer_constructor.opens());
+    assert_eq!(2, reader_constructor.opens());
+    assert_eq!(2, vec_constructor.opens());
+}
+
+#[cfg(all(feature = "signals", unix))]
+#[test]
+fn register_signal_all() {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use reopen::signals::consts::SIGUSR2;
+    use reopen::signals::Signal;
+
+    let first = CountingConstructor::new(|| Ok(Vec::<u8>::new()));
+    let mut first_reopen = Reopen::new(Box::new(first.constructor())).unwrap();
+    let second = CountingConstructor::new(|| Ok(Cursor::new(Vec::<u8>::new())));
+    let mut second_reopen = Reopen::new(Box::new(second.constructor())).unwrap();
+    let mut group = HandleGroup::new();
+    group.add(first_reopen.handle());
+    group.add(second_reopen.handle());
+    assert_eq!(2, group.register_signal_all(Signal::Usr2).unwrap().len());
+
+    signal_hook::low_level::raise(SIGUSR2).unwrap();
+    let start = Instant::now();
+    while first.opens() < 2 || second.opens() < 2 {
+        assert!(start.elapsed() < Duration::from_secs(5), "Not reopened");
+        thread::sleep(Duration::from_millis(10));
+        first_reopen.flush().unwrap();
+        second_reopen.flush().unwrap();
+    }
+}
@@ -0,0 +1,59 @@
+//! Tests of the headers and footers written to each FD.
+
+use std::io::{Error, ErrorKind, Write};
+
+use reopen::test_util::{HistorySink, HistoryWriter};
+use reopen::Reopen;
+
+fn provide_writer() -> (Reopen<HistoryWriter>, HistorySink) {
+    let sink = HistorySink::new();
+    let reopen = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .with_header("header\n")
+        .with_footer("footer\n");
+    (reopen, sink)
+}
+
+#[test]
+fn header_and_footer() {
+    let (mut writer, history) = provide_writer();
+    writer.write_all(b"first\n").unwrap();
+    writer.handle().reopen();
+    writer.write_all(b"second\n").unwrap();
+    writer.write_all(b"third\n").unwrap();
+    drop(writer);
+
+    let history = history.history();
+    assert_eq!(2, history.len());
+    assert_eq!(b"header\nfirst\nfooter\n", &history[0][..]);
+    assert_eq!(b"header\nsecond\nthird\nfooter\n", &history[1][..]);
+}
+
+/// The header is written even before the first operation that doesn't write anything itself.
+#[test]
+fn header_on_flush() {
+    let (mut writer, history) = provide_writer();
+    writer.flush().unwrap();
+    assert_eq!(b"header\n", &history.history()[0][..]);
+}
+
+struct Refusing;
+
+impl Write for Refusing {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn write(&mut self, _: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(ErrorKind::PermissionDenied, "Refusing"))
+    }
+}
+
+/// Failure to write the header fails the operation.
+#[test]
+fn header_failed() {
+    let mut writer = Reopen::new(Box::new(|| Ok(Refusing)))
+        .unwrap()
+        .with_header("header\n");
+    let err = writer.write_all(b"data").unwrap_err();
+    assert_eq!(ErrorKind::PermissionDenied, err.kind());
+}
@@ -0,0 +1,86 @@
+//! Tests of the health snapshot.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reopen::{Clock, ManualClock, Reopen};
+
+#[test]
+fn snapshot_follows_state() {
+    let fail = Arc::new(AtomicBool::new(false));
+    let mut writer = Reopen::new(Box::new({
+        let fail = Arc::clone(&fail);
+        move || {
+            if fail.load(Ordering::Relaxed) {
+                Err(Error::new(ErrorKind::NotFound, "Not there"))
+            } else {
+                Ok(Vec::<u8>::new())
+            }
+        }
+    }))
+    .unwrap()
+    .with_label("health");
+    let handle = writer.handle();
+
+    writer.write_all(b"Hello").unwrap();
+    writer.write_all(b" world").unwrap();
+    let health = writer.health();
+    assert!(health.open);
+    assert!(!health.pending);
+    assert_eq!(0, health.reopen_count);
+    assert_eq!(0, health.generation);
+    assert_eq!(None, health.last_error);
+    assert_eq!(11, health.bytes_written);
+    assert_eq!(Some("health".to_owned()), health.label);
+    let first_opened = health.opened_at.unwrap();
+
+    fail.store(true, Ordering::Relaxed);
+    handle.reopen();
+    writer.write_all(b"Lost").unwrap_err();
+    let health = writer.health();
+    assert!(!health.open);
+    assert!(health.pending);
+    assert_eq!(0, health.reopen_count);
+    assert_eq!(0, health.generation);
+    assert_eq!(Some("Not there".to_owned()), health.last_error);
+    assert_eq!(None, health.opened_at);
+
+    fail.store(false, Ordering::Relaxed);
+    writer.write_all(b"Again").unwrap();
+    let health = writer.health();
+    assert!(health.open);
+    assert!(!health.pending);
+    assert_eq!(1, health.reopen_count);
+    assert_eq!(1, health.generation);
+    assert_eq!(None, health.last_error);
+    assert_eq!(5, health.bytes_written);
+    assert!(health.opened_at.unwrap() >= first_opened);
+}
+
+/// The age of the FD comes from the clock.
+#[test]
+fn opened_at_by_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new())))
+        .unwrap()
+        .with_clock(clock.clone());
+    let first_opened = writer.health().opened_at.unwrap();
+    assert_eq!(clock.now(), first_opened);
+
+    clock.advance(Duration::from_secs(10));
+    writer.handle().reopen();
+    writer.write_all(b"Hello").unwrap();
+    let opened = writer.health().opened_at.unwrap();
+    assert_eq!(Duration::from_secs(10), opened - first_opened);
+}
+
+/// The formatted writes count into the written bytes too.
+#[test]
+fn formatted_bytes() {
+    let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+    write!(writer, "Hello {}", 42).unwrap();
+    writeln!(writer, "!").unwrap();
+    assert_eq!(10, writer.health().bytes_written);
+}
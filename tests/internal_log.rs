@@ -0,0 +1,99 @@
+//! Tests of the records logged with the `log` feature.
+#![cfg(feature = "log")]
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Once};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use reopen::Reopen;
+
+/// Collects the records of all the tests.
+struct Capture(Mutex<Vec<(Level, String, String)>>);
+
+impl Log for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+    fn log(&self, record: &Record<'_>) {
+        self.0.lock().unwrap().push((
+            record.level(),
+            record.target().to_owned(),
+            record.args().to_string(),
+        ));
+    }
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+static INIT: Once = Once::new();
+
+/// The records mentioning the label (each test uses its own, as they run in parallel).
+fn records(label: &str) -> Vec<(Level, String, String)> {
+    INIT.call_once(|| {
+        log::set_logger(&CAPTURE).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+    });
+    CAPTURE
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, _, msg)| msg.contains(label))
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn failed_open_warns() {
+    records("");
+    let opens = Arc::new(AtomicUsize::new(0));
+    let mut writer = Reopen::lazy(Box::new({
+        let opens = Arc::clone(&opens);
+        move || {
+            if opens.fetch_add(1, Ordering::Relaxed) == 0 {
+                Err(Error::new(ErrorKind::NotFound, "Not there"))
+            } else {
+                Ok(Vec::new())
+            }
+        }
+    }))
+    .with_label("failing");
+    writer.write_all(b"Hello").unwrap_err();
+    writer.write_all(b"Hello").unwrap();
+
+    let records = records("failing");
+    assert_eq!(2, records.len(), "{:?}", records);
+    let (level, target, msg) = &records[0];
+    assert_eq!(Level::Warn, *level);
+    assert_eq!("reopen", target);
+    assert!(msg.contains("Not there"), "{}", msg);
+    let (level, _, msg) = &records[1];
+    assert_eq!(Level::Debug, *level);
+    assert!(msg.contains("generation 1"), "{}", msg);
+}
+
+#[test]
+fn reopen_logged() {
+    records("");
+    let mut writer = Reopen::new(Box::new(|| Ok(Vec::new())))
+        .unwrap()
+        .with_label("reopened");
+    writer.handle().reopen();
+    writer.write_all(b"Hello").unwrap();
+    writer.handle().reopen();
+    writer.write_all(b"Hello").unwrap();
+
+    let records = records("reopened");
+    let messages = records
+        .iter()
+        .map(|(_, _, msg)| msg.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            "reopened: Reopened, generation 1",
+            "reopened: Reopened, generation 2",
+        ],
+        messages
+    );
+}
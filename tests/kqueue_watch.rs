@@ -0,0 +1,69 @@
+//! Tests of watching the path on macOS and the BSDs.
+#![cfg(all(
+    feature = "watch",
+    any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "dragonfly"
+    )
+))]
+
+use std::fs;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+fn wait_pending(log: &Reopen<fs::File>) {
+    let start = Instant::now();
+    while !log.is_pending() {
+        assert!(start.elapsed() < Duration::from_secs(5), "Not noticed");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn reopen_on_rename() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let mut log = Reopen::append_to(&path).unwrap();
+    let _guard = log.handle().watch_path(&path).unwrap();
+    writeln!(log, "first").unwrap();
+    // Let the helper thread start watching.
+    thread::sleep(Duration::from_millis(200));
+
+    let rotated = path.with_extension("1");
+    fs::rename(&path, &rotated).unwrap();
+    wait_pending(&log);
+    writeln!(log, "second").unwrap();
+    // Re-armed on the new file.
+    thread::sleep(Duration::from_millis(300));
+
+    let rotated_2 = path.with_extension("2");
+    fs::rename(&path, &rotated_2).unwrap();
+    wait_pending(&log);
+    writeln!(log, "third").unwrap();
+
+    assert_eq!("first\n", fs::read_to_string(&rotated).unwrap());
+    assert_eq!("second\n", fs::read_to_string(&rotated_2).unwrap());
+    assert_eq!("third\n", fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn reopen_on_delete() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let mut log = Reopen::append_to(&path).unwrap();
+    let _guard = log.handle().watch_path(&path).unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    fs::remove_file(&path).unwrap();
+    wait_pending(&log);
+    writeln!(log, "after").unwrap();
+    assert_eq!("after\n", fs::read_to_string(&path).unwrap());
+}
@@ -0,0 +1,63 @@
+//! Tests of adopting the inherited descriptors.
+#![cfg(unix)]
+
+use std::env;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::io::FromRawFd;
+use std::process;
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+/// Creates a pipe, with the write end moved to the first free descriptor from 3 up.
+fn pipe() -> (File, usize) {
+    let mut fds = [0; 2];
+    assert_eq!(0, unsafe { libc::pipe(fds.as_mut_ptr()) });
+    let target = (3..)
+        .find(|&fd| unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1)
+        .unwrap();
+    assert_eq!(target, unsafe { libc::dup2(fds[1], target) });
+    unsafe { libc::close(fds[1]) };
+    let read = unsafe { File::from_raw_fd(fds[0]) };
+    (read, target as usize - 3)
+}
+
+// A single test, as it sets the environment variables.
+#[test]
+fn listen_fds() {
+    let (mut read, index) = pipe();
+    let mut names = vec!["other"; index];
+    names.push("log");
+    env::set_var("LISTEN_FDS", (index + 1).to_string());
+    env::set_var("LISTEN_FDNAMES", names.join(":"));
+
+    env::set_var("LISTEN_PID", (process::id() + 1).to_string());
+    let err = Reopen::from_listen_fds("log", Box::new(|| unreachable!())).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+
+    env::set_var("LISTEN_PID", process::id().to_string());
+    let err = Reopen::from_listen_fds("missing", Box::new(|| unreachable!())).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let mut log = Reopen::from_listen_fds("log", {
+        let path = path.clone();
+        Box::new(move || File::create(&path))
+    })
+    .unwrap();
+    let err = Reopen::from_listen_fds("log", Box::new(|| unreachable!())).unwrap_err();
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+
+    log.write_all(b"inherited").unwrap();
+    log.handle().reopen();
+    log.write_all(b"reopened").unwrap();
+    drop(log);
+
+    // Both the original and the duplicate are closed now.
+    let mut received = String::new();
+    read.read_to_string(&mut received).unwrap();
+    assert_eq!("inherited", received);
+    assert_eq!("reopened", fs::read_to_string(&path).unwrap());
+}
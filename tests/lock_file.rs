@@ -0,0 +1,122 @@
+//! Tests of coordinating the reopens by a lock file.
+#![cfg(unix)]
+
+use std::cell::Cell;
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+fn file_id(meta: &Metadata) -> (u64, u64) {
+    (meta.dev(), meta.ino())
+}
+
+/// Both writers start with the same file, only the reopens rotate.
+///
+/// The file is rotated only if it is still the one the writer opened the last time, otherwise
+/// someone else already rotated it and the fresh file is just opened.
+fn writer(path: &Path, lock: &Path) -> Reopen<File> {
+    let initial = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap();
+    let last = Cell::new(Some(file_id(&initial.metadata().unwrap())));
+    let path = path.to_owned();
+    Reopen::with_fd(
+        initial,
+        Box::new(move || {
+            let there = fs::metadata(&path).ok().map(|meta| file_id(&meta));
+            if there.is_some() && there == last.get() {
+                fs::rename(&path, path.with_extension("1"))?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            last.set(Some(file_id(&file.metadata()?)));
+            Ok(file)
+        }),
+    )
+    .with_lock_file(lock)
+}
+
+fn read_lines(path: PathBuf) -> Vec<String> {
+    let mut lines = fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines
+}
+
+/// The second one to reopen finds the file already rotated and writes into the fresh one.
+#[test]
+fn rotated_by_other() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let lock = dir.path().join("log.lock");
+    let mut a = writer(&path, &lock);
+    let mut b = writer(&path, &lock);
+
+    writeln!(a, "a first").unwrap();
+    writeln!(b, "b first").unwrap();
+    a.handle().reopen();
+    writeln!(a, "a second").unwrap();
+    b.handle().reopen();
+    writeln!(b, "b second").unwrap();
+
+    assert_eq!(
+        vec!["a first", "b first"],
+        read_lines(path.with_extension("1"))
+    );
+    assert_eq!(vec!["a second", "b second"], read_lines(path.clone()));
+    assert!(lock.exists());
+
+    // Next time, the one to come first rotates again.
+    b.handle().reopen();
+    writeln!(b, "b third").unwrap();
+    a.handle().reopen();
+    writeln!(a, "a third").unwrap();
+    assert_eq!(
+        vec!["a second", "b second"],
+        read_lines(path.with_extension("1"))
+    );
+    assert_eq!(vec!["a third", "b third"], read_lines(path));
+}
+
+/// Both reopen at once, in whatever order, but the file gets rotated only once.
+#[test]
+fn single_rotation() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let lock = dir.path().join("log.lock");
+    let barrier = Arc::new(Barrier::new(2));
+
+    let threads = ["a", "b"]
+        .iter()
+        .map(|name| {
+            let mut writer = writer(&path, &lock);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                writeln!(writer, "{} first", name).unwrap();
+                barrier.wait();
+                // Both got their line into the original file, now both reopen at once.
+                writer.handle().reopen();
+                writeln!(writer, "{} second", name).unwrap();
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(
+        vec!["a first", "b first"],
+        read_lines(path.with_extension("1"))
+    );
+    assert_eq!(vec!["a second", "b second"], read_lines(path));
+}
@@ -1,50 +1,21 @@
 //! Test the feature that most operations (like `read_exact`) are not interruptible by a reopen.
 
-use std::io::{Error, ErrorKind, Read, Write};
+use std::io::{ErrorKind, Read, Write};
 use std::iter;
-use std::sync::{Arc, Mutex};
 
 use partial_io::{PartialOp, PartialRead, PartialWrite};
+use reopen::test_util::{HistorySink, HistoryWriter, ReopenAfterEachOp};
 use reopen::{Handle, Reopen};
 
-/// Request reopen after each operation. That way we can check the reopen doesn't happen in the
-/// middle of something.
-struct RequestReopen<FD> {
-    handle: Handle,
-    fd: FD,
-}
-
-impl<FD: Read> Read for RequestReopen<FD> {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
-        let result = self.fd.read(buf);
-        self.handle.reopen();
-        result
-    }
-}
-
-impl<FD: Write> Write for RequestReopen<FD> {
-    fn flush(&mut self) -> Result<(), Error> {
-        self.fd.flush()
-    }
-    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
-        let result = self.fd.write(buf);
-        self.handle.reopen();
-        result
-    }
-}
-
 // Get a reader that has bunch of data in it, but chunks it by a single byte.
-fn provide_reader() -> Reopen<RequestReopen<PartialRead<&'static [u8]>>> {
+fn provide_reader() -> Reopen<ReopenAfterEachOp<PartialRead<&'static [u8]>>> {
     let handle = Handle::stub();
     Reopen::with_handle(
         handle.clone(),
         Box::new(move || {
             let data = b"hello" as &[u8];
             let partial = PartialRead::new(data, iter::repeat(PartialOp::Limited(1)));
-            Ok(RequestReopen {
-                fd: partial,
-                handle: handle.clone(),
-            })
+            Ok(ReopenAfterEachOp::new(handle.clone(), partial))
         }),
     )
     .unwrap()
@@ -121,46 +92,26 @@ fn read_to_end() {
     assert_eq!(b"hellohello", &buf[..]);
 }
 
-// Note: we are single threaded, but something wants Send, so we just comply.
-type History = Arc<Mutex<Vec<Vec<u8>>>>;
-
-struct HistoryWriter {
-    /// History of all the previous writers. Oldest first.
-    history: History,
-}
-
-impl Write for HistoryWriter {
-    fn flush(&mut self) -> Result<(), Error> {
-        self.history.lock().unwrap().last_mut().unwrap().flush()
-    }
-    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
-        self.history.lock().unwrap().last_mut().unwrap().write(buf)
-    }
-}
-
-fn provide_writer() -> (Reopen<RequestReopen<PartialWrite<HistoryWriter>>>, History) {
-    let history = History::default();
+fn provide_writer() -> (
+    Reopen<ReopenAfterEachOp<PartialWrite<HistoryWriter>>>,
+    HistorySink,
+) {
+    let sink = HistorySink::new();
 
     let handle = Handle::stub();
     let reopen = Reopen::with_handle(
         handle.clone(),
         Box::new({
-            let history = Arc::clone(&history);
+            let sink = sink.clone();
             move || {
-                let history = Arc::clone(&history);
-                history.lock().unwrap().push(Vec::new());
-                let writer = HistoryWriter { history };
-                let partial = PartialWrite::new(writer, iter::repeat(PartialOp::Limited(1)));
-                Ok(RequestReopen {
-                    fd: partial,
-                    handle: handle.clone(),
-                })
+                let partial = PartialWrite::new(sink.open()?, iter::repeat(PartialOp::Limited(1)));
+                Ok(ReopenAfterEachOp::new(handle.clone(), partial))
             }
         }),
     )
     .unwrap();
 
-    (reopen, history)
+    (reopen, sink)
 }
 
 #[test]
@@ -168,14 +119,14 @@ fn write_sanity_check() {
     let (mut writer, history) = provide_writer();
     assert_eq!(1, writer.write(b"hello").unwrap());
     assert_eq!(1, writer.write(b"hello").unwrap());
-    assert_eq!(2, history.lock().unwrap().len());
+    assert_eq!(2, history.incarnations());
 }
 
 #[test]
 fn write_all() {
     let (mut writer, history) = provide_writer();
     writer.write_all(b"hello").unwrap();
-    let history = history.lock().unwrap();
+    let history = history.history();
     assert_eq!(1, history.len());
     assert_eq!(b"hello", &history[0][..]);
 }
@@ -186,7 +137,7 @@ fn write_explicit() {
     let lock = writer.lock().unwrap();
     assert_eq!(1, lock.write(b"a").unwrap());
     assert_eq!(1, lock.write(b"b").unwrap());
-    let history = history.lock().unwrap();
+    let history = history.history();
     assert_eq!(1, history.len());
     assert_eq!(b"ab", &history[0][..]);
 }
@@ -196,7 +147,32 @@ fn write_explicit() {
 fn format() {
     let (mut writer, history) = provide_writer();
     write!(&mut writer, "Hello {}", 42).unwrap();
-    let history = history.lock().unwrap();
+    let history = history.history();
     assert_eq!(1, history.len());
     assert_eq!(b"Hello 42", &history[0][..]);
 }
+
+/// A handle obtained late still works, even though nothing could request a reopen before.
+#[test]
+fn late_handle() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    writer.write_all(b"a").unwrap();
+    writer.write_all(b"b").unwrap();
+    let handle = writer.handle();
+    handle.reopen();
+    writer.write_all(b"c").unwrap();
+    assert_eq!(vec![b"ab".to_vec(), b"c".to_vec()], sink.history());
+}
+
+/// A handle passed in from the outside works right away.
+#[test]
+fn stub_handle() {
+    let sink = HistorySink::new();
+    let handle = Handle::stub();
+    let mut writer = Reopen::with_handle(handle.clone(), Box::new(sink.constructor())).unwrap();
+    writer.write_all(b"a").unwrap();
+    handle.reopen();
+    writer.write_all(b"b").unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], sink.history());
+}
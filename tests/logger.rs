@@ -0,0 +1,73 @@
+//! Tests of the minimal logger.
+#![cfg(feature = "logger")]
+
+use log::{debug, info, warn, Level, LevelFilter, Log, Record};
+use reopen::logger::RotatingLogger;
+use reopen::test_util::HistorySink;
+use reopen::Reopen;
+
+/// Checks the line looks like `<secs>.<millis> <rest>`.
+fn check_line(line: &str, rest: &str) {
+    let (timestamp, tail) = line.split_at(line.find(' ').unwrap());
+    let (secs, millis) = timestamp.split_at(timestamp.find('.').unwrap());
+    assert!(secs.parse::<u64>().unwrap() > 0);
+    assert_eq!(4, millis.len());
+    assert!(millis[1..].parse::<u16>().is_ok());
+    assert_eq!(rest, &tail[1..]);
+}
+
+fn lines(data: &[u8]) -> Vec<String> {
+    String::from_utf8(data.to_vec())
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+// A single test installing the logger, as there can be only one.
+#[test]
+fn log_and_rotate() {
+    let sink = HistorySink::new();
+    let reopen = Reopen::new(Box::new(sink.constructor())).unwrap();
+    let handle = reopen::logger::init(reopen, LevelFilter::Info).unwrap();
+
+    info!("first");
+    debug!("filtered out");
+    handle.reopen();
+    warn!("second {}", 2);
+    log::logger().flush();
+
+    let another = Reopen::new(Box::new(|| Ok(Vec::new()))).unwrap();
+    assert!(reopen::logger::init(another, LevelFilter::Info).is_err());
+
+    let history = sink.history();
+    assert_eq!(2, history.len());
+    let first = lines(&history[0]);
+    assert_eq!(1, first.len());
+    check_line(&first[0], "INFO logger: first");
+    let second = lines(&history[1]);
+    assert_eq!(1, second.len());
+    check_line(&second[0], "WARN logger: second 2");
+}
+
+/// The logger can be used without installing it globally.
+#[test]
+fn filters_by_level() {
+    let sink = HistorySink::new();
+    let reopen = Reopen::new(Box::new(sink.constructor())).unwrap();
+    let logger = RotatingLogger::new(reopen, LevelFilter::Warn);
+    for (level, msg) in &[(Level::Info, "filtered out"), (Level::Error, "kept")] {
+        logger.log(
+            &Record::builder()
+                .level(*level)
+                .target("direct")
+                .args(format_args!("{}", msg))
+                .build(),
+        );
+    }
+
+    let history = sink.history();
+    let written = lines(&history[0]);
+    assert_eq!(1, written.len());
+    check_line(&written[0], "ERROR direct: kept");
+}
@@ -0,0 +1,37 @@
+//! The logger of this crate doesn't deadlock on the records about its own [`Reopen`].
+#![cfg(feature = "logger")]
+
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use log::{info, LevelFilter};
+use reopen::test_util::HistorySink;
+use reopen::Reopen;
+
+#[test]
+fn failing_open_inside_logger() {
+    let sink = HistorySink::new();
+    let opens = Arc::new(AtomicUsize::new(0));
+    let reopen = Reopen::lazy(Box::new({
+        let sink = sink.clone();
+        let opens = Arc::clone(&opens);
+        move || {
+            if opens.fetch_add(1, Ordering::Relaxed) == 0 {
+                Err(Error::new(ErrorKind::NotFound, "Not there"))
+            } else {
+                sink.open()
+            }
+        }
+    }));
+    reopen::logger::init(reopen, LevelFilter::Debug).unwrap();
+
+    // The open fails inside the logger, the warning about it is muted instead of deadlocking.
+    info!("Lost");
+    info!("Written");
+
+    let text = String::from_utf8(sink.history().concat()).unwrap();
+    assert!(!text.contains("Lost"), "{}", text);
+    assert!(text.contains("Written"), "{}", text);
+    assert!(!text.contains("Reopened"), "{}", text);
+}
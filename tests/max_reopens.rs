@@ -0,0 +1,78 @@
+//! Tests of the limit on the number of reopens.
+
+use std::io::{ErrorKind, Write};
+
+use reopen::test_util::{CountingConstructor, HistorySink};
+use reopen::Reopen;
+
+#[test]
+fn limit_and_reset() {
+    let sink = HistorySink::new();
+    let counting = CountingConstructor::new(sink.constructor());
+    let mut writer = Reopen::new(Box::new(counting.constructor()))
+        .unwrap()
+        .with_max_reopens(2);
+    let handle = writer.handle();
+    for _ in 0..2 {
+        handle.reopen();
+        writer.write_all(b"a").unwrap();
+    }
+    assert_eq!(3, counting.opens());
+
+    handle.reopen();
+    for _ in 0..2 {
+        let err = writer.write_all(b"b").unwrap_err();
+        assert_eq!(ErrorKind::Other, err.kind());
+        assert_eq!("reopen limit exceeded", err.to_string());
+    }
+    assert_eq!(3, counting.opens());
+
+    // The pending request gets satisfied after the reset.
+    writer.reset();
+    writer.write_all(b"c").unwrap();
+    assert_eq!(4, counting.opens());
+    assert_eq!(
+        vec![Vec::new(), b"a".to_vec(), b"a".to_vec(), b"c".to_vec()],
+        sink.history()
+    );
+}
+
+/// Writes keep going as long as nobody asks for a reopen.
+#[test]
+fn limit_without_requests() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .with_max_reopens(0);
+    writer.write_all(b"a").unwrap();
+    writer.write_all(b"b").unwrap();
+    writer.handle().reopen();
+    assert!(writer.write_all(b"c").is_err());
+    assert_eq!(vec![b"ab".to_vec()], sink.history());
+}
+
+#[test]
+fn poisoned_and_reset() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .with_max_reopens(1);
+    writer.handle().reopen();
+    writer.write_all(b"a").unwrap();
+    assert!(!writer.is_poisoned());
+    assert_eq!(None, writer.poison_reason());
+
+    writer.handle().reopen();
+    assert!(writer.is_poisoned());
+    let err = writer.write_all(b"-").unwrap_err();
+    assert_eq!(Some(err.to_string().as_str()), writer.poison_reason());
+
+    writer.reset();
+    assert!(!writer.is_poisoned());
+    writer.write_all(b"b").unwrap();
+    assert!(!writer.is_poisoned());
+    assert_eq!(
+        vec![Vec::new(), b"a".to_vec(), b"b".to_vec()],
+        sink.history()
+    );
+}
@@ -0,0 +1,136 @@
+//! Tests of the metrics export.
+#![cfg(feature = "metrics")]
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use metrics::{
+    Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+use reopen::Reopen;
+
+/// Counts the values recorded into a histogram.
+#[derive(Default)]
+struct Samples(AtomicU64);
+
+impl HistogramFn for Samples {
+    fn record(&self, _: f64) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Keeps all the metrics so they can be checked.
+#[derive(Default)]
+struct Capture {
+    counters: Mutex<HashMap<Key, Arc<AtomicU64>>>,
+    gauges: Mutex<HashMap<Key, Arc<AtomicU64>>>,
+    histograms: Mutex<HashMap<Key, Arc<Samples>>>,
+}
+
+/// Finds the metric with the given name, labeled as `test`.
+fn find<V: Clone>(metrics: &Mutex<HashMap<Key, V>>, name: &str) -> V {
+    let metrics = metrics.lock().unwrap();
+    let (_, value) = metrics
+        .iter()
+        .find(|(key, _)| {
+            let labels = key.labels().collect::<Vec<_>>();
+            key.name() == name
+                && labels.len() == 1
+                && labels[0].key() == "label"
+                && labels[0].value() == "test"
+        })
+        .unwrap();
+    value.clone()
+}
+
+impl Capture {
+    /// Value of the counter with the given name.
+    fn get(&self, name: &str) -> u64 {
+        find(&self.counters, name).load(Ordering::Relaxed)
+    }
+
+    /// Value of the gauge with the given name.
+    fn gauge(&self, name: &str) -> f64 {
+        f64::from_bits(find(&self.gauges, name).load(Ordering::Relaxed))
+    }
+
+    /// Number of values recorded into the histogram with the given name.
+    fn samples(&self, name: &str) -> u64 {
+        find(&self.histograms, name).0.load(Ordering::Relaxed)
+    }
+}
+
+impl Recorder for Capture {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        let counter = Arc::clone(
+            self.counters
+                .lock()
+                .unwrap()
+                .entry(key.clone())
+                .or_default(),
+        );
+        Counter::from_arc(counter)
+    }
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        let gauge = Arc::clone(self.gauges.lock().unwrap().entry(key.clone()).or_default());
+        Gauge::from_arc(gauge)
+    }
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        let histogram = Arc::clone(
+            self.histograms
+                .lock()
+                .unwrap()
+                .entry(key.clone())
+                .or_default(),
+        );
+        Histogram::from_arc(histogram)
+    }
+}
+
+#[test]
+fn counters_advance() {
+    let capture = Capture::default();
+    let fail = Arc::new(AtomicBool::new(false));
+    let mut writer = metrics::with_local_recorder(&capture, || {
+        let fail = Arc::clone(&fail);
+        Reopen::new(Box::new(move || {
+            if fail.load(Ordering::Relaxed) {
+                Err(Error::new(ErrorKind::NotFound, "Missing"))
+            } else {
+                Ok(Vec::<u8>::new())
+            }
+        }))
+        .unwrap()
+        .with_label("test")
+    });
+
+    writer.write_all(b"hello").unwrap();
+    write!(writer, "{}", 42).unwrap();
+    assert_eq!(7, capture.get("reopen.bytes_written_total"));
+    assert_eq!(0, capture.get("reopen.reopens_total"));
+
+    assert_eq!(0, capture.get("reopen.requests_total"));
+    assert_eq!(0.0, capture.gauge("reopen.pending"));
+
+    fail.store(true, Ordering::Relaxed);
+    writer.handle().reopen();
+    assert!(writer.write_all(b"hello").is_err());
+    assert!(writer.write_all(b"hello").is_err());
+    assert_eq!(2, capture.get("reopen.open_failures_total"));
+    // Retried, but still the same request.
+    assert_eq!(1, capture.get("reopen.requests_total"));
+    assert_eq!(1.0, capture.gauge("reopen.pending"));
+
+    fail.store(false, Ordering::Relaxed);
+    assert_eq!(5, writer.write(b"hello").unwrap());
+    assert_eq!(1, capture.get("reopen.reopens_total"));
+    assert_eq!(12, capture.get("reopen.bytes_written_total"));
+    assert_eq!(1, capture.get("reopen.requests_total"));
+    assert_eq!(0.0, capture.gauge("reopen.pending"));
+    assert_eq!(3, capture.samples("reopen.open_duration_seconds"));
+}
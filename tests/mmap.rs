@@ -0,0 +1,76 @@
+//! Tests of writing into memory-mapped files.
+#![cfg(feature = "mmap")]
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use reopen::mmap::{mmap_file, MmapWriter};
+use reopen::Reopen;
+use tempfile::TempDir;
+
+const SIZE: usize = 16;
+
+fn provide_writer(dir: &TempDir) -> Reopen<MmapWriter> {
+    let base = dir.path().to_path_buf();
+    let counter = AtomicUsize::new(0);
+    mmap_file(
+        move || base.join(format!("log.{}", counter.fetch_add(1, Ordering::Relaxed))),
+        SIZE,
+    )
+    .unwrap()
+}
+
+fn content(dir: &TempDir, idx: usize) -> Vec<u8> {
+    fs::read(dir.path().join(format!("log.{}", idx))).unwrap()
+}
+
+fn padded(data: &[u8]) -> Vec<u8> {
+    let mut data = data.to_vec();
+    data.resize(SIZE, 0);
+    data
+}
+
+/// The records that don't fit go whole into the next file.
+#[test]
+fn rotate_twice() {
+    let dir = TempDir::new().unwrap();
+    let mut writer = provide_writer(&dir);
+    for record in &[b"aaaaa\n", b"bbbbb\n", b"ccccc\n", b"ddddd\n", b"eeeee\n"] {
+        writer.write_record(&record[..]).unwrap();
+    }
+    drop(writer);
+
+    assert_eq!(padded(b"aaaaa\nbbbbb\n"), content(&dir, 0));
+    assert_eq!(padded(b"ccccc\nddddd\n"), content(&dir, 1));
+    assert_eq!(padded(b"eeeee\n"), content(&dir, 2));
+    assert!(!dir.path().join("log.3").exists());
+}
+
+/// Filling the file exactly switches to the next one on the next write.
+#[test]
+fn exactly_full() {
+    let dir = TempDir::new().unwrap();
+    let mut writer = provide_writer(&dir);
+    writer.write_all(&[b'a'; SIZE]).unwrap();
+    assert!(!dir.path().join("log.1").exists());
+    writer.write_all(b"b").unwrap();
+    assert_eq!(1, writer.lock().unwrap().position());
+    drop(writer);
+
+    assert_eq!(vec![b'a'; SIZE], content(&dir, 0));
+    assert_eq!(padded(b"b"), content(&dir, 1));
+}
+
+#[test]
+fn no_space_for_record() {
+    let dir = TempDir::new().unwrap();
+    let path: PathBuf = dir.path().join("log");
+    let mut writer = MmapWriter::create(&path, SIZE).unwrap();
+    writer.write_all(b"Hello").unwrap();
+    assert_eq!(0, writer.write(&[b'x'; SIZE]).unwrap());
+    writer.flush().unwrap();
+    assert_eq!(padded(b"Hello"), fs::read(&path).unwrap());
+    assert!(MmapWriter::create(&path, 0).is_err());
+}
@@ -0,0 +1,33 @@
+//! Tests of creating a Reopen that tolerates the first open failing.
+
+use std::io::{ErrorKind, Write};
+
+use reopen::test_util::{FailN, HistorySink};
+use reopen::Reopen;
+
+#[test]
+fn opens_right_away() {
+    let sink = HistorySink::new();
+    let (mut writer, error) = Reopen::new_or_lazy(Box::new(sink.constructor()));
+    assert!(error.is_none());
+    assert_eq!(1, sink.incarnations());
+    writer.write_all(b"Hello").unwrap();
+    assert_eq!(vec![b"Hello".to_vec()], sink.history());
+}
+
+#[test]
+fn initial_failure() {
+    let sink = HistorySink::new();
+    let fail = FailN::new(2, sink.constructor());
+    let (mut writer, error) = Reopen::new_or_lazy(Box::new(fail.constructor()));
+    assert_eq!(ErrorKind::NotFound, error.unwrap().kind());
+    let health = writer.health();
+    assert!(!health.open);
+    assert_eq!(Some("Failing on purpose".to_owned()), health.last_error);
+
+    // Still failing, then usable.
+    assert!(writer.write_all(b"lost").is_err());
+    writer.write_all(b"Hello").unwrap();
+    assert_eq!(vec![b"Hello".to_vec()], sink.history());
+    assert!(writer.health().last_error.is_none());
+}
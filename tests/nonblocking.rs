@@ -0,0 +1,45 @@
+//! Tests of the `set_nonblocking` mode.
+
+use std::io::{ErrorKind, Read};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use reopen::Reopen;
+
+/// Checks the FD is nonblocking by reading with no data available.
+fn would_block(conn: &mut Reopen<TcpStream>) -> bool {
+    let mut buf = [0; 1];
+    match conn.read_exact(&mut buf) {
+        Err(e) => e.kind() == ErrorKind::WouldBlock,
+        Ok(()) => false,
+    }
+}
+
+#[test]
+fn persists_over_reopen() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut conn = Reopen::new(Box::new(move || TcpStream::connect(addr))).unwrap();
+    // Keep the server sides open, not to get EOF
+    let _first = listener.accept().unwrap();
+
+    conn.set_nonblocking(true).unwrap();
+    assert!(would_block(&mut conn));
+
+    conn.handle().reopen();
+    assert!(would_block(&mut conn));
+    let _second = listener.accept().unwrap();
+
+    // Back to blocking, a read waits for the whole timeout.
+    conn.set_nonblocking(false).unwrap();
+    conn.handle().reopen();
+    let timeout = Duration::from_millis(50);
+    conn.lock()
+        .unwrap()
+        .set_read_timeout(Some(timeout))
+        .unwrap();
+    let _third = listener.accept().unwrap();
+    let start = Instant::now();
+    assert!(conn.read_exact(&mut [0; 1]).is_err());
+    assert!(start.elapsed() >= timeout);
+}
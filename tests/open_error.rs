@@ -0,0 +1,70 @@
+//! Tests of telling the open errors apart from the others.
+
+use std::io::{Error, ErrorKind, Write};
+
+use reopen::test_util::FailN;
+use reopen::{OpenError, Reopen};
+
+/// Accepts nothing.
+#[derive(Debug)]
+struct Full;
+
+impl Write for Full {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write(&mut self, _: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(ErrorKind::WriteZero, "Full"))
+    }
+}
+
+/// An application error, keeping the two apart.
+#[derive(Debug)]
+enum AppError {
+    SinkUnavailable(ErrorKind),
+    WriteFailed(ErrorKind),
+}
+
+impl From<OpenError> for AppError {
+    fn from(e: OpenError) -> Self {
+        AppError::SinkUnavailable(e.kind())
+    }
+}
+
+impl From<Error> for AppError {
+    fn from(e: Error) -> Self {
+        AppError::WriteFailed(e.kind())
+    }
+}
+
+fn log(writer: &mut Reopen<Full>) -> Result<(), AppError> {
+    writer.open_current()?.write_all(b"Hello")?;
+    Ok(())
+}
+
+#[test]
+fn open_then_write_failure() {
+    let fail = FailN::new(1, || Ok(Full));
+    let mut writer = Reopen::lazy(Box::new(fail.constructor()));
+
+    match log(&mut writer) {
+        Err(AppError::SinkUnavailable(ErrorKind::NotFound)) => (),
+        other => panic!("Unexpected {:?}", other),
+    }
+    match log(&mut writer) {
+        Err(AppError::WriteFailed(ErrorKind::WriteZero)) => (),
+        other => panic!("Unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn converts_back() {
+    let fail = FailN::new(1, || Ok(Full));
+    let mut writer = Reopen::lazy(Box::new(fail.constructor()));
+    let err = writer.open_current().unwrap_err();
+    assert_eq!("Failed to open: Failing on purpose", err.to_string());
+    let err: Error = err.into();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+    assert_eq!("Failing on purpose", err.to_string());
+}
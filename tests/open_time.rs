@@ -0,0 +1,57 @@
+//! Tests of measuring how long the opens take.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use reopen::{Clock, ManualClock, Reopen};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn measured_by_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let opens = Arc::new(AtomicUsize::new(0));
+    let mut writer = Reopen::lazy(Box::new({
+        let clock = Arc::clone(&clock);
+        let opens = Arc::clone(&opens);
+        move || {
+            // The first open is slow, the second one fails fast, the third one is in between.
+            let (took, result) = match opens.fetch_add(1, Ordering::Relaxed) {
+                0 => (ms(300), Ok(Vec::new())),
+                1 => (ms(10), Err(Error::new(ErrorKind::NotFound, "Not there"))),
+                _ => (ms(100), Ok(Vec::new())),
+            };
+            clock.sleep(took);
+            result
+        }
+    }))
+    .with_clock(Arc::clone(&clock) as Arc<_>);
+    assert_eq!(None, writer.last_open_duration());
+
+    writer.write_all(b"a").unwrap();
+    assert_eq!(Some(ms(300)), writer.last_open_duration());
+
+    writer.handle().reopen();
+    assert!(writer.write_all(b"b").is_err());
+    assert_eq!(Some(ms(10)), writer.last_open_duration());
+    writer.write_all(b"c").unwrap();
+    assert_eq!(Some(ms(100)), writer.last_open_duration());
+
+    assert_eq!(ms(410), writer.total_open_duration());
+    assert_eq!(ms(300), writer.max_open_duration());
+}
+
+#[test]
+fn real_sleep() {
+    let mut writer = Reopen::lazy(Box::new(|| {
+        thread::sleep(ms(50));
+        Ok(Vec::<u8>::new())
+    }));
+    writer.write_all(b"a").unwrap();
+    assert!(writer.last_open_duration().unwrap() >= ms(50));
+}
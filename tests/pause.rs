@@ -0,0 +1,47 @@
+//! Holding back the reopens with a paused handle.
+
+use std::io::{Error, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use reopen::Reopen;
+
+fn counted() -> (Reopen<Vec<u8>>, Arc<AtomicUsize>) {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let reopen = Reopen::new(Box::new({
+        let opened = Arc::clone(&opened);
+        move || {
+            opened.fetch_add(1, Ordering::Relaxed);
+            Ok::<_, Error>(Vec::new())
+        }
+    }))
+    .unwrap();
+    (reopen, opened)
+}
+
+/// The requests are merged while paused and handled only after the last resume.
+#[test]
+fn nested_pauses() {
+    let (mut writer, opened) = counted();
+    let handle = writer.handle();
+    handle.pause();
+    handle.pause();
+    handle.reopen();
+    handle.reopen();
+    writer.write_all(b"a").unwrap();
+    handle.resume();
+    writer.write_all(b"b").unwrap();
+    assert_eq!(1, opened.load(Ordering::Relaxed));
+    assert!(writer.is_pending());
+    handle.resume();
+    writer.write_all(b"c").unwrap();
+    assert_eq!(2, opened.load(Ordering::Relaxed));
+    assert_eq!(b"c", &writer.lock().unwrap()[..]);
+}
+
+#[test]
+#[should_panic(expected = "Resume without a pause")]
+fn resume_unpaused() {
+    let (writer, _) = counted();
+    writer.handle().resume();
+}
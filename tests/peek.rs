@@ -0,0 +1,71 @@
+//! Tests of reading the rest of the FD and reopening right after.
+
+use std::io::{Cursor, Error, ErrorKind, Read};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use reopen::Reopen;
+
+/// Each incarnation contains its sequence number, the opens fail while `fail` is set.
+fn provide_reader() -> (Reopen<Cursor<Vec<u8>>>, Arc<AtomicBool>) {
+    let fail = Arc::new(AtomicBool::new(false));
+    let opened = AtomicUsize::new(0);
+    let reader = Reopen::new(Box::new({
+        let fail = Arc::clone(&fail);
+        move || {
+            if fail.load(Ordering::Relaxed) {
+                return Err(Error::new(ErrorKind::NotFound, "Not there"));
+            }
+            let n = opened.fetch_add(1, Ordering::Relaxed);
+            Ok(Cursor::new(format!("file {}", n).into_bytes()))
+        }
+    }))
+    .unwrap();
+    (reader, fail)
+}
+
+#[test]
+fn tail_then_new() {
+    let (mut reader, _) = provide_reader();
+    let mut buf = [0; 5];
+    reader.read_exact(&mut buf).unwrap();
+    let mut tail = String::new();
+    reader
+        .peek_then_reopen(|fd| fd.read_to_string(&mut tail))
+        .unwrap();
+    assert_eq!("0", tail);
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!("file 1", rest);
+}
+
+/// A pending request doesn't get satisfied before the peek.
+#[test]
+fn no_reopen_before() {
+    let (mut reader, _) = provide_reader();
+    reader.handle().reopen();
+    let mut all = String::new();
+    reader
+        .peek_then_reopen(|fd| fd.read_to_string(&mut all))
+        .unwrap();
+    assert_eq!("file 0", all);
+    assert!(!reader.is_pending());
+}
+
+/// Failed reopen doesn't lose the data, the reopen is retried later.
+#[test]
+fn reopen_failed() {
+    let (mut reader, fail) = provide_reader();
+    fail.store(true, Ordering::Relaxed);
+    let mut all = String::new();
+    reader
+        .peek_then_reopen(|fd| fd.read_to_string(&mut all))
+        .unwrap();
+    assert_eq!("file 0", all);
+    assert!(reader.is_pending());
+    assert!(reader.read_to_string(&mut all).is_err());
+    fail.store(false, Ordering::Relaxed);
+    all.clear();
+    reader.read_to_string(&mut all).unwrap();
+    assert_eq!("file 1", all);
+}
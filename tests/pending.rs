@@ -0,0 +1,100 @@
+//! Tests of reopen requests staying pending until satisfied.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use reopen::test_util::{HistorySink, HistoryWriter};
+use reopen::{Handle, Reopen};
+
+/// Controls of the constructor of a writer.
+#[derive(Default)]
+struct Control {
+    /// Fail the opens.
+    fail: AtomicBool,
+    /// Request another reopen from within this many following opens.
+    request_inside: AtomicUsize,
+}
+
+fn provide_writer() -> (Reopen<HistoryWriter>, Handle, Arc<Control>, HistorySink) {
+    let sink = HistorySink::new();
+    let control = Arc::new(Control::default());
+    let handle = Handle::stub();
+    let writer = Reopen::with_handle(
+        handle.clone(),
+        Box::new({
+            let sink = sink.clone();
+            let control = Arc::clone(&control);
+            let handle = handle.clone();
+            move || {
+                let inside = control.request_inside.load(Ordering::Relaxed);
+                if inside > 0 {
+                    control.request_inside.store(inside - 1, Ordering::Relaxed);
+                    handle.reopen();
+                }
+                if control.fail.load(Ordering::Relaxed) {
+                    Err(Error::new(ErrorKind::NotFound, "Not there"))
+                } else {
+                    sink.open()
+                }
+            }
+        }),
+    )
+    .unwrap();
+    (writer, handle, control, sink)
+}
+
+#[test]
+fn failure_then_success() {
+    let (mut writer, handle, control, sink) = provide_writer();
+    writer.write_all(b"a").unwrap();
+    assert!(!writer.is_pending());
+
+    control.fail.store(true, Ordering::Relaxed);
+    handle.reopen();
+    assert!(writer.is_pending());
+    assert!(writer.write_all(b"b").is_err());
+    assert!(writer.is_pending());
+    assert!(writer.write_all(b"b").is_err());
+    assert!(writer.is_pending());
+
+    control.fail.store(false, Ordering::Relaxed);
+    writer.write_all(b"c").unwrap();
+    assert!(!writer.is_pending());
+    writer.write_all(b"d").unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"cd".to_vec()], sink.history());
+}
+
+/// A request that comes during a failed attempt is merged with the one being attempted.
+#[test]
+fn request_during_failed_attempt() {
+    let (mut writer, handle, control, sink) = provide_writer();
+    control.fail.store(true, Ordering::Relaxed);
+    control.request_inside.store(1, Ordering::Relaxed);
+    handle.reopen();
+    assert!(writer.write_all(b"a").is_err());
+    assert!(writer.is_pending());
+
+    control.fail.store(false, Ordering::Relaxed);
+    writer.write_all(b"b").unwrap();
+    assert!(!writer.is_pending());
+    writer.write_all(b"c").unwrap();
+    assert_eq!(vec![Vec::new(), b"bc".to_vec()], sink.history());
+}
+
+/// A request that comes during a successful attempt might have been too late for it, so it is
+/// satisfied by another reopen.
+#[test]
+fn request_during_successful_attempt() {
+    let (mut writer, handle, control, sink) = provide_writer();
+    control.request_inside.store(1, Ordering::Relaxed);
+    handle.reopen();
+    writer.write_all(b"a").unwrap();
+    assert!(writer.is_pending());
+    writer.write_all(b"b").unwrap();
+    assert!(!writer.is_pending());
+    assert_eq!(
+        vec![Vec::new(), b"a".to_vec(), b"b".to_vec()],
+        sink.history()
+    );
+}
@@ -0,0 +1,95 @@
+//! Tests of writing at positions in the file across reopens.
+
+use std::fs::{self, OpenOptions};
+use std::io::{Cursor, Error, Seek, SeekFrom, Write};
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+fn preallocated(dir: &TempDir) -> Reopen<std::fs::File> {
+    let path = dir.path().join("buffer");
+    fs::write(&path, [b'.'; 8]).unwrap();
+    Reopen::new(Box::new(move || {
+        OpenOptions::new().read(true).write(true).open(&path)
+    }))
+    .unwrap()
+}
+
+#[test]
+fn position_preserved() {
+    let dir = TempDir::new().unwrap();
+    let mut file = preallocated(&dir).preserve_position(true);
+    file.write_all(b"ab").unwrap();
+    file.handle().reopen();
+    file.write_all(b"cd").unwrap();
+    file.lock().unwrap().seek(SeekFrom::Start(6)).unwrap();
+    file.handle().reopen();
+    file.write_all(b"ef").unwrap();
+    file.flush().unwrap();
+    assert_eq!(
+        b"abcd..ef",
+        &fs::read(dir.path().join("buffer")).unwrap()[..]
+    );
+}
+
+#[test]
+fn position_reset_by_default() {
+    let dir = TempDir::new().unwrap();
+    let mut file = preallocated(&dir);
+    file.write_all(b"ab").unwrap();
+    file.handle().reopen();
+    file.write_all(b"cd").unwrap();
+    assert_eq!(
+        b"cd......",
+        &fs::read(dir.path().join("buffer")).unwrap()[..]
+    );
+}
+
+/// If the new FD can't be moved, the operation fails and the next one tries again.
+#[test]
+fn restore_failure() {
+    let mut file = Reopen::new(Box::new(|| Ok(Broken(Cursor::new(Vec::new())))))
+        .unwrap()
+        .preserve_position(true);
+    file.write_all(b"abc").unwrap();
+    file.handle().reopen();
+    assert!(file.write_all(b"x").is_err());
+    assert!(file.write_all(b"x").is_err());
+}
+
+/// Can tell where it is, but can't move anywhere.
+struct Broken(Cursor<Vec<u8>>);
+
+impl Seek for Broken {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Error> {
+        match pos {
+            SeekFrom::Current(0) => self.0.seek(pos),
+            _ => Err(Error::new(std::io::ErrorKind::Unsupported, "Can't seek")),
+        }
+    }
+}
+
+impl Write for Broken {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn positioned_io() {
+    let dir = TempDir::new().unwrap();
+    let mut file = preallocated(&dir);
+    file.write_at(b"xy", 3).unwrap();
+    file.handle().reopen();
+    file.write_at(b"z", 7).unwrap();
+    // The writes at positions didn't move the position in the file.
+    file.write_all(b"a").unwrap();
+    let mut buf = [0; 8];
+    assert_eq!(8, file.read_at(&mut buf, 0).unwrap());
+    assert_eq!(b"a..xy..z", &buf);
+}
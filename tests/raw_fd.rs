@@ -0,0 +1,62 @@
+//! Tests of `with_raw_fd`.
+#![cfg(unix)]
+
+use std::fs::OpenOptions;
+use std::io::Error;
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+fn flags(fd: libc::c_int) -> Result<libc::c_int, Error> {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags == -1 {
+        Err(Error::last_os_error())
+    } else {
+        Ok(flags)
+    }
+}
+
+/// The descriptor is the one of the current file.
+#[test]
+fn fcntl() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let mut log = Reopen::append_to(&path).unwrap();
+    let appending = log.with_raw_fd(flags).unwrap();
+    assert_ne!(0, appending & libc::O_APPEND);
+
+    let mut plain = Reopen::append_with(&path, {
+        let mut options = OpenOptions::new();
+        options.write(true);
+        options
+    })
+    .unwrap();
+    let not_appending = plain.with_raw_fd(flags).unwrap();
+    assert_eq!(0, not_appending & libc::O_APPEND);
+}
+
+/// A pending reopen happens before the closure, not during or after it.
+#[test]
+fn reopens_before() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let mut log = Reopen::append_to(&path).unwrap();
+    log.handle().reopen();
+    let fd = log
+        .with_raw_fd(|fd| {
+            flags(fd)?;
+            Ok(fd)
+        })
+        .unwrap();
+    assert!(!log.is_pending());
+    assert_eq!(fd, log.with_raw_fd(Ok).unwrap());
+}
+
+/// Errors from the closure are passed through.
+#[test]
+fn error() {
+    let dir = TempDir::new().unwrap();
+    let mut log = Reopen::append_to(dir.path().join("log")).unwrap();
+    let err = log.with_raw_fd(|_| flags(-1)).unwrap_err();
+    assert_eq!(Some(libc::EBADF), err.raw_os_error());
+}
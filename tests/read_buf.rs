@@ -0,0 +1,68 @@
+//! Tests of reading into uninitialized buffers (nightly only).
+#![cfg(read_buf)]
+#![feature(read_buf, core_io_borrowed_buf)]
+
+use std::io::{BorrowedBuf, ErrorKind, Read};
+use std::mem::MaybeUninit;
+
+use reopen::test_util::CountingConstructor;
+use reopen::{CheckTiming, Reopen};
+
+fn provide_reader() -> (
+    Reopen<&'static [u8]>,
+    CountingConstructor<fn() -> std::io::Result<&'static [u8]>>,
+) {
+    let counting = CountingConstructor::new((|| Ok(b"hello" as &[u8])) as fn() -> _);
+    let reader = Reopen::new(Box::new(counting.constructor())).unwrap();
+    (reader, counting)
+}
+
+#[test]
+fn read_buf() {
+    let (mut reader, counting) = provide_reader();
+    let mut storage = [MaybeUninit::uninit(); 3];
+    let mut buf = BorrowedBuf::from(&mut storage[..]);
+    reader.read_buf(buf.unfilled()).unwrap();
+    assert_eq!(b"hel", buf.filled());
+
+    // Reopened once at the start, then reads the new one from the beginning.
+    reader.handle().reopen();
+    let mut storage = [MaybeUninit::uninit(); 10];
+    let mut buf = BorrowedBuf::from(&mut storage[..]);
+    reader.read_buf(buf.unfilled()).unwrap();
+    assert_eq!(b"hello", buf.filled());
+    assert_eq!(2, counting.opens());
+}
+
+#[test]
+fn read_buf_exact() {
+    let (mut reader, counting) = provide_reader();
+    let mut storage = [MaybeUninit::uninit(); 4];
+    let mut buf = BorrowedBuf::from(&mut storage[..]);
+    reader.handle().reopen();
+    reader.read_buf_exact(buf.unfilled()).unwrap();
+    assert_eq!(b"hell", buf.filled());
+    assert_eq!(2, counting.opens());
+
+    let mut storage = [MaybeUninit::uninit(); 4];
+    let mut buf = BorrowedBuf::from(&mut storage[..]);
+    let err = reader.read_buf_exact(buf.unfilled()).unwrap_err();
+    assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+}
+
+/// The old FD is read to the end before continuing in the new one.
+#[test]
+fn read_buf_exact_after() {
+    let (reader, counting) = provide_reader();
+    let mut reader = reader.check_timing(CheckTiming::After);
+    let mut storage = [MaybeUninit::uninit(); 3];
+    let mut buf = BorrowedBuf::from(&mut storage[..]);
+    reader.read_buf_exact(buf.unfilled()).unwrap();
+    reader.handle().reopen();
+
+    let mut storage = [MaybeUninit::uninit(); 4];
+    let mut buf = BorrowedBuf::from(&mut storage[..]);
+    reader.read_buf_exact(buf.unfilled()).unwrap();
+    assert_eq!(b"lohe", buf.filled());
+    assert_eq!(2, counting.opens());
+}
@@ -0,0 +1,153 @@
+//! Tests of `write_record`.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::{Arc, Mutex};
+
+use reopen::Reopen;
+
+type History = Arc<Mutex<Vec<Vec<u8>>>>;
+
+/// Accepts at most `limit` bytes, then fails.
+struct Breaking {
+    limit: usize,
+    history: History,
+}
+
+impl Write for Breaking {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let mut history = self.history.lock().unwrap();
+        let current = history.last_mut().unwrap();
+        if current.len() >= self.limit {
+            return Err(Error::new(ErrorKind::BrokenPipe, "Broken"));
+        }
+        let len = buf.len().min(self.limit - current.len()).min(2);
+        current.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Each FD breaks after the corresponding limit, the ones past the list accept anything.
+fn provide_writer(limits: Vec<usize>) -> (Reopen<Breaking>, History) {
+    let history = History::default();
+    let limits = Mutex::new(limits.into_iter());
+    let reopen = Reopen::new(Box::new({
+        let history = Arc::clone(&history);
+        move || {
+            history.lock().unwrap().push(Vec::new());
+            Ok(Breaking {
+                limit: limits.lock().unwrap().next().unwrap_or(usize::MAX),
+                history: Arc::clone(&history),
+            })
+        }
+    }))
+    .unwrap();
+    (reopen, history)
+}
+
+#[test]
+fn whole_record_on_new_fd() {
+    let (mut writer, history) = provide_writer(vec![3]);
+    writer.write_record(b"hello world").unwrap();
+    let history = history.lock().unwrap();
+    assert_eq!(2, history.len());
+    assert_eq!(b"hel", &history[0][..]);
+    assert_eq!(b"hello world", &history[1][..]);
+}
+
+#[test]
+fn retries_exhausted() {
+    let (writer, history) = provide_writer(vec![3, 3, 3]);
+    let mut writer = writer.record_retries(2);
+    let err = writer.write_record(b"hello world").unwrap_err();
+    assert_eq!(ErrorKind::BrokenPipe, err.kind());
+    assert_eq!(3, history.lock().unwrap().len());
+}
+
+#[test]
+fn no_retries() {
+    let (writer, history) = provide_writer(vec![3]);
+    let mut writer = writer.record_retries(0);
+    assert!(writer.write_record(b"hello world").is_err());
+    // Gave up without reopening.
+    assert_eq!(1, history.lock().unwrap().len());
+}
+
+#[test]
+fn retry_after_reopen() {
+    let (mut writer, history) = provide_writer(vec![3, 0]);
+    writer.write_all_with_reopen_retry(b"hello", 3).unwrap();
+    let history = history.lock().unwrap();
+    assert_eq!(3, history.len());
+    assert_eq!(b"hel", &history[0][..]);
+    assert!(history[1].is_empty());
+    assert_eq!(b"hello", &history[2][..]);
+}
+
+#[test]
+fn retry_gives_up() {
+    let (mut writer, history) = provide_writer(vec![0, 0, 0, 0]);
+    let err = writer.write_all_with_reopen_retry(b"hello", 2).unwrap_err();
+    assert_eq!(ErrorKind::BrokenPipe, err.kind());
+    // The first one and two reopens.
+    assert_eq!(3, history.lock().unwrap().len());
+}
+
+/// Fails with an error a new FD doesn't help with.
+struct Denied;
+
+impl Write for Denied {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn write(&mut self, _: &[u8]) -> Result<usize, Error> {
+        Err(Error::new(ErrorKind::PermissionDenied, "Denied"))
+    }
+}
+
+#[test]
+fn retry_only_dead() {
+    let opens = Arc::new(Mutex::new(0));
+    let mut writer = Reopen::new(Box::new({
+        let opens = Arc::clone(&opens);
+        move || {
+            *opens.lock().unwrap() += 1;
+            Ok(Denied)
+        }
+    }))
+    .unwrap();
+    let err = writer.write_all_with_reopen_retry(b"hello", 3).unwrap_err();
+    assert_eq!(ErrorKind::PermissionDenied, err.kind());
+    assert_eq!(1, *opens.lock().unwrap());
+}
+
+/// Errors that don't mean a dead FD are returned without a reopen.
+#[test]
+fn not_recoverable() {
+    struct Invalid;
+    impl Write for Invalid {
+        fn flush(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+        fn write(&mut self, _: &[u8]) -> Result<usize, Error> {
+            Err(Error::new(ErrorKind::InvalidData, "Invalid"))
+        }
+    }
+
+    let opened = Arc::new(Mutex::new(0));
+    let mut writer = Reopen::new(Box::new({
+        let opened = Arc::clone(&opened);
+        move || {
+            *opened.lock().unwrap() += 1;
+            Ok(Invalid)
+        }
+    }))
+    .unwrap()
+    .record_retries(3);
+    let err = writer.write_record(b"hello world").unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    assert_eq!(1, *opened.lock().unwrap());
+    assert!(writer.health().open);
+}
@@ -0,0 +1,63 @@
+//! Tests of the `RecordWriter`.
+
+use std::io::Write;
+
+use reopen::test_util::{HistorySink, HistoryWriter};
+use reopen::{RecordWriter, Reopen};
+
+fn writer(sink: &HistorySink) -> RecordWriter<HistoryWriter> {
+    RecordWriter::new(Reopen::new(Box::new(sink.constructor())).unwrap())
+}
+
+/// Incomplete records stay in the buffer.
+#[test]
+fn partial() {
+    let sink = HistorySink::new();
+    let mut writer = writer(&sink);
+    writer.write_all(b"hel").unwrap();
+    writer.write_all(b"lo").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(b"hello", writer.buffered());
+    assert_eq!(vec![Vec::<u8>::new()], sink.history());
+
+    writer.write_all(b"\nwor").unwrap();
+    assert_eq!(b"wor", writer.buffered());
+    assert_eq!(vec![b"hello\n".to_vec()], sink.history());
+}
+
+/// A reopen requested in the middle of a record waits for the record to complete.
+#[test]
+fn reopen_mid_record() {
+    let sink = HistorySink::new();
+    let mut writer = writer(&sink);
+    writer.write_all(b"first\nsec").unwrap();
+    writer.get_ref().handle().reopen();
+    writer.write_all(b"on").unwrap();
+    assert!(writer.get_ref().is_pending());
+    assert_eq!(1, sink.incarnations());
+
+    writer.write_all(b"d\nthird\n").unwrap();
+    assert!(!writer.get_ref().is_pending());
+    assert_eq!(
+        vec![b"first\n".to_vec(), b"second\nthird\n".to_vec()],
+        sink.history()
+    );
+}
+
+#[test]
+fn custom_delimiter() {
+    let sink = HistorySink::new();
+    let mut writer = writer(&sink).with_delimiter(0);
+    writer.write_all(b"a\nb\0c").unwrap();
+    assert_eq!(vec![b"a\nb\0".to_vec()], sink.history());
+}
+
+/// The incomplete record is written on drop.
+#[test]
+fn drop_writes_rest() {
+    let sink = HistorySink::new();
+    let mut writer = writer(&sink);
+    writer.write_all(b"a\nb").unwrap();
+    drop(writer);
+    assert_eq!(vec![b"a\nb".to_vec()], sink.history());
+}
@@ -0,0 +1,60 @@
+//! Tests of redirecting the standard output of the whole process.
+//!
+//! This is the only test in here, as it changes what the whole process writes into.
+#![cfg(all(feature = "redirect", unix))]
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::FromRawFd;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reopen::redirect::{stdio, StdioTargets};
+use tempfile::TempDir;
+
+/// Writes directly into the descriptor, the way a C library would.
+fn c_write(msg: &str) {
+    let written = unsafe { libc::write(1, msg.as_ptr() as *const _, msg.len()) };
+    assert_eq!(msg.len() as isize, written);
+}
+
+/// Checks if the standard output goes into the given file.
+fn stdout_is(path: &Path) -> bool {
+    let stdout = unsafe { File::from_raw_fd(libc::dup(1)) };
+    match fs::metadata(path) {
+        Ok(meta) => meta.ino() == stdout.metadata().unwrap().ino(),
+        Err(_) => false,
+    }
+}
+
+#[test]
+fn follows_rotation() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("out.log");
+    let rotated = dir.path().join("out.log.1");
+    let (guard, handle) = stdio(&path, StdioTargets::Stdout).unwrap();
+    assert!(stdout_is(&path));
+    // Not println, the test harness captures that one.
+    writeln!(io::stdout(), "rust before").unwrap();
+    c_write("c before\n");
+
+    fs::rename(&path, &rotated).unwrap();
+    handle.reopen();
+    let start = Instant::now();
+    while !stdout_is(&path) {
+        assert!(start.elapsed() < Duration::from_secs(10), "Not redirected");
+        thread::sleep(Duration::from_millis(10));
+    }
+    writeln!(io::stdout(), "rust after").unwrap();
+    c_write("c after\n");
+
+    drop(guard);
+    assert!(!stdout_is(&path));
+    assert_eq!(
+        "rust before\nc before\n",
+        fs::read_to_string(&rotated).unwrap()
+    );
+    assert_eq!("rust after\nc after\n", fs::read_to_string(&path).unwrap());
+}
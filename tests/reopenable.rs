@@ -0,0 +1,61 @@
+//! Tests of being generic over the reopenable types.
+
+use std::io::Write;
+
+use reopen::test_util::HistorySink;
+use reopen::{Reopen, Reopenable, SyncReopen};
+
+fn rotate(target: &mut impl Reopenable) {
+    target.reopen_now().unwrap();
+}
+
+#[test]
+fn reopen() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    writer.write_all(b"a").unwrap();
+    rotate(&mut writer);
+    // Reopened right away, not on the next operation.
+    assert_eq!(2, sink.incarnations());
+    writer.write_all(b"b").unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], sink.history());
+}
+
+#[test]
+fn sync_reopen() {
+    let sink = HistorySink::new();
+    let mut writer = SyncReopen::new(Reopen::new(Box::new(sink.constructor())).unwrap());
+    (&writer).write_all(b"a").unwrap();
+    rotate(&mut writer);
+    assert_eq!(2, sink.incarnations());
+    (&writer).write_all(b"b").unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], sink.history());
+}
+
+/// The buffered data go into the old FD.
+#[test]
+fn buffered() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .wrap_buffered(100);
+    writer.write_all(b"a").unwrap();
+    rotate(&mut writer);
+    writer.write_all(b"b").unwrap();
+    writer.flush().unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], sink.history());
+}
+
+#[test]
+fn object_safe() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    let handle = {
+        let target: &mut dyn Reopenable = &mut writer;
+        target.reopen_now().unwrap();
+        target.handle()
+    };
+    handle.reopen();
+    writer.write_all(b"a").unwrap();
+    assert_eq!(3, sink.incarnations());
+}
@@ -0,0 +1,43 @@
+//! Tests of the turnkey rotating logger.
+#![cfg(all(feature = "logger", feature = "signals", unix))]
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use log::{debug, error, info, LevelFilter};
+use tempfile::TempDir;
+
+fn lines(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            // Strip the timestamp, but check it's there.
+            let (timestamp, rest) = line.split_at(line.find(' ').unwrap());
+            assert!(timestamp.parse::<f64>().unwrap() > 0.0);
+            rest[1..].to_owned()
+        })
+        .collect()
+}
+
+// A single test, as there can be only one logger.
+#[test]
+fn split_by_signal() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    reopen::install_rotating_logger(&path, LevelFilter::Info, libc::SIGUSR2).unwrap();
+
+    info!("before");
+    debug!("filtered out");
+    let rotated = path.with_extension("1");
+    fs::rename(&path, &rotated).unwrap();
+    assert_eq!(0, unsafe { libc::raise(libc::SIGUSR2) });
+    error!("after {}", 2);
+
+    assert_eq!(vec!["INFO rotating_logger: before"], lines(&rotated));
+    assert_eq!(vec!["ERROR rotating_logger: after 2"], lines(&path));
+
+    let err = reopen::install_rotating_logger(&path, LevelFilter::Info, libc::SIGUSR2).unwrap_err();
+    assert_eq!(ErrorKind::AlreadyExists, err.kind());
+}
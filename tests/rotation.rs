@@ -0,0 +1,127 @@
+//! Tests of the built-in rotation by size.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+/// The names of the files in the directory, sorted.
+fn files(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn read(dir: &Path, name: &str) -> String {
+    fs::read_to_string(dir.join(name)).unwrap()
+}
+
+#[test]
+fn rotates_and_prunes() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    let mut log = Reopen::append_with_rotation(&path, 10, 2).unwrap();
+    // Each record is 5 bytes, so two fill a file.
+    for i in 0..7 {
+        writeln!(log, "rec{}", i).unwrap();
+        // The current writes always go to the path.
+        assert!(read(dir.path(), "app.log").ends_with(&format!("rec{}\n", i)));
+    }
+
+    assert_eq!(vec!["app.log", "app.log.1", "app.log.2"], files(dir.path()));
+    assert_eq!("rec6\n", read(dir.path(), "app.log"));
+    assert_eq!("rec4\nrec5\n", read(dir.path(), "app.log.1"));
+    assert_eq!("rec2\nrec3\n", read(dir.path(), "app.log.2"));
+}
+
+#[test]
+fn existing_content_counts() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    fs::write(&path, "older\n").unwrap();
+    let mut log = Reopen::append_with_rotation(&path, 10, 3).unwrap();
+    log.write_all(b"new1\n").unwrap();
+    log.write_all(b"new2\n").unwrap();
+
+    assert_eq!(vec!["app.log", "app.log.1"], files(dir.path()));
+    assert_eq!("older\nnew1\n", read(dir.path(), "app.log.1"));
+    assert_eq!("new2\n", read(dir.path(), "app.log"));
+}
+
+#[test]
+fn full_file_rotated_on_start() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    fs::write(&path, "0123456789\n").unwrap();
+    let mut log = Reopen::append_with_rotation(&path, 10, 3).unwrap();
+    log.write_all(b"new\n").unwrap();
+
+    assert_eq!(vec!["app.log", "app.log.1"], files(dir.path()));
+    assert_eq!("0123456789\n", read(dir.path(), "app.log.1"));
+    assert_eq!("new\n", read(dir.path(), "app.log"));
+}
+
+/// A zero limit would rotate on every write.
+#[test]
+fn zero_limit() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    let err = Reopen::append_with_rotation(&path, 0, 5).unwrap_err();
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+    assert!(files(dir.path()).is_empty());
+}
+
+#[test]
+fn keep_none() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    let mut log = Reopen::append_with_rotation(&path, 4, 0).unwrap();
+    for i in 0..3 {
+        writeln!(log, "rec{}", i).unwrap();
+    }
+
+    assert_eq!(vec!["app.log"], files(dir.path()));
+    assert_eq!("rec2\n", read(dir.path(), "app.log"));
+}
+
+#[test]
+fn requested_reopen_doesnt_rotate() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    let mut log = Reopen::append_with_rotation(&path, 10, 3).unwrap();
+    log.write_all(b"abc\n").unwrap();
+    log.handle().reopen();
+    log.write_all(b"def\n").unwrap();
+    // Still counts what is already in the file.
+    log.write_all(b"ghi\n").unwrap();
+    log.write_all(b"jkl\n").unwrap();
+
+    assert_eq!(vec!["app.log", "app.log.1"], files(dir.path()));
+    assert_eq!("abc\ndef\nghi\n", read(dir.path(), "app.log.1"));
+    assert_eq!("jkl\n", read(dir.path(), "app.log"));
+}
+
+/// Writes through the locked file are not counted, but the size is found out from the file.
+#[test]
+fn written_through_lock() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.log");
+    let mut log = Reopen::append_with_rotation(&path, 10, 2).unwrap();
+    log.lock().unwrap().write_all(b"0123456789").unwrap();
+    // Noticed at the next lock, before handing out the file.
+    log.lock().unwrap().write_all(b"next\n").unwrap();
+    assert_eq!("0123456789", read(dir.path(), "app.log.1"));
+    assert_eq!("next\n", read(dir.path(), "app.log"));
+
+    // And at the next write.
+    log.lock().unwrap().write_all(b"67890").unwrap();
+    writeln!(log, "x").unwrap();
+    writeln!(log, "last").unwrap();
+    assert_eq!("next\n67890x\n", read(dir.path(), "app.log.1"));
+    assert_eq!("last\n", read(dir.path(), "app.log"));
+}
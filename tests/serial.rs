@@ -0,0 +1,88 @@
+//! Tests of finding the serial devices, simulated by pseudo-terminals.
+#![cfg(all(feature = "serial", unix))]
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{Error, Read, Write};
+use std::os::unix::fs::symlink;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use reopen::device::serial_by_id;
+use reopen::Reopen;
+use tempfile::TempDir;
+
+/// Creates a pseudo-terminal, returning its master side and the path of the "device".
+fn pty() -> (File, PathBuf) {
+    unsafe {
+        let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+        assert!(master >= 0);
+        assert_eq!(0, libc::grantpt(master));
+        assert_eq!(0, libc::unlockpt(master));
+        let name = libc::ptsname(master);
+        assert!(!name.is_null());
+        let path = PathBuf::from(CStr::from_ptr(name).to_str().unwrap());
+        (File::from_raw_fd(master), path)
+    }
+}
+
+/// Switches the terminal to the raw mode, so the data pass through unchanged.
+fn raw_mode(device: &mut File) -> Result<(), Error> {
+    unsafe {
+        let mut termios = std::mem::zeroed();
+        if libc::tcgetattr(device.as_raw_fd(), &mut termios) != 0 {
+            return Err(Error::last_os_error());
+        }
+        libc::cfmakeraw(&mut termios);
+        if libc::tcsetattr(device.as_raw_fd(), libc::TCSANOW, &termios) != 0 {
+            return Err(Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn read(master: &mut File, len: usize) -> String {
+    let mut buf = vec![0; len];
+    master.read_exact(&mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn device_reappears() {
+    let dir = TempDir::new().unwrap();
+    let setups = Arc::new(AtomicUsize::new(0));
+    let (mut master, device) = pty();
+    let first_link = dir.path().join("usb-adapter-0001");
+    symlink(&device, &first_link).unwrap();
+
+    let pattern = dir.path().join("usb-adapter-*");
+    let mut writer = Reopen::from_fn(serial_by_id(pattern.to_str().unwrap(), {
+        let setups = Arc::clone(&setups);
+        move |device| {
+            setups.fetch_add(1, Ordering::Relaxed);
+            raw_mode(device)
+        }
+    }))
+    .unwrap()
+    .reopen_on_os_errors(vec![libc::EIO, libc::ENXIO]);
+
+    writeln!(writer, "first").unwrap();
+    assert_eq!("first\n", read(&mut master, 6));
+
+    // Unplugged.
+    drop(master);
+    std::fs::remove_file(&first_link).unwrap();
+    writer.write_all(b"lost\n").unwrap_err();
+    assert!(writer.is_pending());
+    // Not there yet.
+    writer.write_all(b"lost\n").unwrap_err();
+
+    // Plugged in again, as a different device.
+    let (mut master, device) = pty();
+    symlink(&device, dir.path().join("usb-adapter-0002")).unwrap();
+    writeln!(writer, "second").unwrap();
+    assert_eq!("second\n", read(&mut master, 7));
+    assert_eq!(2, setups.load(Ordering::Relaxed));
+}
@@ -0,0 +1,45 @@
+//! Tests of installing an FD from the outside.
+
+use std::io::{Cursor, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use reopen::test_util::HistorySink;
+use reopen::Reopen;
+
+#[test]
+fn reads_and_writes_go_to_installed() {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let mut rw = Reopen::new(Box::new({
+        let opened = Arc::clone(&opened);
+        move || {
+            opened.fetch_add(1, Ordering::Relaxed);
+            Ok(Cursor::new(b"constructed".to_vec()))
+        }
+    }))
+    .unwrap()
+    .with_header("# header\n");
+    rw.handle().reopen();
+    rw.set_fd(Cursor::new(b"prefilled".to_vec()));
+
+    let mut buf = [0; 3];
+    rw.read_exact(&mut buf).unwrap();
+    assert_eq!(b"pre", &buf);
+    rw.write_all(b"FILLED").unwrap();
+    // The pending reopen was dropped together with the old FD, no header either.
+    assert_eq!(1, opened.load(Ordering::Relaxed));
+    assert_eq!(b"preFILLED", &rw.lock().unwrap().get_ref()[..]);
+}
+
+/// The old FD is closed the same way as on a reopen.
+#[test]
+fn old_closed() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .with_footer("|end");
+    writer.write_all(b"old").unwrap();
+    writer.set_fd(sink.open().unwrap());
+    writer.write_all(b"new").unwrap();
+    assert_eq!(vec![b"old|end".to_vec(), b"new".to_vec()], sink.history());
+}
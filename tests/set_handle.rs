@@ -0,0 +1,47 @@
+//! Tests of moving a `Reopen` to a different handle.
+
+use std::io::Write;
+
+use reopen::test_util::HistorySink;
+use reopen::{Handle, Reopen};
+
+#[test]
+fn new_handle_controls() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    let old = writer.handle();
+    let new = Handle::stub();
+    writer.set_handle(new.clone(), false);
+
+    old.reopen();
+    assert!(!writer.is_pending());
+    writer.write_all(b"a").unwrap();
+    assert_eq!(1, sink.incarnations());
+
+    new.reopen();
+    assert!(writer.is_pending());
+    writer.write_all(b"b").unwrap();
+    assert_eq!(vec![b"a".to_vec(), b"b".to_vec()], sink.history());
+}
+
+#[test]
+fn pending_carried_over() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    writer.handle().reopen();
+    writer.set_handle(Handle::stub(), true);
+    assert!(writer.is_pending());
+    writer.write_all(b"a").unwrap();
+    assert_eq!(vec![Vec::new(), b"a".to_vec()], sink.history());
+}
+
+#[test]
+fn pending_dropped() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    writer.handle().reopen();
+    writer.set_handle(Handle::stub(), false);
+    assert!(!writer.is_pending());
+    writer.write_all(b"a").unwrap();
+    assert_eq!(vec![b"a".to_vec()], sink.history());
+}
@@ -0,0 +1,53 @@
+//! Tests of the status reporting on SIGINFO.
+#![cfg(all(
+    feature = "signals",
+    any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "dragonfly"
+    )
+))]
+
+use std::io::Write;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reopen::{Reopen, StatusSnapshot};
+
+#[test]
+fn status_reported() {
+    let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    writer
+        .handle()
+        .register_siginfo_status(move |status| {
+            let _ = sender.lock().unwrap().send(status);
+        })
+        .unwrap();
+    let status = || -> StatusSnapshot {
+        assert_eq!(0, unsafe { libc::raise(libc::SIGINFO) });
+        receiver.recv_timeout(Duration::from_secs(10)).unwrap()
+    };
+
+    writer.write_all(b"hello").unwrap();
+    writer.handle().reopen();
+    let before = status();
+    assert_eq!(0, before.generation);
+    assert_eq!(5, before.written);
+    assert!(before.pending);
+    assert_eq!(
+        "generation 0, 5 bytes written since open, reopen pending",
+        before.to_string()
+    );
+
+    writer.write_all(b"abc").unwrap();
+    let after = status();
+    assert_eq!(1, after.generation);
+    assert_eq!(3, after.written);
+    assert!(!after.pending);
+}
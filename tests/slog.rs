@@ -0,0 +1,49 @@
+//! Tests of the slog drain.
+#![cfg(feature = "slog")]
+
+use reopen::integrations::ReopenDrain;
+use reopen::test_util::HistorySink;
+use reopen::Reopen;
+use slog::{debug, info, o, warn, Logger};
+
+/// Checks the line looks like `<secs>.<millis> <rest>`.
+fn check_line(line: &str, rest: &str) {
+    let (timestamp, tail) = line.split_at(line.find(' ').unwrap());
+    let (secs, millis) = timestamp.split_at(timestamp.find('.').unwrap());
+    assert!(secs.parse::<u64>().unwrap() > 0);
+    assert_eq!(4, millis.len());
+    assert!(millis[1..].parse::<u16>().is_ok());
+    assert_eq!(rest, &tail[1..]);
+}
+
+fn lines(data: &[u8]) -> Vec<String> {
+    String::from_utf8(data.to_vec())
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn log_and_rotate() {
+    let sink = HistorySink::new();
+    let drain = ReopenDrain::new(Reopen::new(Box::new(sink.constructor())).unwrap());
+    let handle = drain.handle();
+    let logger = Logger::root(drain, o!("app" => "test"));
+
+    info!(logger, "first"; "n" => 1);
+    // Not filtered by the drain itself
+    debug!(logger, "debug");
+    handle.reopen();
+    warn!(logger, "second");
+
+    let history = sink.history();
+    assert_eq!(2, history.len());
+    let first = lines(&history[0]);
+    assert_eq!(2, first.len());
+    check_line(&first[0], "INFO slog: first, n=1, app=test");
+    check_line(&first[1], "DEBG slog: debug, app=test");
+    let second = lines(&history[1]);
+    assert_eq!(1, second.len());
+    check_line(&second[0], "WARN slog: second, app=test");
+}
@@ -0,0 +1,61 @@
+//! Tests of the `SwapReopen`.
+#![cfg(feature = "arc-swap")]
+
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use reopen::SwapReopen;
+
+const THREADS: usize = 8;
+
+/// Many readers, one reopen. All of them eventually see the new generation.
+#[test]
+fn readers_observe_reopen() {
+    let generation = Arc::new(AtomicUsize::new(0));
+    let reopen = SwapReopen::new(Box::new({
+        let generation = Arc::clone(&generation);
+        move || Ok(generation.fetch_add(1, Ordering::Relaxed))
+    }))
+    .unwrap();
+    assert_eq!(0, *reopen.load().unwrap());
+    let started = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                started.fetch_add(1, Ordering::Relaxed);
+                while *reopen.load().unwrap() == 0 {
+                    thread::yield_now();
+                }
+                assert_eq!(1, *reopen.load().unwrap());
+            });
+        }
+        while started.load(Ordering::Relaxed) < THREADS {
+            thread::yield_now();
+        }
+        reopen.handle().reopen();
+    });
+
+    // Reopened exactly once, no matter how many threads noticed.
+    assert_eq!(2, generation.load(Ordering::Relaxed));
+}
+
+/// A failed reopen keeps the old instance and retries next time.
+#[test]
+fn failed_reopen() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let reopen = SwapReopen::new(Box::new({
+        let attempts = Arc::clone(&attempts);
+        move || match attempts.fetch_add(1, Ordering::Relaxed) {
+            1 => Err(Error::new(ErrorKind::NotFound, "Missing")),
+            n => Ok(n),
+        }
+    }))
+    .unwrap();
+    reopen.handle().reopen();
+    assert!(reopen.load().is_err());
+    assert_eq!(2, *reopen.load().unwrap());
+    assert_eq!(2, *reopen.load().unwrap());
+}
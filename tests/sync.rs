@@ -0,0 +1,88 @@
+//! Tests of the `SyncReopen` wrapper.
+
+use std::io::{Error, Write};
+use std::iter;
+use std::sync::Mutex;
+use std::thread;
+
+use once_cell::sync::Lazy;
+use partial_io::{PartialOp, PartialWrite};
+use reopen::{Reopen, SyncReopen};
+
+const THREADS: usize = 4;
+const ROUNDS: usize = 100;
+
+static DATA: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+struct Collector;
+
+impl Write for Collector {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        DATA.lock().unwrap().write(buf)
+    }
+}
+
+// Write single byte at a time, so records would get mixed if the whole write_all was not done
+// under the lock.
+static WRITER: Lazy<SyncReopen<PartialWrite<Collector>>> = Lazy::new(|| {
+    let reopen = Reopen::new(Box::new(|| {
+        Ok(PartialWrite::new(
+            Collector,
+            iter::repeat(PartialOp::Limited(1)),
+        ))
+    }))
+    .unwrap();
+    SyncReopen::new(reopen)
+});
+
+#[test]
+fn concurrent_writes() {
+    let handle = WRITER.handle();
+    let threads = (0..THREADS)
+        .map(|i| {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    (&*WRITER).write_all(format!("{}\n", i).as_bytes()).unwrap();
+                    handle.reopen();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for t in threads {
+        t.join().unwrap();
+    }
+
+    let data = DATA.lock().unwrap();
+    let data = String::from_utf8(data.clone()).unwrap();
+    let lines = data.lines().collect::<Vec<_>>();
+    assert_eq!(THREADS * ROUNDS, lines.len());
+    for i in 0..THREADS {
+        let expected = i.to_string();
+        assert_eq!(ROUNDS, lines.iter().filter(|l| **l == expected).count());
+    }
+}
+
+/// Many threads hammering a single shared writer, without any reopens.
+///
+/// The CI runs this both with and without the `parking_lot` feature.
+#[test]
+fn contention() {
+    let writer = SyncReopen::new(Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap());
+    thread::scope(|s| {
+        for _ in 0..THREADS * 2 {
+            s.spawn(|| {
+                for _ in 0..ROUNDS * 10 {
+                    (&writer).write_all(b"abcd").unwrap();
+                }
+            });
+        }
+    });
+    let mut reopen = writer.into_inner();
+    let data = reopen.lock().unwrap();
+    assert_eq!(THREADS * 2 * ROUNDS * 10 * 4, data.len());
+    assert!(data.chunks(4).all(|c| c == b"abcd"));
+}
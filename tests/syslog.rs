@@ -0,0 +1,102 @@
+//! Tests of the unix datagram sockets.
+#![cfg(all(feature = "syslog", unix))]
+
+use std::fs;
+use std::io::Write;
+use std::os::unix::net::UnixDatagram;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use reopen::net::unix_datagram;
+use reopen::Reopen;
+use tempfile::TempDir;
+
+fn recv(server: &UnixDatagram) -> String {
+    let mut buf = [0; 64];
+    let len = server.recv(&mut buf).unwrap();
+    String::from_utf8(buf[..len].to_vec()).unwrap()
+}
+
+fn bind(path: &Path) -> UnixDatagram {
+    // Like a restarting syslog daemon, remove the stale socket first.
+    let _ = fs::remove_file(path);
+    UnixDatagram::bind(path).unwrap()
+}
+
+#[test]
+fn resumes_after_server_restart() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let server = bind(&path);
+    let mut writer = Reopen::from_fn(unix_datagram(path.clone()))
+        .unwrap()
+        .reopen_on_disconnect(true)
+        .buffer_fmt(true);
+
+    writeln!(writer, "first {}", 1).unwrap();
+    assert_eq!("first 1\n", recv(&server));
+
+    drop(server);
+    writer.write_all(b"lost").unwrap_err();
+    assert!(writer.is_pending());
+
+    let server = bind(&path);
+    writeln!(writer, "second {}", 2).unwrap();
+    assert_eq!("second 2\n", recv(&server));
+}
+
+#[test]
+fn stale_without_reopen() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let server = bind(&path);
+    let mut writer = Reopen::from_fn(unix_datagram(path.clone())).unwrap();
+
+    drop(server);
+    let _server = bind(&path);
+    writer.write_all(b"lost").unwrap_err();
+    writer.write_all(b"still lost").unwrap_err();
+}
+
+#[test]
+fn send_reconnects() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let server = bind(&path);
+    let mut socket = Reopen::unix_datagram(&path).unwrap();
+
+    socket.send(b"first").unwrap();
+    assert_eq!("first", recv(&server));
+
+    drop(server);
+    socket.send(b"lost").unwrap_err();
+
+    let server = bind(&path);
+    socket.send(b"second").unwrap();
+    assert_eq!("second", recv(&server));
+}
+
+#[test]
+fn socketpair_reopen_on_error() {
+    // The constructor hands out one end of a new pair each time, we keep the other ends.
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let mut socket = Reopen::from_fn({
+        let peers = Arc::clone(&peers);
+        move || {
+            let (ours, theirs) = UnixDatagram::pair()?;
+            peers.lock().unwrap().push(theirs);
+            Ok(ours)
+        }
+    })
+    .unwrap()
+    .reopen_on_disconnect(true);
+
+    socket.send(b"first").unwrap();
+    assert_eq!("first", recv(&peers.lock().unwrap()[0]));
+
+    // The peer goes away.
+    peers.lock().unwrap().clear();
+    socket.send(b"lost").unwrap_err();
+    socket.send(b"second").unwrap();
+    assert_eq!("second", recv(&peers.lock().unwrap()[0]));
+}
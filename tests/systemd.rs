@@ -0,0 +1,49 @@
+//! Tests of the systemd reload protocol.
+#![cfg(all(feature = "sd-notify", unix))]
+
+use std::env;
+use std::io::Write;
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+#[test]
+fn reload_sequence() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("notify");
+    let socket = UnixDatagram::bind(&path).unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    // This is the only test in this binary, nobody else reads the environment.
+    env::set_var("NOTIFY_SOCKET", &path);
+    let mut writer = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+    writer.handle().register_systemd_reload().unwrap();
+    let recv = || {
+        let mut buf = [0; 128];
+        let len = socket.recv(&mut buf).unwrap();
+        String::from_utf8(buf[..len].to_vec()).unwrap()
+    };
+
+    assert_eq!(0, unsafe { libc::raise(libc::SIGHUP) });
+    let reloading = recv();
+    assert!(
+        reloading.starts_with("RELOADING=1\nMONOTONIC_USEC="),
+        "{}",
+        reloading
+    );
+    assert!(writer.is_pending());
+
+    // The ready comes only after the reopen.
+    socket
+        .set_read_timeout(Some(Duration::from_millis(100)))
+        .unwrap();
+    assert!(socket.recv(&mut [0; 128]).is_err());
+    socket
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    writer.write_all(b"hello").unwrap();
+    assert_eq!("READY=1", recv());
+}
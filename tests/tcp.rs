@@ -0,0 +1,103 @@
+//! Tests of the TCP constructors.
+#![cfg(feature = "net")]
+
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use reopen::net::{tcp, tcp_to};
+use reopen::Reopen;
+#[cfg(any(unix, windows))]
+use socket2::SockRef;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn read_line(listener: &TcpListener) -> String {
+    let (stream, _) = listener.accept().unwrap();
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+    line
+}
+
+#[test]
+fn resolves_name() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let mut writer = Reopen::from_fn(tcp(format!("localhost:{}", port), TIMEOUT, None))
+        .unwrap()
+        .buffer_fmt(true);
+    assert!(writer.lock().unwrap().nodelay().unwrap());
+    writeln!(writer, "Hello {}", port).unwrap();
+    drop(writer);
+    assert_eq!(format!("Hello {}\n", port), read_line(&listener));
+}
+
+#[test]
+#[cfg(any(unix, windows))]
+fn keepalive() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut writer =
+        Reopen::from_fn(tcp_to(move || addr, TIMEOUT, Some(Duration::from_secs(60)))).unwrap();
+    let stream = writer.lock().unwrap();
+    let sock = SockRef::from(&*stream);
+    assert!(sock.keepalive().unwrap());
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    assert_eq!(Duration::from_secs(60), sock.tcp_keepalive_time().unwrap());
+}
+
+#[test]
+fn follows_address_change() {
+    let first = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = Arc::new(Mutex::new(first.local_addr().unwrap()));
+    let mut writer = Reopen::from_fn(tcp_to(
+        {
+            let addr = Arc::clone(&addr);
+            move || *addr.lock().unwrap()
+        },
+        TIMEOUT,
+        None,
+    ))
+    .unwrap()
+    .reopen_on_disconnect(true)
+    .reopen_on_broken_pipe(true);
+
+    writer.write_all(b"first\n").unwrap();
+    let (stream, _) = first.accept().unwrap();
+    let mut line = String::new();
+    let mut reader = BufReader::new(stream);
+    reader.read_line(&mut line).unwrap();
+    assert_eq!("first\n", line);
+
+    // The server goes away, the writes start failing after a while.
+    drop(reader);
+    drop(first);
+    let mut failed = false;
+    for _ in 0..100 {
+        if writer.write_all(b"lost\n").is_err() {
+            failed = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(failed);
+    assert!(writer.is_pending());
+
+    // It comes back elsewhere.
+    let second = TcpListener::bind("127.0.0.1:0").unwrap();
+    *addr.lock().unwrap() = second.local_addr().unwrap();
+    writer.write_all(b"second\n").unwrap();
+    drop(writer);
+    assert_eq!("second\n", read_line(&second));
+}
+
+#[test]
+fn nobody_listens() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr: SocketAddr = listener.local_addr().unwrap();
+    drop(listener);
+    let err = Reopen::from_fn(tcp_to(move || addr, TIMEOUT, None)).unwrap_err();
+    assert_eq!(ErrorKind::ConnectionRefused, err.kind());
+}
@@ -0,0 +1,120 @@
+//! Tests of the TLS connections.
+#![cfg(feature = "rustls")]
+
+use std::convert::TryInto;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use reopen::net::tls;
+use reopen::Reopen;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::{ClientConfig, RootCertStore, ServerConfig, ServerConnection, StreamOwned};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs a server reading a line from each of the given number of connections.
+///
+/// Returns the port, the certificate to trust and the lines read.
+fn server(
+    connections: usize,
+) -> (
+    u16,
+    CertificateDer<'static>,
+    Receiver<String>,
+    JoinHandle<()>,
+) {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+    let cert = certified.cert.der().clone();
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(certified.key_pair.serialize_der()));
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert.clone()], key)
+        .unwrap();
+    let config = Arc::new(config);
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, lines) = mpsc::channel();
+    let thread = thread::spawn(move || {
+        for _ in 0..connections {
+            let (sock, _) = listener.accept().unwrap();
+            let conn = ServerConnection::new(Arc::clone(&config)).unwrap();
+            let mut line = String::new();
+            // The handshake may fail, on purpose.
+            if BufReader::new(StreamOwned::new(conn, sock))
+                .read_line(&mut line)
+                .is_ok()
+            {
+                sender.send(line).unwrap();
+            }
+        }
+    });
+    (port, cert, lines, thread)
+}
+
+fn client_config(trusted: Option<CertificateDer<'static>>) -> Arc<ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    if let Some(cert) = trusted {
+        roots.add(cert).unwrap();
+    }
+    Arc::new(
+        ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    )
+}
+
+#[test]
+fn reconnects() {
+    let (port, cert, lines, server) = server(2);
+    let connect = tls(
+        client_config(Some(cert)),
+        "localhost".try_into().unwrap(),
+        format!("localhost:{}", port),
+        TIMEOUT,
+    );
+    let mut writer = Reopen::from_fn(connect)
+        .unwrap()
+        .reopen_on_disconnect(true)
+        .reopen_on_broken_pipe(true)
+        .buffer_fmt(true);
+
+    writeln!(writer, "first {}", port).unwrap();
+    assert_eq!(format!("first {}\n", port), lines.recv().unwrap());
+
+    // The server closed the connection, the writes start failing after a while.
+    let mut failed = false;
+    for _ in 0..100 {
+        if writer.write_all(b"lost\n").is_err() {
+            failed = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(failed);
+
+    writeln!(writer, "second {}", port).unwrap();
+    assert_eq!(format!("second {}\n", port), lines.recv().unwrap());
+    drop(writer);
+    server.join().unwrap();
+}
+
+#[test]
+fn untrusted_certificate() {
+    let (port, _, _, server) = server(1);
+    let connect = tls(
+        client_config(None),
+        "localhost".try_into().unwrap(),
+        format!("127.0.0.1:{}", port),
+        TIMEOUT,
+    );
+    let err = Reopen::from_fn(connect).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    let msg = err.to_string();
+    assert!(msg.starts_with("TLS handshake with 127.0.0.1"), "{}", msg);
+    assert!(msg.contains("certificate"), "{}", msg);
+    server.join().unwrap();
+}
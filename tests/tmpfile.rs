@@ -0,0 +1,45 @@
+//! Tests of the anonymous files linked into place on reopen.
+#![cfg(all(feature = "tmpfile", target_os = "linux"))]
+
+use std::fs;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use reopen::tmpfile::linked_on_close;
+use tempfile::TempDir;
+
+fn names(dir: &TempDir) -> Vec<String> {
+    let mut names = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+#[test]
+fn appears_on_rotation() {
+    let dir = TempDir::new().unwrap();
+    let counter = AtomicUsize::new(0);
+    let mut writer = linked_on_close(dir.path(), move || {
+        format!("log.{}", counter.fetch_add(1, Ordering::Relaxed))
+    });
+    writeln!(writer, "first").unwrap();
+    writer.flush().unwrap();
+    assert!(names(&dir).is_empty());
+
+    writer.handle().reopen();
+    writeln!(writer, "second").unwrap();
+    assert_eq!(vec!["log.0"], names(&dir));
+    assert_eq!(
+        "first\n",
+        fs::read_to_string(dir.path().join("log.0")).unwrap()
+    );
+
+    drop(writer);
+    assert_eq!(vec!["log.0", "log.1"], names(&dir));
+    assert_eq!(
+        "second\n",
+        fs::read_to_string(dir.path().join("log.1")).unwrap()
+    );
+}
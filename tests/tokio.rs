@@ -0,0 +1,31 @@
+//! Tests of reopening on a tokio notification.
+#![cfg(feature = "tokio")]
+
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reopen::test_util::HistorySink;
+use reopen::Reopen;
+use tokio::sync::Notify;
+use tokio::time;
+
+#[tokio::test]
+async fn notified() {
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    let notify = Arc::new(Notify::new());
+    let task = writer.handle().spawn_notified(Arc::clone(&notify));
+
+    notify.notify_one();
+    while !writer.is_pending() {
+        time::sleep(Duration::from_millis(1)).await;
+    }
+    writer.write_all(b"hello").unwrap();
+    assert_eq!(2, sink.incarnations());
+
+    // Once the reopen is gone, the task ends at the next notification.
+    drop(writer);
+    notify.notify_one();
+    task.await.unwrap();
+}
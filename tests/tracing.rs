@@ -0,0 +1,115 @@
+//! Tests of the events emitted with the `tracing` feature.
+#![cfg(feature = "tracing")]
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use reopen::Reopen;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Collects everything the subscriber formats.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Capture {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Capture {
+    type Writer = Capture;
+    fn make_writer(&'a self) -> Capture {
+        self.clone()
+    }
+}
+
+fn captured<F: FnOnce()>(f: F) -> Vec<String> {
+    let capture = Capture::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(capture.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    capture.lines()
+}
+
+fn count(lines: &[String], what: &str) -> usize {
+    lines.iter().filter(|l| l.contains(what)).count()
+}
+
+#[test]
+fn lifecycle() {
+    let lines = captured(|| {
+        let mut writer = Reopen::lazy(Box::new(|| Ok(Vec::new()))).with_label("audit");
+        writer.write_all(b"Hello").unwrap();
+        writer.handle().reopen();
+        writer.write_all(b"Hello").unwrap();
+    });
+    // All of them go under our target.
+    assert!(lines.iter().all(|l| l.contains("reopen")), "{:?}", lines);
+    assert_eq!(1, count(&lines, "reopen requested"), "{:?}", lines);
+    assert_eq!(1, count(&lines, "source=\"handle\""), "{:?}", lines);
+    assert_eq!(2, count(&lines, "open succeeded"), "{:?}", lines);
+    assert_eq!(2, count(&lines, "open{label=\"audit\"}"), "{:?}", lines);
+    assert!(lines.iter().any(|l| l.contains("duration=")), "{:?}", lines);
+    // The replaced one and the one dropped at the end.
+    assert_eq!(2, count(&lines, "old FD closed"), "{:?}", lines);
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.contains("old FD closed") && l.contains("audit")),
+        "{:?}",
+        lines
+    );
+}
+
+#[test]
+fn failed_open() {
+    let opens = Arc::new(AtomicUsize::new(0));
+    let lines = captured(|| {
+        let mut writer = Reopen::lazy(Box::new({
+            let opens = Arc::clone(&opens);
+            move || {
+                if opens.fetch_add(1, Ordering::Relaxed) == 0 {
+                    Err(Error::new(ErrorKind::NotFound, "Not there"))
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+        }));
+        writer.write_all(b"Hello").unwrap_err();
+        writer.write_all(b"Hello").unwrap();
+    });
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.contains("open failed") && l.contains("Not there")),
+        "{:?}",
+        lines
+    );
+    assert_eq!(1, count(&lines, "open succeeded"), "{:?}", lines);
+}
+
+#[test]
+fn nothing_without_subscriber() {
+    let mut writer = Reopen::new(Box::new(|| Ok(Vec::new()))).unwrap();
+    writer.handle().reopen();
+    writer.write_all(b"Hello").unwrap();
+}
@@ -0,0 +1,38 @@
+//! Tests of reopening on a trigger file.
+
+use std::fs;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reopen::test_util::HistorySink;
+use reopen::{Clock, ManualClock, Reopen};
+use tempfile::TempDir;
+
+#[test]
+fn reopen_on_trigger() {
+    let dir = TempDir::new().unwrap();
+    let trigger = dir.path().join("reopen");
+    let clock = Arc::new(ManualClock::new());
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor()))
+        .unwrap()
+        .with_clock(Arc::clone(&clock) as Arc<dyn Clock>)
+        .with_trigger_file(&trigger, Duration::from_secs(1));
+
+    writer.write_all(b"a").unwrap();
+    fs::write(&trigger, "").unwrap();
+    // Not looked at before the interval is up.
+    writer.write_all(b"b").unwrap();
+    assert!(trigger.exists());
+
+    clock.advance(Duration::from_secs(1));
+    writer.write_all(b"c").unwrap();
+    assert!(!trigger.exists());
+    assert_eq!(vec![b"ab".to_vec(), b"c".to_vec()], sink.history());
+
+    // Without the file, nothing happens.
+    clock.advance(Duration::from_secs(1));
+    writer.write_all(b"d").unwrap();
+    assert_eq!(vec![b"ab".to_vec(), b"cd".to_vec()], sink.history());
+}
@@ -0,0 +1,42 @@
+//! Tests of the UDP sockets bound again on reopen.
+#![cfg(feature = "net")]
+
+use std::net::{SocketAddr, UdpSocket};
+use std::time::Duration;
+
+use reopen::net::udp_bound;
+use reopen::Reopen;
+
+fn localhost() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 0))
+}
+
+fn receive(receiver: &UdpSocket) -> (Vec<u8>, SocketAddr) {
+    let mut buf = [0; 100];
+    let (len, from) = receiver.recv_from(&mut buf).unwrap();
+    (buf[..len].to_vec(), from)
+}
+
+#[test]
+fn rebind() {
+    let receiver = UdpSocket::bind(localhost()).unwrap();
+    receiver
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let target = receiver.local_addr().unwrap();
+
+    let mut socket = Reopen::from_fn(udp_bound(localhost)).unwrap();
+    let old = socket.lock().unwrap().local_addr().unwrap();
+    socket.send_to(b"first", target).unwrap();
+    assert_eq!((b"first".to_vec(), old), receive(&receiver));
+
+    socket.handle().reopen();
+    socket.send_to(b"second", target).unwrap();
+    let (data, new) = receive(&receiver);
+    assert_eq!(b"second", &data[..]);
+    assert_ne!(old, new);
+    assert_eq!(new, socket.lock().unwrap().local_addr().unwrap());
+
+    // The old socket is closed, so its port is free again.
+    UdpSocket::bind(old).unwrap();
+}
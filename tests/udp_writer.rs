@@ -0,0 +1,85 @@
+//! Tests of writing into connected UDP sockets.
+#![cfg(feature = "net")]
+
+use std::io::{ErrorKind, Write};
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reopen::net::{udp, udp_to, OversizedDatagram, UdpWriter};
+use reopen::Reopen;
+
+fn listener() -> (UdpSocket, SocketAddr) {
+    let socket = UdpSocket::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let addr = socket.local_addr().unwrap();
+    (socket, addr)
+}
+
+fn receive(listener: &UdpSocket) -> Vec<u8> {
+    let mut buf = [0; 100];
+    let len = listener.recv(&mut buf).unwrap();
+    buf[..len].to_vec()
+}
+
+/// A reopen asks the resolver again and the datagrams go to the new address.
+#[test]
+fn re_resolve() {
+    let (first, first_addr) = listener();
+    let (second, second_addr) = listener();
+    let current = Arc::new(Mutex::new(first_addr));
+    let mut writer = Reopen::from_fn(udp_to({
+        let current = Arc::clone(&current);
+        move || *current.lock().unwrap()
+    }))
+    .unwrap()
+    .buffer_fmt(true);
+
+    writeln!(writer, "one").unwrap();
+    writeln!(writer, "two").unwrap();
+    assert_eq!(b"one\n", &receive(&first)[..]);
+    assert_eq!(b"two\n", &receive(&first)[..]);
+
+    *current.lock().unwrap() = second_addr;
+    // Not resolved again yet.
+    writeln!(writer, "three").unwrap();
+    assert_eq!(b"three\n", &receive(&first)[..]);
+    writer.handle().reopen();
+    writeln!(writer, "four").unwrap();
+    writeln!(writer, "five").unwrap();
+    assert_eq!(b"four\n", &receive(&second)[..]);
+    assert_eq!(b"five\n", &receive(&second)[..]);
+}
+
+#[test]
+fn from_string() {
+    let (listener, addr) = listener();
+    let mut writer = Reopen::from_fn(udp(addr.to_string())).unwrap();
+    writer.write_all(b"Hello").unwrap();
+    assert_eq!(b"Hello", &receive(&listener)[..]);
+    assert!(Reopen::from_fn(udp("no port".to_owned())).is_err());
+}
+
+#[test]
+fn oversized() {
+    let (listener, addr) = listener();
+    let connect = |oversized| {
+        let socket = UdpSocket::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        socket.connect(addr).unwrap();
+        UdpWriter::new(socket).max_datagram_size(4, oversized)
+    };
+
+    let mut split = connect(OversizedDatagram::Split);
+    split.write_all(b"HelloWorld").unwrap();
+    assert_eq!(b"Hell", &receive(&listener)[..]);
+    assert_eq!(b"oWor", &receive(&listener)[..]);
+    assert_eq!(b"ld", &receive(&listener)[..]);
+
+    let mut fail = connect(OversizedDatagram::Fail);
+    let err = fail.write(b"HelloWorld").unwrap_err();
+    assert_eq!(ErrorKind::InvalidInput, err.kind());
+    fail.write_all(b"Hi").unwrap();
+    assert_eq!(b"Hi", &receive(&listener)[..]);
+}
@@ -0,0 +1,122 @@
+//! Tests of reconnecting unix stream sockets.
+#![cfg(all(feature = "net", unix))]
+
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use reopen::net::{unix_stream, unix_stream_timeout};
+use reopen::Reopen;
+use tempfile::TempDir;
+
+fn bind(path: &Path) -> UnixListener {
+    // A restarting server removes its stale socket first.
+    let _ = fs::remove_file(path);
+    UnixListener::bind(path).unwrap()
+}
+
+/// Accepts a single connection, reads a line and closes both the connection and the listener.
+fn serve_one(listener: UnixListener) -> String {
+    let (stream, _) = listener.accept().unwrap();
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+    line
+}
+
+#[test]
+fn resumes_after_server_restart() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sock");
+    let (lines_tx, lines) = mpsc::channel();
+    let (restart, restarted) = mpsc::channel();
+    let server = {
+        let path = path.clone();
+        let first = bind(&path);
+        thread::spawn(move || {
+            lines_tx.send(serve_one(first)).unwrap();
+            // Down until told to come back.
+            restarted.recv().unwrap();
+            let second = bind(&path);
+            lines_tx.send("restarted".to_owned()).unwrap();
+            lines_tx.send(serve_one(second)).unwrap();
+        })
+    };
+
+    let mut writer = Reopen::from_fn(unix_stream(path.clone()))
+        .unwrap()
+        .reopen_on_broken_pipe(true)
+        .reopen_on_disconnect(true)
+        .buffer_fmt(true);
+    writeln!(writer, "first {}", 1).unwrap();
+    assert_eq!("first 1\n", lines.recv().unwrap());
+
+    // The server is gone, writes fail and ask for a reconnect, which fails too.
+    let mut failed = false;
+    for _ in 0..100 {
+        if writer.write_all(b"lost\n").is_err() {
+            failed = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(failed);
+    assert!(writer.write_all(b"lost\n").is_err());
+    assert!(writer.is_pending());
+
+    restart.send(()).unwrap();
+    assert_eq!("restarted", lines.recv().unwrap());
+    writeln!(writer, "second {}", 2).unwrap();
+    assert_eq!("second 2\n", lines.recv().unwrap());
+    server.join().unwrap();
+}
+
+#[test]
+fn timeout_connects() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sock");
+    let listener = bind(&path);
+    let mut writer =
+        Reopen::from_fn(unix_stream_timeout(path.clone(), Duration::from_secs(5))).unwrap();
+    writer.write_all(b"hello\n").unwrap();
+    drop(writer);
+    assert_eq!("hello\n", serve_one(listener));
+}
+
+#[test]
+fn timeout_missing() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sock");
+    let err = Reopen::from_fn(unix_stream_timeout(path, Duration::from_secs(5))).unwrap_err();
+    assert_eq!(ErrorKind::NotFound, err.kind());
+}
+
+/// A server that doesn't accept makes the connecting time out (Linux waits for the backlog,
+/// others refuse right away).
+#[test]
+#[cfg(target_os = "linux")]
+fn timeout_full_backlog() {
+    use std::os::unix::io::AsRawFd;
+    use std::time::Instant;
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("sock");
+    let listener = bind(&path);
+    // Shrink the backlog to the minimum, so it's quick to fill.
+    assert_eq!(0, unsafe { libc::listen(listener.as_raw_fd(), 0) });
+    let connect = unix_stream_timeout(path, Duration::from_millis(100));
+    let mut connections = Vec::new();
+    let start = Instant::now();
+    let err = loop {
+        match connect() {
+            Ok(connection) => connections.push(connection),
+            Err(e) => break e,
+        }
+        assert!(connections.len() < 100, "Backlog never full");
+    };
+    assert_eq!(ErrorKind::TimedOut, err.kind());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
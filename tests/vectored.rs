@@ -0,0 +1,90 @@
+//! Tests of forwarding the vectored IO.
+
+use std::io::{Error, IoSlice, IoSliceMut, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use reopen::{Reopen, SyncReopen, HAS_VECTORED};
+
+/// An FD remembering if its vectored methods got called.
+struct Recorder(Arc<AtomicBool>);
+
+impl Read for Recorder {
+    fn read(&mut self, _: &mut [u8]) -> Result<usize, Error> {
+        Ok(0)
+    }
+
+    fn read_vectored(&mut self, _: &mut [IoSliceMut<'_>]) -> Result<usize, Error> {
+        self.0.store(true, Ordering::Relaxed);
+        Ok(0)
+    }
+}
+
+impl Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        Ok(buf.len())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> Result<usize, Error> {
+        self.0.store(true, Ordering::Relaxed);
+        Ok(bufs.iter().map(|buf| buf.len()).sum())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+fn recording() -> (Reopen<Recorder>, Arc<AtomicBool>) {
+    let called = Arc::new(AtomicBool::new(false));
+    let fd_called = Arc::clone(&called);
+    let reopen = Reopen::new(Box::new(move || Ok(Recorder(Arc::clone(&fd_called))))).unwrap();
+    (reopen, called)
+}
+
+/// Any compiler new enough to build the tests has the vectored IO.
+#[test]
+#[allow(clippy::assertions_on_constants)] // Constant in each build, but that's what we check.
+fn detected() {
+    assert!(HAS_VECTORED);
+}
+
+#[test]
+fn write_forwarded() {
+    let (mut reopen, called) = recording();
+    let written = reopen
+        .write_vectored(&[IoSlice::new(b"hello "), IoSlice::new(b"world")])
+        .unwrap();
+    assert_eq!(11, written);
+    assert_eq!(HAS_VECTORED, called.load(Ordering::Relaxed));
+}
+
+#[test]
+fn read_forwarded() {
+    let (mut reopen, called) = recording();
+    let mut buf = [0; 4];
+    let read = reopen
+        .read_vectored(&mut [IoSliceMut::new(&mut buf)])
+        .unwrap();
+    assert_eq!(0, read);
+    assert_eq!(HAS_VECTORED, called.load(Ordering::Relaxed));
+}
+
+#[test]
+fn sync_forwarded() {
+    let (reopen, called) = recording();
+    let reopen = SyncReopen::new(reopen);
+    let written = (&reopen)
+        .write_vectored(&[IoSlice::new(b"hello "), IoSlice::new(b"world")])
+        .unwrap();
+    assert_eq!(11, written);
+    assert_eq!(HAS_VECTORED, called.load(Ordering::Relaxed));
+
+    called.store(false, Ordering::Relaxed);
+    let mut buf = [0; 4];
+    let read = (&reopen)
+        .read_vectored(&mut [IoSliceMut::new(&mut buf)])
+        .unwrap();
+    assert_eq!(0, read);
+    assert_eq!(HAS_VECTORED, called.load(Ordering::Relaxed));
+}
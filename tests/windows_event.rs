@@ -0,0 +1,29 @@
+//! Tests of reopening on a named event object.
+#![cfg(all(windows, feature = "windows-event"))]
+
+use std::io::Write;
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reopen::test_util::HistorySink;
+use reopen::windows::signal_event;
+use reopen::Reopen;
+
+#[test]
+fn signalled_from_same_process() {
+    let name = format!("reopen-test-{}", process::id());
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    let guard = writer.handle().register_named_event(&name).unwrap();
+
+    signal_event(&name).unwrap();
+    let start = Instant::now();
+    while sink.incarnations() < 2 {
+        assert!(start.elapsed() < Duration::from_secs(5), "Not reopened");
+        thread::sleep(Duration::from_millis(10));
+        writer.flush().unwrap();
+    }
+
+    drop(guard);
+}
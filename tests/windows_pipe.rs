@@ -0,0 +1,52 @@
+//! Tests of the named pipe control channel.
+#![cfg(all(windows, feature = "windows-pipe"))]
+
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use reopen::test_util::HistorySink;
+use reopen::Reopen;
+
+#[test]
+fn reopen_through_pipe() {
+    let name = format!(r"\\.\pipe\reopen-test-{}", process::id());
+    let sink = HistorySink::new();
+    let mut writer = Reopen::new(Box::new(sink.constructor())).unwrap();
+    let guard = writer.handle().serve_named_pipe(&name).unwrap();
+    let done = Arc::new(AtomicBool::new(false));
+    let writing = thread::spawn({
+        let done = Arc::clone(&done);
+        move || {
+            while !done.load(Ordering::Relaxed) {
+                writer.write_all(b"x").unwrap();
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+    });
+
+    let client = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&name)
+        .unwrap();
+    let mut answers = BufReader::new(&client);
+    let mut answer = String::new();
+    (&client).write_all(b"status\nreopen\n").unwrap();
+    answers.read_line(&mut answer).unwrap();
+    assert_eq!("idle\n", answer);
+    answer.clear();
+    answers.read_line(&mut answer).unwrap();
+    assert_eq!("ok\n", answer);
+    assert!(sink.incarnations() >= 2);
+    drop(answers);
+    drop(client);
+
+    done.store(true, Ordering::Relaxed);
+    writing.join().unwrap();
+    drop(guard);
+}
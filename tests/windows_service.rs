@@ -0,0 +1,40 @@
+//! Tests of the windows service control handler.
+#![cfg(all(windows, feature = "windows-service"))]
+
+use reopen::windows::service_control_handler;
+use reopen::Handle;
+use windows_service::service::{ServiceControl, UserEventCode};
+use windows_service::service_control_handler::ServiceControlHandlerResult;
+
+#[test]
+fn reopens_on_code() {
+    let handle = Handle::stub();
+    let handler = service_control_handler(handle.clone(), UserEventCode::from_raw(130).unwrap());
+    let other = ServiceControl::UserEvent(UserEventCode::from_raw(131).unwrap());
+    assert_eq!(
+        ServiceControlHandlerResult::NotImplemented.to_raw(),
+        handler(other).to_raw()
+    );
+    assert_eq!(
+        ServiceControlHandlerResult::NotImplemented.to_raw(),
+        handler(ServiceControl::Stop).to_raw()
+    );
+    assert_eq!(
+        ServiceControlHandlerResult::NoError.to_raw(),
+        handler(ServiceControl::Interrogate).to_raw()
+    );
+    // None of the above requested a reopen.
+    let mut reopen =
+        reopen::Reopen::with_handle(handle, Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+    assert!(!reopen.is_pending());
+    assert_eq!(
+        ServiceControlHandlerResult::NoError.to_raw(),
+        handler(ServiceControl::UserEvent(
+            UserEventCode::from_raw(130).unwrap()
+        ))
+        .to_raw()
+    );
+    assert!(reopen.is_pending());
+    reopen.lock().unwrap();
+    assert!(!reopen.is_pending());
+}
@@ -0,0 +1,37 @@
+//! Tests of watching the path on windows.
+#![cfg(all(windows, feature = "watch"))]
+
+use std::fs;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reopen::Reopen;
+use tempfile::TempDir;
+
+#[test]
+fn reopen_on_rename() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("log");
+    let mut log = Reopen::append_to(&path).unwrap();
+    let _guard = log.handle().watch_path(&path).unwrap();
+    writeln!(log, "first").unwrap();
+
+    let rotated = path.with_extension("1");
+    fs::rename(&path, &rotated).unwrap();
+    let start = Instant::now();
+    while !log.is_pending() {
+        assert!(start.elapsed() < Duration::from_secs(5), "Not noticed");
+        thread::sleep(Duration::from_millis(10));
+    }
+    writeln!(log, "second").unwrap();
+
+    assert_eq!("first\n", fs::read_to_string(&rotated).unwrap());
+    assert_eq!("second\n", fs::read_to_string(&path).unwrap());
+}
+
+#[test]
+fn no_file_name() {
+    let log = Reopen::new(Box::new(|| Ok(Vec::<u8>::new()))).unwrap();
+    assert!(log.handle().watch_path("C:\\").is_err());
+}
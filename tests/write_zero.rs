@@ -0,0 +1,82 @@
+//! Tests for the `reopen_on_write_zero` mode.
+
+use std::io::{Error, ErrorKind, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use reopen::Reopen;
+
+/// A writer that refuses everything in its first incarnation and accepts data in later ones.
+struct Stubborn {
+    refuse: bool,
+    data: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Write for Stubborn {
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if self.refuse {
+            Ok(0)
+        } else {
+            self.data.lock().unwrap().write(buf)
+        }
+    }
+}
+
+fn provide_writer(enable: bool) -> (Reopen<Stubborn>, Arc<AtomicUsize>, Arc<Mutex<Vec<u8>>>) {
+    let opened = Arc::new(AtomicUsize::new(0));
+    let data = Arc::new(Mutex::new(Vec::new()));
+    let writer = Reopen::new(Box::new({
+        let opened = Arc::clone(&opened);
+        let data = Arc::clone(&data);
+        move || {
+            let first = opened.fetch_add(1, Ordering::Relaxed) == 0;
+            Ok(Stubborn {
+                refuse: first,
+                data: Arc::clone(&data),
+            })
+        }
+    }))
+    .unwrap()
+    .reopen_on_write_zero(enable);
+    (writer, opened, data)
+}
+
+#[test]
+fn write_zero_reopens() {
+    let (mut writer, opened, data) = provide_writer(true);
+    assert_eq!(0, writer.write(b"hello").unwrap());
+    assert_eq!(1, opened.load(Ordering::Relaxed));
+    assert_eq!(5, writer.write(b"hello").unwrap());
+    assert_eq!(2, opened.load(Ordering::Relaxed));
+    assert_eq!(b"hello", &data.lock().unwrap()[..]);
+}
+
+#[test]
+fn write_all_zero_reopens() {
+    let (mut writer, opened, data) = provide_writer(true);
+    let err = writer.write_all(b"hello").unwrap_err();
+    assert_eq!(ErrorKind::WriteZero, err.kind());
+    writer.write_all(b"hello").unwrap();
+    assert_eq!(2, opened.load(Ordering::Relaxed));
+    assert_eq!(b"hello", &data.lock().unwrap()[..]);
+}
+
+/// Empty writes legitimately return 0 and must not cause a reopen.
+#[test]
+fn empty_write_no_reopen() {
+    let (mut writer, opened, _) = provide_writer(true);
+    assert_eq!(0, writer.write(b"").unwrap());
+    assert_eq!(0, writer.write(b"").unwrap());
+    assert_eq!(1, opened.load(Ordering::Relaxed));
+}
+
+#[test]
+fn disabled_by_default() {
+    let (mut writer, opened, _) = provide_writer(false);
+    assert_eq!(0, writer.write(b"hello").unwrap());
+    assert_eq!(0, writer.write(b"hello").unwrap());
+    assert_eq!(1, opened.load(Ordering::Relaxed));
+}